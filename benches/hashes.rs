@@ -0,0 +1,125 @@
+// run with RUSTFLAGS="-C target-cpu=native" cargo bench
+//
+// Only proving is measured: the circuit is built and the prover set up by `HashProver`,
+// and the witness populated through the gadget's `HashCircuit::populate`, once per input
+// size, outside the timed closure, which only runs `HashProver::prove_witness`.
+//
+// The prover uses the library's `ProverPacking`, selected with the packed-* features,
+// e.g. `cargo bench --features packed-2x128`.
+
+use binius_core::constraint_system::ValueVec;
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use sha256_example::blake2b::Blake2bHash;
+use sha256_example::hash_circuit::{
+    DEFAULT_LOG_INV_RATE, HashCircuit, HashProver, LOG_INV_RATES, VerifierCache, prove_hash,
+    verify_hash,
+};
+use sha256_example::keccak::Keccak256Hash;
+use sha256_example::sha256::Sha256Hash;
+
+const SIZES: [usize; 3] = [1 << 10, 1 << 16, 1 << 20];
+
+// The prover for messages of `image.len()` bytes and the populated witness for `image`.
+fn prover_and_witness<H: HashCircuit>(
+    image: &[u8],
+    log_inv_rate: usize,
+) -> (HashProver<H>, ValueVec) {
+    let prover = HashProver::<H>::with_log_inv_rate(image.len(), log_inv_rate).unwrap();
+    let witness_vec = prover.populate(image).unwrap();
+    (prover, witness_vec)
+}
+
+// Time proving a clone of `witness_vec`, the clone is made outside the measurement.
+fn bench_prove_witness<H: HashCircuit>(
+    b: &mut criterion::Bencher,
+    prover: &HashProver<H>,
+    witness_vec: &ValueVec,
+) {
+    b.iter_batched(
+        || witness_vec.clone(),
+        |witness_vec| prover.prove_witness(witness_vec).unwrap(),
+        BatchSize::LargeInput,
+    )
+}
+
+fn bench_prove<H: HashCircuit>(c: &mut Criterion) {
+    let mut group = c.benchmark_group(H::NAME);
+    group.sample_size(10);
+    let mut rng = StdRng::seed_from_u64(0);
+
+    for size in SIZES {
+        let mut image = vec![0u8; size];
+        rng.fill(&mut image[..]);
+
+        let (prover, witness_vec) = prover_and_witness::<H>(&image, DEFAULT_LOG_INV_RATE);
+
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(size),
+            &witness_vec,
+            |b, witness_vec| bench_prove_witness(b, &prover, witness_vec),
+        );
+    }
+
+    group.finish();
+}
+
+// Proving time of sha256 over one size at each log_inv_rate the demo binary accepts,
+// with the proof size each one gives printed alongside, since criterion only reports
+// times.
+fn bench_log_inv_rates(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sha256 log_inv_rate");
+    group.sample_size(10);
+    let mut image = vec![0u8; 1 << 16];
+    StdRng::seed_from_u64(0).fill(&mut image[..]);
+
+    for log_inv_rate in LOG_INV_RATES {
+        let (prover, witness_vec) = prover_and_witness::<Sha256Hash>(&image, log_inv_rate);
+        let (proof, _) = prover.prove_witness(witness_vec.clone()).unwrap();
+        println!(
+            "sha256 {} bytes at log_inv_rate {log_inv_rate}: proof size {} bytes",
+            image.len(),
+            proof.len()
+        );
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(log_inv_rate),
+            &witness_vec,
+            |b, witness_vec| bench_prove_witness(b, &prover, witness_vec),
+        );
+    }
+
+    group.finish();
+}
+
+// Verifying the same sha256 proof with a fresh verifier setup each time, as
+// `verify_hash` does, and with the verifier a `VerifierCache` set up on its first use.
+// The difference is the setup time a verification service saves per proof.
+fn bench_verify(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sha256 verify");
+    let mut image = vec![0u8; 1 << 10];
+    StdRng::seed_from_u64(0).fill(&mut image[..]);
+    let (proof, public_words, _) = prove_hash::<Sha256Hash>(&image, false).unwrap();
+
+    group.bench_function("setup per proof", |b| {
+        b.iter(|| verify_hash::<Sha256Hash>(image.len(), proof.clone(), &public_words, false))
+    });
+    let cache = VerifierCache::<Sha256Hash>::new();
+    group.bench_function("cached verifier", |b| {
+        b.iter(|| cache.verify(image.len(), proof.clone(), &public_words))
+    });
+
+    group.finish();
+}
+
+fn hashes(c: &mut Criterion) {
+    bench_prove::<Sha256Hash>(c);
+    bench_prove::<Keccak256Hash>(c);
+    bench_prove::<Blake2bHash>(c);
+    bench_log_inv_rates(c);
+    bench_verify(c);
+}
+
+criterion_group!(benches, hashes);
+criterion_main!(benches);
@@ -0,0 +1,135 @@
+// The prover side of the two-step flow: prove a message and save the proof to disk,
+// then hand the file to whoever verifies it, e.g. with `examples/verify.rs`:
+//
+//   cargo run --release --example prove -- sha256 message.bin proof.bprf
+//   cargo run --release --example verify -- proof.bprf
+//
+// The algo is one of the demo binary's `--algo` names, see `Algo`. The message is either
+// a file or, given a number instead of a path, that many random bytes. Only the proof
+// and the public words, the digest among them, go into the file.
+
+use sha256_example::ProofError;
+use sha256_example::algo::Algo;
+#[cfg(feature = "blake2b")]
+use sha256_example::blake2b::Blake2bHash;
+#[cfg(feature = "blake2s")]
+use sha256_example::blake2s::Blake2sHash;
+#[cfg(feature = "blake3")]
+use sha256_example::blake3::Blake3Hash;
+use sha256_example::hash_circuit::{HashCircuit, prove_hash};
+use sha256_example::io::save_proof;
+#[cfg(feature = "keccak")]
+use sha256_example::keccak::Keccak256Hash;
+#[cfg(feature = "legacy")]
+use sha256_example::md5::Md5Hash;
+#[cfg(all(feature = "sha256", feature = "keccak"))]
+use sha256_example::multi_hash::Sha256KeccakHash;
+#[cfg(feature = "ripemd")]
+use sha256_example::ripemd::{Hash160Hash, Ripemd160Hash};
+#[cfg(feature = "sha2-truncated")]
+use sha256_example::sha2_truncated::{Sha224Hash, Sha384Hash};
+#[cfg(feature = "sha3")]
+use sha256_example::sha3::{Keccak512Hash, Sha3_256Hash};
+#[cfg(feature = "sha256")]
+use sha256_example::sha256::{Sha256Hash, Sha256dHash};
+#[cfg(feature = "sha512")]
+use sha256_example::sha512::Sha512Hash;
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+// Prove `msg` with circuit `H` and write the proof to `out`, returning the prove time.
+fn prove_and_save<H: HashCircuit>(msg: &[u8], out: &str) -> Result<u128, ProofError> {
+    // The circuit is built for exactly `msg.len()` bytes, and `save_proof` records that
+    // length so the verifier can rebuild the same constraint system.
+    let (proof, public_words, timings) = prove_hash::<H>(msg, false)?;
+    save_proof::<H>(out, msg.len(), &proof, &public_words)?;
+    Ok(timings.prove_ms)
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [algo_name, input, out] = &args[..] else {
+        eprintln!("usage: prove <algo> <input file | random size in bytes> <proof file>");
+        std::process::exit(2);
+    };
+
+    // a number is a size for a random message, anything else a path to read
+    let msg = match input.parse::<usize>() {
+        Ok(n) => {
+            let mut msg = vec![0u8; n];
+            StdRng::seed_from_u64(0).fill(&mut msg[..]);
+            msg
+        }
+        Err(_) => std::fs::read(input).unwrap_or_else(|e| {
+            eprintln!("cannot read {input}: {e}");
+            std::process::exit(1);
+        }),
+    };
+
+    // the algo names of the demo binary's `--algo`
+    let algo = match Algo::from_name(algo_name) {
+        Some(algo) if algo.enabled() => algo,
+        Some(algo) => {
+            eprintln!(
+                "algo '{algo_name}' is not compiled in, rebuild with --features {}",
+                algo.feature()
+            );
+            std::process::exit(2);
+        }
+        None => {
+            eprintln!("unknown algo '{algo_name}'");
+            std::process::exit(2);
+        }
+    };
+
+    let result = match algo {
+        #[cfg(feature = "sha256")]
+        Algo::Sha256 => prove_and_save::<Sha256Hash>(&msg, out),
+        #[cfg(feature = "sha256")]
+        Algo::Sha256d => prove_and_save::<Sha256dHash>(&msg, out),
+        #[cfg(feature = "sha512")]
+        Algo::Sha512 => prove_and_save::<Sha512Hash>(&msg, out),
+        #[cfg(feature = "sha2-truncated")]
+        Algo::Sha224 => prove_and_save::<Sha224Hash>(&msg, out),
+        #[cfg(feature = "sha2-truncated")]
+        Algo::Sha384 => prove_and_save::<Sha384Hash>(&msg, out),
+        #[cfg(feature = "blake2b")]
+        Algo::Blake2b => prove_and_save::<Blake2bHash>(&msg, out),
+        #[cfg(feature = "blake2s")]
+        Algo::Blake2s => prove_and_save::<Blake2sHash>(&msg, out),
+        #[cfg(feature = "blake3")]
+        Algo::Blake3 => prove_and_save::<Blake3Hash>(&msg, out),
+        #[cfg(feature = "keccak")]
+        Algo::Keccak => prove_and_save::<Keccak256Hash>(&msg, out),
+        #[cfg(all(feature = "sha256", feature = "keccak"))]
+        Algo::Sha256Keccak => prove_and_save::<Sha256KeccakHash>(&msg, out),
+        #[cfg(feature = "sha3")]
+        Algo::Sha3_256 => prove_and_save::<Sha3_256Hash>(&msg, out),
+        #[cfg(feature = "sha3")]
+        Algo::Keccak512 => prove_and_save::<Keccak512Hash>(&msg, out),
+        #[cfg(feature = "ripemd")]
+        Algo::Ripemd160 => prove_and_save::<Ripemd160Hash>(&msg, out),
+        #[cfg(feature = "ripemd")]
+        Algo::Hash160 => prove_and_save::<Hash160Hash>(&msg, out),
+        #[cfg(feature = "legacy")]
+        Algo::Md5 => prove_and_save::<Md5Hash>(&msg, out),
+        // a proof file names a `HashCircuit`, the lattice circuit is not one
+        Algo::Lattice => Err(ProofError::InvalidParameter(
+            "lattice proofs cannot be saved to a proof file".into(),
+        )),
+        // only algos that are compiled in get past `enabled` above
+        #[allow(unreachable_patterns)]
+        _ => unreachable!("{} is not compiled in", algo.name()),
+    };
+
+    match result {
+        Ok(prove_ms) => println!(
+            "wrote {algo_name} proof for {} bytes to {out}, proved in {prove_ms}ms",
+            msg.len()
+        ),
+        Err(e) => {
+            eprintln!("{algo_name}: {e}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,42 @@
+// The verifier side of a proof made elsewhere, e.g. by `examples/prove.rs`:
+//
+//   cargo run --release --example verify -- proof.bprf
+//
+// It needs nothing but the proof file. The file names the circuit and the message
+// length it was built for, which is all the verifier needs to rebuild the constraint
+// system; the message itself never leaves the prover.
+
+use std::time::Instant;
+
+use sha256_example::io::{load_proof, verify_from_file};
+
+fn main() {
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("usage: verify <proof file>");
+        std::process::exit(2);
+    };
+
+    // Only read the header here, to report which circuit the proof is for. A file that
+    // does not parse is rejected the same way by `verify_from_file` below.
+    let algo = match load_proof(&path) {
+        Ok(file) => format!("{} for {} bytes", file.algo, file.max_len),
+        Err(e) => {
+            eprintln!("FAIL: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    // Rebuild the circuit, set up the verifier and check the proof against the public
+    // words stored with it. The digest the proof commits to is the last of those words.
+    let timer = Instant::now();
+    let result = verify_from_file(&path);
+    let verify_ms = timer.elapsed().as_millis();
+
+    match result {
+        Ok(()) => println!("PASS: {algo}, verified in {verify_ms}ms"),
+        Err(e) => {
+            println!("FAIL: {algo}: {e}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sha256_example::blake2s::Blake2sHash;
+
+mod common;
+
+fuzz_target!(|data: &[u8]| common::check::<Blake2sHash>(data));
@@ -0,0 +1,33 @@
+// Shared by every fuzz target: one hash circuit per target, the input the message.
+// Run one with `cargo +nightly fuzz run sha256` from the crate root, the target names
+// are the file names in this directory.
+
+use sha256_example::hash_circuit::{HashCircuit, digest_hex, digest_only};
+
+// Circuits grow with the message and each input builds a fresh one, longer messages
+// only slow the fuzzer down without reaching new padding cases.
+const MAX_LEN: usize = 512;
+
+// Build the circuit for `data`, populate the message and digest wires, derive the rest
+// with `populate_wire_witness`, check the constraints and read the digest back from the
+// public words. Panics, failing the input, when the constraints do not hold or the
+// circuit's digest differs from the reference.
+pub fn check<H: HashCircuit>(data: &[u8]) {
+    let msg = &data[..data.len().min(MAX_LEN)];
+    match digest_only::<H>(msg) {
+        Ok(digest) => assert_eq!(
+            digest,
+            H::reference_digest(msg),
+            "{} on {} bytes {}",
+            H::NAME,
+            msg.len(),
+            digest_hex(msg)
+        ),
+        Err(e) => panic!(
+            "{} on {} bytes {}: {e}",
+            H::NAME,
+            msg.len(),
+            digest_hex(msg)
+        ),
+    }
+}
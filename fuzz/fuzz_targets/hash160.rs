@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sha256_example::ripemd::Hash160Hash;
+
+mod common;
+
+fuzz_target!(|data: &[u8]| common::check::<Hash160Hash>(data));
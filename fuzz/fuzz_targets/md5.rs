@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sha256_example::md5::Md5Hash;
+
+mod common;
+
+fuzz_target!(|data: &[u8]| common::check::<Md5Hash>(data));
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sha256_example::multi_hash::Sha256KeccakHash;
+
+mod common;
+
+fuzz_target!(|data: &[u8]| common::check::<Sha256KeccakHash>(data));
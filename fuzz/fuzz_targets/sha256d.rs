@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sha256_example::sha256::Sha256dHash;
+
+mod common;
+
+fuzz_target!(|data: &[u8]| common::check::<Sha256dHash>(data));
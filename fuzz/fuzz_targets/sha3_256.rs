@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sha256_example::sha3::Sha3_256Hash;
+
+mod common;
+
+fuzz_target!(|data: &[u8]| common::check::<Sha3_256Hash>(data));
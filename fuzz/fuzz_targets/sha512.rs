@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sha256_example::sha512::Sha512Hash;
+
+mod common;
+
+fuzz_target!(|data: &[u8]| common::check::<Sha512Hash>(data));
@@ -0,0 +1,110 @@
+//! The algorithms the demo binary and the examples select by name, with the cargo
+//! features that compile each one in.
+
+/// Algorithms selectable by name, e.g. with the demo binary's `--algo`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algo {
+    Sha256,
+    Sha256d,
+    Sha512,
+    Sha224,
+    Sha384,
+    Keccak,
+    Sha256Keccak,
+    Sha3_256,
+    Keccak512,
+    Blake2b,
+    Blake2s,
+    Blake3,
+    Ripemd160,
+    Hash160,
+    Md5,
+    Lattice,
+}
+
+impl Algo {
+    pub const ALL: [Algo; 16] = [
+        Algo::Lattice,
+        Algo::Sha256,
+        Algo::Sha256d,
+        Algo::Sha512,
+        Algo::Sha224,
+        Algo::Sha384,
+        Algo::Blake2b,
+        Algo::Blake2s,
+        Algo::Blake3,
+        Algo::Keccak,
+        Algo::Sha256Keccak,
+        Algo::Sha3_256,
+        Algo::Keccak512,
+        Algo::Ripemd160,
+        Algo::Hash160,
+        Algo::Md5,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Algo::Sha256 => "sha256",
+            Algo::Sha256d => "sha256d",
+            Algo::Sha512 => "sha512",
+            Algo::Sha224 => "sha224",
+            Algo::Sha384 => "sha384",
+            Algo::Keccak => "keccak",
+            Algo::Sha256Keccak => "sha256+keccak",
+            Algo::Sha3_256 => "sha3-256",
+            Algo::Keccak512 => "keccak512",
+            Algo::Blake2b => "blake2b",
+            Algo::Blake2s => "blake2s",
+            Algo::Blake3 => "blake3",
+            Algo::Ripemd160 => "ripemd160",
+            Algo::Hash160 => "hash160",
+            Algo::Md5 => "md5",
+            Algo::Lattice => "lattice",
+        }
+    }
+
+    /// The algorithm called `name`, whether or not it is compiled in.
+    pub fn from_name(name: &str) -> Option<Algo> {
+        Algo::ALL.into_iter().find(|a| a.name() == name)
+    }
+
+    /// The cargo features that compile this algorithm in, comma separated.
+    pub fn feature(self) -> &'static str {
+        match self {
+            Algo::Sha256 | Algo::Sha256d => "sha256",
+            Algo::Sha512 => "sha512",
+            Algo::Sha224 | Algo::Sha384 => "sha2-truncated",
+            Algo::Keccak => "keccak",
+            Algo::Sha256Keccak => "sha256,keccak",
+            Algo::Sha3_256 | Algo::Keccak512 => "sha3",
+            Algo::Blake2b => "blake2b",
+            Algo::Blake2s => "blake2s",
+            Algo::Blake3 => "blake3",
+            Algo::Ripemd160 | Algo::Hash160 => "ripemd",
+            Algo::Md5 => "legacy",
+            Algo::Lattice => "lattice",
+        }
+    }
+
+    pub fn enabled(self) -> bool {
+        match self {
+            Algo::Sha256 | Algo::Sha256d => cfg!(feature = "sha256"),
+            Algo::Sha512 => cfg!(feature = "sha512"),
+            Algo::Sha224 | Algo::Sha384 => cfg!(feature = "sha2-truncated"),
+            Algo::Keccak => cfg!(feature = "keccak"),
+            Algo::Sha256Keccak => cfg!(all(feature = "sha256", feature = "keccak")),
+            Algo::Sha3_256 | Algo::Keccak512 => cfg!(feature = "sha3"),
+            Algo::Blake2b => cfg!(feature = "blake2b"),
+            Algo::Blake2s => cfg!(feature = "blake2s"),
+            Algo::Blake3 => cfg!(feature = "blake3"),
+            Algo::Ripemd160 | Algo::Hash160 => cfg!(feature = "ripemd"),
+            Algo::Md5 => cfg!(feature = "legacy"),
+            Algo::Lattice => cfg!(feature = "lattice"),
+        }
+    }
+
+    /// The algorithms compiled into this build, in `ALL` order.
+    pub fn enabled_algos() -> Vec<Algo> {
+        Algo::ALL.into_iter().filter(|a| a.enabled()).collect()
+    }
+}
@@ -1,77 +1,507 @@
 use binius_circuits::blake2b::{Blake2bCircuit, blake2b};
-use binius_core::{verify::verify_constraints};
-use binius_frontend::CircuitBuilder;
+use binius_core::{constraint_system::ConstraintSystem, word::Word};
+use binius_frontend::{CircuitBuilder, Wire, WitnessFiller};
+use blake2::Blake2bVarCore;
+use blake2::digest::core_api::{Buffer, UpdateCore, VariableOutputCore};
 
-use binius_prover::{
-    OptimalPackedB128, Prover, hash::parallel_compression::ParallelCompressionAdaptor,
-};
-use binius_transcript::{ProverTranscript, VerifierTranscript};
-use binius_verifier::{
-    Verifier,
-    config::StdChallenger,
-    hash::{StdCompression, StdDigest},
+use crate::config::RunConfig;
+use crate::error::ProofError;
+use crate::hash_circuit::{
+    HashCircuit, WordEndian, build_cs, check_exact_len, digest_only_words, digest_words,
+    prove_and_verify_circuit, public_digest, run_hash_circuit_with_config, verify_hash,
+    words_to_digest,
 };
+use crate::timings::CircuitTimings;
 
-use std::time::Instant;
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
 
-pub fn blake2b_circuit(image_bytes: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Proof for Blake2b circuit:");
-    // Start timer for setup
-    //let setup_timer = Instant::now();
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
 
-    // New Circuit
-    let builder = CircuitBuilder::new();
+const BLOCK_LEN: usize = 128;
+pub(crate) const MAX_OUT_LEN: usize = 64;
+pub(crate) const MAX_KEY_LEN: usize = 64;
 
-    // Create Circuit
-    let max_msg_len_bytes = image_bytes.len();
-    let blake2b_circuit = Blake2bCircuit::new_with_length(&builder, max_msg_len_bytes);
-    let circuit = builder.build();
+pub struct Blake2bHash {
+    blake2b: Blake2bCircuit,
+    len: usize,
+}
 
-    // Create witness
-    let mut witness = circuit.new_witness_filler();
+impl HashCircuit for Blake2bHash {
+    const NAME: &'static str = "blake2b";
+
+    fn build(builder: &CircuitBuilder, max_len: usize) -> Self {
+        Self {
+            blake2b: Blake2bCircuit::new_with_length(builder, max_len),
+            len: max_len,
+        }
+    }
+
+    fn max_len(&self) -> usize {
+        self.len
+    }
+
+    // the message length is fixed when the circuit is built
+    fn check_len(&self, msg: &[u8]) -> Result<(), ProofError> {
+        check_exact_len(msg, self.len)
+    }
+
+    fn populate_with_digest(&self, witness: &mut WitnessFiller, msg: &[u8], digest: &[u8]) {
+        let mut expected_digest = [0u8; 64];
+        expected_digest.copy_from_slice(digest);
+
+        self.blake2b.populate_message(witness, msg);
+        self.blake2b.populate_digest(witness, &expected_digest);
+    }
+
+    fn digest_from_public(&self, public: &[Word]) -> Vec<u8> {
+        public_digest(public, MAX_OUT_LEN, DIGEST_ENDIAN)
+    }
+
+    fn reference_digest(msg: &[u8]) -> Vec<u8> {
+        blake2b(msg, 64)
+    }
+}
+
+fn add64(b: &CircuitBuilder, x: Wire, y: Wire) -> Wire {
+    let zero = b.add_constant_64(0);
+    b.iadd_cin_cout(x, y, zero).0
+}
+
+// G mixing function on state words a, b, c, d with message words x, y.
+fn g(b: &CircuitBuilder, v: &mut [Wire; 16], i: [usize; 4], x: Wire, y: Wire) {
+    let [a, bb, c, d] = i;
+    v[a] = add64(b, add64(b, v[a], v[bb]), x);
+    v[d] = b.rotr(b.bxor(v[d], v[a]), 32);
+    v[c] = add64(b, v[c], v[d]);
+    v[bb] = b.rotr(b.bxor(v[bb], v[c]), 24);
+    v[a] = add64(b, add64(b, v[a], v[bb]), y);
+    v[d] = b.rotr(b.bxor(v[d], v[a]), 16);
+    v[c] = add64(b, v[c], v[d]);
+    v[bb] = b.rotr(b.bxor(v[bb], v[c]), 63);
+}
+
+// Blake2b compression of one block into `h`. The byte counter `t` and the final-block
+// flag are known when the circuit is built, so they enter as constants.
+fn compress(b: &CircuitBuilder, h: &mut [Wire; 8], m: &[Wire; 16], t: u128, last: bool) {
+    let mut v: [Wire; 16] = core::array::from_fn(|i| match i {
+        0..8 => h[i],
+        12 => b.add_constant_64(IV[4] ^ t as u64),
+        13 => b.add_constant_64(IV[5] ^ (t >> 64) as u64),
+        14 if last => b.add_constant_64(!IV[6]),
+        _ => b.add_constant_64(IV[i - 8]),
+    });
+
+    for s in SIGMA.iter().cycle().take(12) {
+        g(b, &mut v, [0, 4, 8, 12], m[s[0]], m[s[1]]);
+        g(b, &mut v, [1, 5, 9, 13], m[s[2]], m[s[3]]);
+        g(b, &mut v, [2, 6, 10, 14], m[s[4]], m[s[5]]);
+        g(b, &mut v, [3, 7, 11, 15], m[s[6]], m[s[7]]);
+        g(b, &mut v, [0, 5, 10, 15], m[s[8]], m[s[9]]);
+        g(b, &mut v, [1, 6, 11, 12], m[s[10]], m[s[11]]);
+        g(b, &mut v, [2, 7, 8, 13], m[s[12]], m[s[13]]);
+        g(b, &mut v, [3, 4, 9, 14], m[s[14]], m[s[15]]);
+    }
+
+    for i in 0..8 {
+        h[i] = b.bxor(h[i], b.bxor(v[i], v[i + 8]));
+    }
+}
+
+/// Blake2b with a configurable output length.
+///
+/// `Blake2bCircuit` hardcodes the 64-byte output in its parameter block, and a shorter
+/// Blake2b digest is not a truncation of the 64-byte one, so this gadget builds the
+/// compression function itself. The message length is fixed when the circuit is built.
+///
+/// Message wires hold 8 little-endian bytes each. The digest is exposed as
+/// `out_len.div_ceil(8)` inout words in the same packing, with the unused high bytes
+/// of the last word zero.
+///
+/// Built with `new_keyed`, it computes the keyed Blake2b MAC instead. The key length is
+/// part of the parameter block, so it is fixed at build time like the message length
+/// and is public; the key bytes are private witness words in the same packing. Blake2b
+/// keys the hash by compressing the key, zero padded to a full 128-byte block, ahead of
+/// the message, with the byte counter running over that block too. The key block is
+/// therefore the first block of the circuit, made of the key wires and zero constants,
+/// and for an empty message it is also the last one.
+pub struct Blake2bOutHash {
+    len: usize,
+    out_len: usize,
+    key_len: usize,
+    key: Vec<Wire>,
+    message: Vec<Wire>,
+    digest: Vec<Wire>,
+}
+
+impl Blake2bOutHash {
+    /// Build the gadget for `len`-byte messages and an `out_len`-byte digest, where
+    /// `out_len` is in `1..=64`.
+    pub fn new(builder: &CircuitBuilder, len: usize, out_len: usize) -> Self {
+        Self::new_keyed(builder, len, out_len, 0)
+    }
+
+    /// Like `new`, keyed with a `key_len`-byte key, where `key_len` is in `0..=64` and 0
+    /// is the unkeyed hash.
+    pub fn new_keyed(builder: &CircuitBuilder, len: usize, out_len: usize, key_len: usize) -> Self {
+        assert!((1..=MAX_OUT_LEN).contains(&out_len));
+        assert!(key_len <= MAX_KEY_LEN);
+
+        let key: Vec<_> = (0..key_len.div_ceil(8))
+            .map(|_| builder.add_witness())
+            .collect();
+        let message: Vec<_> = (0..len.div_ceil(8))
+            .map(|_| builder.add_witness())
+            .collect();
+        let digest: Vec<_> = (0..out_len.div_ceil(8))
+            .map(|_| builder.add_inout())
+            .collect();
+
+        // parameter block: digest length, key length, fanout and depth 1
+        let mut h: [Wire; 8] = core::array::from_fn(|i| builder.add_constant_64(IV[i]));
+        h[0] =
+            builder.add_constant_64(IV[0] ^ 0x0101_0000 ^ ((key_len as u64) << 8) ^ out_len as u64);
+
+        let zero = builder.add_constant_64(0);
+        // the key block, if any, then the message
+        let key_block_len = if key_len > 0 { BLOCK_LEN } else { 0 };
+        let input: Vec<Wire> = (0..key_block_len / 8)
+            .map(|k| key.get(k).copied().unwrap_or(zero))
+            .chain(message.iter().copied())
+            .collect();
+        let total_len = key_block_len + len;
+
+        // the empty message still compresses one empty block
+        let n_blocks = total_len.div_ceil(BLOCK_LEN).max(1);
+        for j in 0..n_blocks {
+            let block: [Wire; 16] =
+                core::array::from_fn(|k| input.get(j * 16 + k).copied().unwrap_or(zero));
+            let last = j == n_blocks - 1;
+            let t = if last { total_len } else { (j + 1) * BLOCK_LEN };
+            compress(builder, &mut h, &block, t as u128, last);
+        }
+
+        for (i, &d) in digest.iter().enumerate() {
+            let n_bytes = (out_len - 8 * i).min(8);
+            let out = if n_bytes == 8 {
+                h[i]
+            } else {
+                builder.band(h[i], builder.add_constant_64((1 << (8 * n_bytes)) - 1))
+            };
+            builder.assert_eq(format!("blake2b digest[{i}]"), out, d);
+        }
+
+        Self {
+            len,
+            out_len,
+            key_len,
+            key,
+            message,
+            digest,
+        }
+    }
+
+    pub fn populate_key(&self, witness: &mut WitnessFiller, key: &[u8]) {
+        debug_assert_eq!(key.len(), self.key_len);
+        for (i, &w) in self.key.iter().enumerate() {
+            witness[w] = Word(pack_le(key, 8 * i));
+        }
+    }
 
-    let expected_digest_vec = blake2b(&image_bytes, 64);
-    let mut expected_digest = [0u8; 64];
-    expected_digest.copy_from_slice(&expected_digest_vec);
+    pub fn populate_message(&self, witness: &mut WitnessFiller, msg: &[u8]) {
+        debug_assert!(msg.len() <= self.len);
+        for (i, &w) in self.message.iter().enumerate() {
+            witness[w] = Word(pack_le(msg, 8 * i));
+        }
+    }
 
-    blake2b_circuit.populate_message(&mut witness, &image_bytes);
-    blake2b_circuit.populate_digest(&mut witness, &expected_digest);
+    pub fn populate_digest(&self, witness: &mut WitnessFiller, digest: &[u8]) {
+        debug_assert_eq!(digest.len(), self.out_len);
+        for (i, &w) in self.digest.iter().enumerate() {
+            witness[w] = Word(pack_le(digest, 8 * i));
+        }
+    }
+}
+
+// Little-endian u64 from the 8 bytes at `offset`, zero padded past the end.
+fn pack_le(bytes: &[u8], offset: usize) -> u64 {
+    let mut word = [0u8; 8];
+    for (k, byte) in word.iter_mut().enumerate() {
+        *byte = bytes.get(offset + k).copied().unwrap_or(0);
+    }
+    u64::from_le_bytes(word)
+}
+
+impl HashCircuit for Blake2bOutHash {
+    // distinct from `Blake2bHash`, whose circuit is a different gadget, so that a proof
+    // file names the circuit that verifies it. This is the 64-byte unkeyed circuit of
+    // `build`, other parameters are saved with `io::save_blake2b_out_proof`
+    const NAME: &'static str = "blake2b-out";
+
+    fn build(builder: &CircuitBuilder, max_len: usize) -> Self {
+        Self::new(builder, max_len, MAX_OUT_LEN)
+    }
+
+    fn max_len(&self) -> usize {
+        self.len
+    }
+
+    // the reference digest has to be computed with this circuit's output length
+    fn expected_digest(&self, msg: &[u8]) -> Vec<u8> {
+        blake2b(msg, self.out_len)
+    }
+
+    // the message length is fixed when the circuit is built
+    fn check_len(&self, msg: &[u8]) -> Result<(), ProofError> {
+        check_exact_len(msg, self.len)
+    }
+
+    fn populate_with_digest(&self, witness: &mut WitnessFiller, msg: &[u8], digest: &[u8]) {
+        self.populate_message(witness, msg);
+        self.populate_digest(witness, digest);
+    }
+
+    fn digest_from_public(&self, public: &[Word]) -> Vec<u8> {
+        public_digest(public, self.out_len, DIGEST_ENDIAN)
+    }
+
+    fn reference_digest(msg: &[u8]) -> Vec<u8> {
+        blake2b(msg, MAX_OUT_LEN)
+    }
+}
+
+/// Byte order of the Blake2b digest words: BLAKE2 stores its state words little-endian,
+/// so digest byte `8i` is the least significant byte of digest word `i`.
+pub const DIGEST_ENDIAN: WordEndian = WordEndian::Little;
+
+/// The public words of a 64-byte Blake2b proof, with the digest at its known position.
+///
+/// Use this instead of indexing the raw words, whose leading entries are the circuit's
+/// constants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Blake2bPublicInputs {
+    words: Vec<Word>,
+}
+
+impl Blake2bPublicInputs {
+    /// Wrap the public words of a proof, e.g. as returned by `prove_hash::<Blake2bHash>`.
+    pub fn from_words(words: Vec<Word>) -> Result<Self, ProofError> {
+        digest_words(&words, 8)?;
+        Ok(Self { words })
+    }
 
-    circuit.populate_wire_witness(&mut witness)?;
+    /// The 64-byte digest, the last 8 public words, each little-endian.
+    pub fn digest(&self) -> [u8; 64] {
+        let words = digest_words(&self.words, 8).expect("checked in from_words");
+        words_to_digest(words, 64, DIGEST_ENDIAN)
+            .try_into()
+            .expect("8 words hold 64 bytes")
+    }
 
-    let cs = circuit.constraint_system();
-    let witness_vec = witness.into_value_vec();
-    verify_constraints(cs, &witness_vec)?;
-    println!("✓ constraints verified");
+    /// All public words, as passed to the verifier.
+    pub fn words(&self) -> &[Word] {
+        &self.words
+    }
 
-    // prover / verifier
-    let compression = ParallelCompressionAdaptor::new(StdCompression::default());
-    let verifier = Verifier::<StdDigest, _>::setup(cs.clone(), 1, StdCompression::default())?;
-    let prover = Prover::<OptimalPackedB128, _, StdDigest>::setup(verifier.clone(), compression)?;
+    pub fn into_words(self) -> Vec<Word> {
+        self.words
+    }
+}
+
+/// The 64-byte Blake2b digest of `image_bytes` read from the public words of the
+/// checked circuit witness, without proving, see `hash_circuit::digest_only_words`.
+pub fn digest_only_blake2b(image_bytes: &[u8]) -> Result<[u8; 64], ProofError> {
+    let words = digest_only_words::<Blake2bHash>(image_bytes)?;
+    Ok(Blake2bPublicInputs::from_words(words)?.digest())
+}
+
+/// The 64-byte Blake2b constraint system for messages of up to `max_len` bytes, see
+/// `hash_circuit::build_cs`.
+pub fn build_cs_blake2b(max_len: usize) -> ConstraintSystem {
+    build_cs::<Blake2bHash>(max_len)
+}
+
+/// Verify a proof of a 64-byte Blake2b digest from its public words alone, without the
+/// witness or any prover setup. `max_len` is the message length the proof was made for.
+pub fn verify_blake2b(
+    max_len: usize,
+    proof: Vec<u8>,
+    public_words: &[Word],
+) -> Result<(), ProofError> {
+    verify_hash::<Blake2bHash>(max_len, proof, public_words, false).map(|_| ())
+}
+
+/// Prove an `out_len`-byte Blake2b digest of `image_bytes`, `out_len` must be in `1..=64`.
+///
+/// The full 64-byte digest uses the upstream `Blake2bCircuit`, shorter digests use
+/// `Blake2bOutHash`.
+pub fn blake2b_circuit(
+    image_bytes: &[u8],
+    out_len: usize,
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    if !(1..=MAX_OUT_LEN).contains(&out_len) {
+        return Err(ProofError::InvalidParameter(format!(
+            "blake2b output length must be between 1 and {MAX_OUT_LEN} bytes, got {out_len}"
+        )));
+    }
 
-    let challenger = StdChallenger::default();
-    let mut prover_transcript = ProverTranscript::new(challenger.clone());
-    let public_words = witness_vec.public().to_vec();
+    if out_len == MAX_OUT_LEN {
+        run_hash_circuit_with_config(image_bytes, config, Blake2bHash::build)
+    } else {
+        run_hash_circuit_with_config(image_bytes, config, |builder, len| {
+            Blake2bOutHash::new(builder, len, out_len)
+        })
+    }
+}
+
+/// Keyed Blake2b of `msg`, an `out_len`-byte MAC under a key of up to 64 bytes, computed
+/// by the `blake2` crate. An empty key gives the unkeyed hash.
+pub fn reference_keyed(key: &[u8], msg: &[u8], out_len: usize) -> Vec<u8> {
+    let mut core = Blake2bVarCore::new_with_params(&[], &[], key.len(), out_len);
+    let mut buffer = Buffer::<Blake2bVarCore>::default();
+    // the lazy buffer holds back the last full block, so a key block with nothing after
+    // it is compressed as the final block
+    if !key.is_empty() {
+        let mut key_block = [0u8; BLOCK_LEN];
+        key_block[..key.len()].copy_from_slice(key);
+        buffer.digest_blocks(&key_block, |blocks| core.update_blocks(blocks));
+    }
+    buffer.digest_blocks(msg, |blocks| core.update_blocks(blocks));
+    let mut out = Default::default();
+    core.finalize_variable_core(&mut buffer, &mut out);
+    out[..out_len].to_vec()
+}
+
+/// Prove an `out_len`-byte keyed Blake2b MAC of `message` under `key`, with the key and
+/// message private and only the MAC public. The key must be at most 64 bytes and
+/// `out_len` in `1..=64`.
+///
+/// Blake2b's native keying needs a single pass over the key block and the message,
+/// where HMAC hashes twice, see `Blake2bOutHash` for how the key block is wired. The key
+/// length is public, as it is fixed in the circuit.
+pub fn blake2b_keyed_circuit(
+    key: &[u8],
+    message: &[u8],
+    out_len: usize,
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    if key.len() > MAX_KEY_LEN {
+        return Err(ProofError::InvalidParameter(format!(
+            "blake2b keys must be at most {MAX_KEY_LEN} bytes, got {}",
+            key.len()
+        )));
+    }
+    if !(1..=MAX_OUT_LEN).contains(&out_len) {
+        return Err(ProofError::InvalidParameter(format!(
+            "blake2b output length must be between 1 and {MAX_OUT_LEN} bytes, got {out_len}"
+        )));
+    }
 
-    //println!("Setup time {}ms", setup_timer.elapsed().as_millis());
+    if config.print_progress() {
+        println!("Proof for keyed blake2b circuit: ");
+    }
 
-    let prove_timer = Instant::now();
+    let builder = CircuitBuilder::new();
+    let hash = Blake2bOutHash::new_keyed(&builder, message.len(), out_len, key.len());
+    let circuit = builder.build();
 
-    prover.prove(witness_vec, &mut prover_transcript)?;
-    let proof = prover_transcript.finalize();
+    let mut witness = circuit.new_witness_filler();
+    hash.populate_key(&mut witness, key);
+    hash.populate_message(&mut witness, message);
+    hash.populate_digest(&mut witness, &reference_keyed(key, message, out_len));
+    circuit
+        .populate_wire_witness(&mut witness)
+        .map_err(|e| ProofError::from(e).in_circuit(Blake2bOutHash::NAME, message.len()))?;
 
-    println!("Proof time {}ms", prove_timer.elapsed().as_millis());
+    prove_and_verify_circuit(&circuit, witness.into_value_vec(), config)
+        .map_err(|e| e.in_circuit(Blake2bOutHash::NAME, message.len()))
+}
 
-    let mut verifier_transcript = VerifierTranscript::new(challenger, proof);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use binius_core::verify::verify_constraints;
 
-    let verify_timer = Instant::now();
+    #[test]
+    fn public_inputs_digest_matches_reference() {
+        let msg = b"abc";
+        let (_, public_words, _) =
+            crate::hash_circuit::prove_hash::<Blake2bHash>(msg, false).unwrap();
+        let public = Blake2bPublicInputs::from_words(public_words.clone()).unwrap();
+        assert_eq!(public.digest().to_vec(), Blake2bHash::reference_digest(msg));
+        assert_eq!(public.words(), &public_words[..]);
+    }
 
-    verifier.verify(&public_words, &mut verifier_transcript)?;
-    verifier_transcript.finalize()?;
+    #[test]
+    fn keyed_reference_matches_known_answers() {
+        let key: Vec<u8> = (0..64).collect();
+        assert_eq!(
+            hex::encode(reference_keyed(&key, b"abc", 64)),
+            "06bbc3dedf13a31139498655251b7588ccd3bb5aaa071b2d44d8e0a04095579e\
+             d590fbfdcf941f4370ce5ce623624e7a76d33e7a8109dcda9b57d72f8f8efa51"
+        );
+        assert_eq!(
+            hex::encode(reference_keyed(b"key", b"", 32)),
+            "e65edfce5a36261cd824cb0f0da736b1109dcf20d2b831d598f337bb3552a3e4"
+        );
+        assert_eq!(reference_keyed(b"", b"abc", 64), blake2b(b"abc", 64));
+    }
 
-    println!("Verify time {}ms", verify_timer.elapsed().as_millis());
+    #[test]
+    fn keyed_digest_matches_reference() {
+        let key: Vec<u8> = (0..64).map(|i| 0xa0 ^ i).collect();
+        let msg: Vec<u8> = (0..129).collect();
+        // an empty message leaves the key block as the last block
+        for key_len in [0, 1, 64] {
+            for len in [0, 128, 129] {
+                for out_len in [20, 64] {
+                    let builder = CircuitBuilder::new();
+                    let hash = Blake2bOutHash::new_keyed(&builder, len, out_len, key_len);
+                    let circuit = builder.build();
 
-    println!("✓ proof successfully verified");
+                    let mut witness = circuit.new_witness_filler();
+                    hash.populate_key(&mut witness, &key[..key_len]);
+                    hash.populate_message(&mut witness, &msg[..len]);
+                    hash.populate_digest(
+                        &mut witness,
+                        &reference_keyed(&key[..key_len], &msg[..len], out_len),
+                    );
+                    let case = format!("key_len {key_len}, len {len}, out_len {out_len}");
+                    circuit
+                        .populate_wire_witness(&mut witness)
+                        .unwrap_or_else(|e| panic!("{case}: {e:?}"));
+                    verify_constraints(circuit.constraint_system(), &witness.into_value_vec())
+                        .unwrap_or_else(|e| panic!("{case}: {e:?}"));
+                }
+            }
+        }
+    }
 
-    Ok(())
+    #[test]
+    fn too_long_key_is_rejected() {
+        assert!(matches!(
+            blake2b_keyed_circuit(&[0; 65], b"abc", 64, &RunConfig::default()),
+            Err(ProofError::InvalidParameter(_))
+        ));
+    }
 }
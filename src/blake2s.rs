@@ -0,0 +1,271 @@
+//! Blake2s circuit.
+//!
+//! `binius_circuits` does not ship a Blake2s gadget, so the compression function is built
+//! here from the 32-bit builder primitives, in the same way as the Blake3 circuit and
+//! `Blake2bOutHash`. The message length is fixed when the circuit is built, and only
+//! the unkeyed mode is supported.
+
+use binius_core::word::Word;
+use binius_frontend::{CircuitBuilder, Wire, WitnessFiller};
+use blake2::Blake2sVar;
+use blake2::digest::{Update, VariableOutput};
+
+use crate::config::RunConfig;
+use crate::error::ProofError;
+use crate::hash_circuit::{
+    HashCircuit, WordEndian, check_exact_len, public_digest, run_hash_circuit_with_config,
+};
+use crate::timings::CircuitTimings;
+
+const IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+const BLOCK_LEN: usize = 64;
+const MAX_OUT_LEN: usize = 32;
+
+// G mixing function on state words a, b, c, d with message words x, y.
+fn g(b: &CircuitBuilder, v: &mut [Wire; 16], i: [usize; 4], x: Wire, y: Wire) {
+    let [a, bb, c, d] = i;
+    v[a] = b.iadd_32(b.iadd_32(v[a], v[bb]), x);
+    v[d] = b.rotr_32(b.bxor(v[d], v[a]), 16);
+    v[c] = b.iadd_32(v[c], v[d]);
+    v[bb] = b.rotr_32(b.bxor(v[bb], v[c]), 12);
+    v[a] = b.iadd_32(b.iadd_32(v[a], v[bb]), y);
+    v[d] = b.rotr_32(b.bxor(v[d], v[a]), 8);
+    v[c] = b.iadd_32(v[c], v[d]);
+    v[bb] = b.rotr_32(b.bxor(v[bb], v[c]), 7);
+}
+
+// Blake2s compression of one block into `h`. The byte counter `t` and the final-block
+// flag are known when the circuit is built, so they enter as constants.
+fn compress(b: &CircuitBuilder, h: &mut [Wire; 8], m: &[Wire; 16], t: u64, last: bool) {
+    let mut v: [Wire; 16] = core::array::from_fn(|i| match i {
+        0..8 => h[i],
+        12 => b.add_constant_64((IV[4] ^ t as u32) as u64),
+        13 => b.add_constant_64((IV[5] ^ (t >> 32) as u32) as u64),
+        14 if last => b.add_constant_64(!IV[6] as u64),
+        _ => b.add_constant_64(IV[i - 8] as u64),
+    });
+
+    for s in &SIGMA {
+        g(b, &mut v, [0, 4, 8, 12], m[s[0]], m[s[1]]);
+        g(b, &mut v, [1, 5, 9, 13], m[s[2]], m[s[3]]);
+        g(b, &mut v, [2, 6, 10, 14], m[s[4]], m[s[5]]);
+        g(b, &mut v, [3, 7, 11, 15], m[s[6]], m[s[7]]);
+        g(b, &mut v, [0, 5, 10, 15], m[s[8]], m[s[9]]);
+        g(b, &mut v, [1, 6, 11, 12], m[s[10]], m[s[11]]);
+        g(b, &mut v, [2, 7, 8, 13], m[s[12]], m[s[13]]);
+        g(b, &mut v, [3, 4, 9, 14], m[s[14]], m[s[15]]);
+    }
+
+    for i in 0..8 {
+        h[i] = b.bxor(h[i], b.bxor(v[i], v[i + 8]));
+    }
+}
+
+/// Blake2s with a configurable output length, over a message whose length is fixed when
+/// the circuit is built.
+///
+/// Message wires hold one little-endian 32-bit word each. The digest is exposed as
+/// `out_len.div_ceil(8)` inout words, each the little-endian packing of 8 digest bytes
+/// as in the Blake3 circuit, with the unused high bytes of the last word zero.
+pub struct Blake2sHash {
+    len: usize,
+    out_len: usize,
+    message: Vec<Wire>,
+    digest: Vec<Wire>,
+}
+
+impl Blake2sHash {
+    /// Build the gadget for `len`-byte messages and an `out_len`-byte digest, where
+    /// `out_len` is in `1..=32`.
+    pub fn new(builder: &CircuitBuilder, len: usize, out_len: usize) -> Self {
+        assert!((1..=MAX_OUT_LEN).contains(&out_len));
+
+        let message: Vec<_> = (0..len.div_ceil(4))
+            .map(|_| builder.add_witness())
+            .collect();
+        let digest: Vec<_> = (0..out_len.div_ceil(8))
+            .map(|_| builder.add_inout())
+            .collect();
+
+        // parameter block: digest length, no key, fanout and depth 1
+        let mut h: [Wire; 8] = core::array::from_fn(|i| builder.add_constant_64(IV[i] as u64));
+        h[0] = builder.add_constant_64((IV[0] ^ 0x0101_0000 ^ out_len as u32) as u64);
+
+        let zero = builder.add_constant_64(0);
+        // the empty message still compresses one empty block
+        let n_blocks = len.div_ceil(BLOCK_LEN).max(1);
+        for j in 0..n_blocks {
+            let block: [Wire; 16] =
+                core::array::from_fn(|k| message.get(j * 16 + k).copied().unwrap_or(zero));
+            let last = j == n_blocks - 1;
+            let t = if last { len } else { (j + 1) * BLOCK_LEN };
+            compress(builder, &mut h, &block, t as u64, last);
+        }
+
+        // pack two 32-bit output words into each 64-bit commitment word
+        let mask = builder.add_constant_64(0xffff_ffff);
+        for (i, &d) in digest.iter().enumerate() {
+            let n_bytes = (out_len - 8 * i).min(8);
+            let lo = builder.band(h[2 * i], mask);
+            let out = if n_bytes > 4 {
+                let hi = builder.shl(builder.band(h[2 * i + 1], mask), 32);
+                builder.bxor(lo, hi)
+            } else {
+                lo
+            };
+            let out = if n_bytes % 4 == 0 {
+                out
+            } else {
+                builder.band(out, builder.add_constant_64((1 << (8 * n_bytes)) - 1))
+            };
+            builder.assert_eq(format!("blake2s digest[{i}]"), out, d);
+        }
+
+        Self {
+            len,
+            out_len,
+            message,
+            digest,
+        }
+    }
+
+    pub fn populate_message(&self, witness: &mut WitnessFiller, msg: &[u8]) {
+        // the circuit hashes exactly `len` bytes, shorter messages are zero padded and
+        // will fail the digest check
+        debug_assert!(msg.len() <= self.len);
+        for (i, &w) in self.message.iter().enumerate() {
+            let mut word = [0u8; 4];
+            for (k, byte) in word.iter_mut().enumerate() {
+                *byte = msg.get(4 * i + k).copied().unwrap_or(0);
+            }
+            witness[w] = Word(u32::from_le_bytes(word) as u64);
+        }
+    }
+
+    pub fn populate_digest(&self, witness: &mut WitnessFiller, digest: &[u8]) {
+        debug_assert_eq!(digest.len(), self.out_len);
+        for (i, &w) in self.digest.iter().enumerate() {
+            let mut word = [0u8; 8];
+            for (k, byte) in word.iter_mut().enumerate() {
+                *byte = digest.get(8 * i + k).copied().unwrap_or(0);
+            }
+            witness[w] = Word(u64::from_le_bytes(word));
+        }
+    }
+}
+
+// Reference `out_len`-byte Blake2s digest.
+fn blake2s(msg: &[u8], out_len: usize) -> Vec<u8> {
+    let mut hasher = Blake2sVar::new(out_len).expect("output length in 1..=32");
+    hasher.update(msg);
+    let mut out = vec![0u8; out_len];
+    hasher
+        .finalize_variable(&mut out)
+        .expect("buffer has the output length");
+    out
+}
+
+impl HashCircuit for Blake2sHash {
+    const NAME: &'static str = "blake2s";
+
+    fn build(builder: &CircuitBuilder, max_len: usize) -> Self {
+        Self::new(builder, max_len, MAX_OUT_LEN)
+    }
+
+    fn max_len(&self) -> usize {
+        self.len
+    }
+
+    // the reference digest has to be computed with this circuit's output length
+    fn expected_digest(&self, msg: &[u8]) -> Vec<u8> {
+        blake2s(msg, self.out_len)
+    }
+
+    // the message length is fixed when the circuit is built
+    fn check_len(&self, msg: &[u8]) -> Result<(), ProofError> {
+        check_exact_len(msg, self.len)
+    }
+
+    fn populate_with_digest(&self, witness: &mut WitnessFiller, msg: &[u8], digest: &[u8]) {
+        self.populate_message(witness, msg);
+        self.populate_digest(witness, digest);
+    }
+
+    fn digest_from_public(&self, public: &[Word]) -> Vec<u8> {
+        public_digest(public, self.out_len, WordEndian::Little)
+    }
+
+    fn reference_digest(msg: &[u8]) -> Vec<u8> {
+        blake2s(msg, MAX_OUT_LEN)
+    }
+}
+
+/// Prove an `out_len`-byte Blake2s digest of `image_bytes`, `out_len` must be in `1..=32`.
+pub fn blake2s_circuit(
+    image_bytes: &[u8],
+    out_len: usize,
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    if !(1..=MAX_OUT_LEN).contains(&out_len) {
+        return Err(ProofError::InvalidParameter(format!(
+            "blake2s output length must be between 1 and {MAX_OUT_LEN} bytes, got {out_len}"
+        )));
+    }
+
+    run_hash_circuit_with_config(image_bytes, config, |builder, len| {
+        Blake2sHash::new(builder, len, out_len)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use binius_core::verify::verify_constraints;
+
+    #[test]
+    fn digest_matches_reference_for_each_output_length() {
+        let msg: Vec<u8> = (0..65).collect();
+        // partial and full commitment words, and the 64-byte block boundary
+        for out_len in [1, 4, 5, 20, 32] {
+            for len in [0, 64, 65] {
+                let builder = CircuitBuilder::new();
+                let hash = Blake2sHash::new(&builder, len, out_len);
+                let circuit = builder.build();
+
+                let mut witness = circuit.new_witness_filler();
+                hash.populate(&mut witness, &msg[..len]);
+                circuit
+                    .populate_wire_witness(&mut witness)
+                    .unwrap_or_else(|e| panic!("out_len {out_len}, len {len}: {e:?}"));
+                verify_constraints(circuit.constraint_system(), &witness.into_value_vec())
+                    .unwrap_or_else(|e| panic!("out_len {out_len}, len {len}: {e:?}"));
+            }
+        }
+    }
+
+    #[test]
+    fn out_of_range_output_length_is_rejected() {
+        for out_len in [0, 33] {
+            assert!(matches!(
+                blake2s_circuit(b"abc", out_len, &RunConfig::default()),
+                Err(ProofError::InvalidParameter(_))
+            ));
+        }
+    }
+}
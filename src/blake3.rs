@@ -0,0 +1,272 @@
+//! Blake3 circuit.
+//!
+//! `binius_circuits` does not ship a Blake3 gadget, so the compression function is built
+//! here directly from the 32-bit builder primitives (`iadd_32`, `rotr_32`, `bxor`).
+//!
+//! What is missing compared to a full gadget:
+//! - the message length is fixed when the circuit is built, so the chunk and parent
+//!   tree is static and one circuit proves exactly one message length;
+//! - only the unkeyed hash mode with the default 32-byte output is supported (no keyed
+//!   hashing, key derivation or extendable output).
+
+use binius_core::{constraint_system::ConstraintSystem, word::Word};
+use binius_frontend::{CircuitBuilder, Wire, WitnessFiller};
+
+use crate::config::RunConfig;
+use crate::error::ProofError;
+use crate::hash_circuit::{
+    HashCircuit, WordEndian, build_cs, check_exact_len, public_digest, run_hash_circuit_with_config,
+};
+use crate::timings::CircuitTimings;
+
+const IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+const CHUNK_START: u32 = 1 << 0;
+const CHUNK_END: u32 = 1 << 1;
+const PARENT: u32 = 1 << 2;
+const ROOT: u32 = 1 << 3;
+
+const BLOCK_LEN: usize = 64;
+const CHUNK_LEN: usize = 1024;
+
+// G mixing function on state words a, b, c, d with message words mx, my.
+fn g(b: &CircuitBuilder, s: &mut [Wire; 16], i: [usize; 4], mx: Wire, my: Wire) {
+    let [a, bb, c, d] = i;
+    s[a] = b.iadd_32(b.iadd_32(s[a], s[bb]), mx);
+    s[d] = b.rotr_32(b.bxor(s[d], s[a]), 16);
+    s[c] = b.iadd_32(s[c], s[d]);
+    s[bb] = b.rotr_32(b.bxor(s[bb], s[c]), 12);
+    s[a] = b.iadd_32(b.iadd_32(s[a], s[bb]), my);
+    s[d] = b.rotr_32(b.bxor(s[d], s[a]), 8);
+    s[c] = b.iadd_32(s[c], s[d]);
+    s[bb] = b.rotr_32(b.bxor(s[bb], s[c]), 7);
+}
+
+// Blake3 compression, returning the 8-word chaining value.
+fn compress(
+    b: &CircuitBuilder,
+    cv: &[Wire; 8],
+    block: &[Wire; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+) -> [Wire; 8] {
+    let mut s: [Wire; 16] = core::array::from_fn(|i| match i {
+        0..8 => cv[i],
+        8..12 => b.add_constant_64(IV[i - 8] as u64),
+        12 => b.add_constant_64(counter & 0xffff_ffff),
+        13 => b.add_constant_64(counter >> 32),
+        14 => b.add_constant_64(block_len as u64),
+        _ => b.add_constant_64(flags as u64),
+    });
+
+    let mut m = *block;
+    for _ in 0..7 {
+        g(b, &mut s, [0, 4, 8, 12], m[0], m[1]);
+        g(b, &mut s, [1, 5, 9, 13], m[2], m[3]);
+        g(b, &mut s, [2, 6, 10, 14], m[4], m[5]);
+        g(b, &mut s, [3, 7, 11, 15], m[6], m[7]);
+        g(b, &mut s, [0, 5, 10, 15], m[8], m[9]);
+        g(b, &mut s, [1, 6, 11, 12], m[10], m[11]);
+        g(b, &mut s, [2, 7, 8, 13], m[12], m[13]);
+        g(b, &mut s, [3, 4, 9, 14], m[14], m[15]);
+        m = core::array::from_fn(|i| m[MSG_PERMUTATION[i]]);
+    }
+
+    core::array::from_fn(|i| b.bxor(s[i], s[i + 8]))
+}
+
+// Chaining value of one chunk, `words` holds the chunk's message words.
+fn chunk_cv(
+    b: &CircuitBuilder,
+    words: &[Wire],
+    chunk_len: usize,
+    counter: u64,
+    is_root: bool,
+) -> [Wire; 8] {
+    let zero = b.add_constant_64(0);
+    let mut cv: [Wire; 8] = core::array::from_fn(|i| b.add_constant_64(IV[i] as u64));
+
+    // the empty message still compresses one empty block
+    let n_blocks = chunk_len.div_ceil(BLOCK_LEN).max(1);
+    for j in 0..n_blocks {
+        let block: [Wire; 16] =
+            core::array::from_fn(|k| words.get(j * 16 + k).copied().unwrap_or(zero));
+        let block_len = (chunk_len - j * BLOCK_LEN).min(BLOCK_LEN) as u32;
+
+        let mut flags = 0;
+        if j == 0 {
+            flags |= CHUNK_START;
+        }
+        if j == n_blocks - 1 {
+            flags |= CHUNK_END;
+            if is_root {
+                flags |= ROOT;
+            }
+        }
+        cv = compress(b, &cv, &block, counter, block_len, flags);
+    }
+    cv
+}
+
+// Chaining value of the subtree covering `len` bytes starting at chunk `first_chunk`.
+// The left subtree holds the largest power-of-two number of chunks that leaves at least
+// one chunk for the right.
+fn subtree_cv(
+    b: &CircuitBuilder,
+    words: &[Wire],
+    len: usize,
+    first_chunk: u64,
+    is_root: bool,
+) -> [Wire; 8] {
+    if len <= CHUNK_LEN {
+        return chunk_cv(b, words, len, first_chunk, is_root);
+    }
+
+    let n_chunks = len.div_ceil(CHUNK_LEN);
+    let left_chunks = 1 << (usize::BITS - 1 - (n_chunks - 1).leading_zeros());
+    let left_len = left_chunks * CHUNK_LEN;
+    let split = left_len / 4;
+
+    let left = subtree_cv(b, &words[..split], left_len, first_chunk, false);
+    let right = subtree_cv(
+        b,
+        &words[split..],
+        len - left_len,
+        first_chunk + left_chunks as u64,
+        false,
+    );
+
+    let key: [Wire; 8] = core::array::from_fn(|i| b.add_constant_64(IV[i] as u64));
+    let block: [Wire; 16] = core::array::from_fn(|i| if i < 8 { left[i] } else { right[i - 8] });
+    let flags = if is_root { PARENT | ROOT } else { PARENT };
+    compress(b, &key, &block, 0, BLOCK_LEN as u32, flags)
+}
+
+/// Blake3 over a message whose length is fixed when the circuit is built.
+///
+/// Message wires hold one little-endian 32-bit word each. The 32-byte digest is exposed
+/// as 4 inout words, each the little-endian packing of 8 digest bytes, the same layout
+/// as the keccak commitment.
+pub struct Blake3Hash {
+    len: usize,
+    message: Vec<Wire>,
+    digest: [Wire; 4],
+}
+
+impl HashCircuit for Blake3Hash {
+    const NAME: &'static str = "blake3";
+
+    fn build(builder: &CircuitBuilder, max_len: usize) -> Self {
+        let message: Vec<_> = (0..max_len.div_ceil(4))
+            .map(|_| builder.add_witness())
+            .collect();
+        let digest: [_; 4] = core::array::from_fn(|_| builder.add_inout());
+
+        let out = subtree_cv(builder, &message, max_len, 0, true);
+
+        // pack two 32-bit output words into each 64-bit commitment word
+        let mask = builder.add_constant_64(0xffff_ffff);
+        for (i, &d) in digest.iter().enumerate() {
+            let lo = builder.band(out[2 * i], mask);
+            let hi = builder.shl(builder.band(out[2 * i + 1], mask), 32);
+            builder.assert_eq(format!("blake3 digest[{i}]"), builder.bxor(lo, hi), d);
+        }
+
+        Self {
+            len: max_len,
+            message,
+            digest,
+        }
+    }
+
+    fn max_len(&self) -> usize {
+        self.len
+    }
+
+    // the message length is fixed when the circuit is built
+    fn check_len(&self, msg: &[u8]) -> Result<(), ProofError> {
+        check_exact_len(msg, self.len)
+    }
+
+    fn populate_with_digest(&self, witness: &mut WitnessFiller, msg: &[u8], digest: &[u8]) {
+        // the circuit hashes exactly `len` bytes, shorter messages are zero padded and
+        // will fail the digest check
+        debug_assert!(msg.len() <= self.len);
+        for (i, &w) in self.message.iter().enumerate() {
+            let mut word = [0u8; 4];
+            for (k, byte) in word.iter_mut().enumerate() {
+                *byte = msg.get(4 * i + k).copied().unwrap_or(0);
+            }
+            witness[w] = Word(u32::from_le_bytes(word) as u64);
+        }
+
+        for (i, &w) in self.digest.iter().enumerate() {
+            let bytes: [u8; 8] = digest[8 * i..8 * i + 8].try_into().unwrap();
+            witness[w] = Word(u64::from_le_bytes(bytes));
+        }
+    }
+
+    fn digest_from_public(&self, public: &[Word]) -> Vec<u8> {
+        public_digest(public, 32, WordEndian::Little)
+    }
+
+    fn reference_digest(msg: &[u8]) -> Vec<u8> {
+        blake3::hash(msg).as_bytes().to_vec()
+    }
+}
+
+/// The blake3 constraint system for messages of up to `max_len` bytes, see
+/// `hash_circuit::build_cs`.
+pub fn build_cs_blake3(max_len: usize) -> ConstraintSystem {
+    build_cs::<Blake3Hash>(max_len)
+}
+
+pub fn blake3_circuit(
+    image_bytes: &[u8],
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    run_hash_circuit_with_config(image_bytes, config, Blake3Hash::build)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use binius_core::verify::verify_constraints;
+
+    // one full chunk, then trees of 2, 3 and 5 chunks, whose parents join a left subtree
+    // of a power of two chunks with the rest, partial last chunks included
+    #[test]
+    fn multi_chunk_digest_matches_blake3() {
+        for len in [
+            CHUNK_LEN,
+            CHUNK_LEN + 1,
+            2 * CHUNK_LEN,
+            3 * CHUNK_LEN,
+            4 * CHUNK_LEN + 1,
+        ] {
+            let msg: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+
+            let builder = CircuitBuilder::new();
+            let hash = Blake3Hash::build(&builder, len);
+            let circuit = builder.build();
+
+            let mut witness = circuit.new_witness_filler();
+            hash.populate_with_digest(&mut witness, &msg, blake3::hash(&msg).as_bytes());
+            circuit
+                .populate_wire_witness(&mut witness)
+                .unwrap_or_else(|e| panic!("len {len}: {e:?}"));
+            let witness = witness.into_value_vec();
+            verify_constraints(circuit.constraint_system(), &witness)
+                .unwrap_or_else(|e| panic!("len {len}: {e:?}"));
+            assert_eq!(
+                hash.digest_from_public(witness.public()),
+                blake3::hash(&msg).as_bytes(),
+                "len {len}"
+            );
+        }
+    }
+}
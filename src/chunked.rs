@@ -0,0 +1,135 @@
+//! SHA-256 of a large input in fixed-size chunks, one proof per chunk.
+//!
+//! The input is split into `chunk_size`-byte chunks (the last one may be shorter), and
+//! each chunk gets its own sha256 proof from a single circuit built for `chunk_size`
+//! bytes. Only one chunk's witness and proof are in memory at a time, so peak memory
+//! depends on `chunk_size` and not on the input length.
+//!
+//! The resulting commitment is **not** `sha256(image)`. It is the hash list
+//! `sha256(sha256(c_0) || sha256(c_1) || ... || sha256(c_{n-1}))` over the chunk
+//! digests, see `chunked_commitment`, so it also depends on `chunk_size`: the same input
+//! committed with another chunk size gives another value. The chunk digests are the
+//! public outputs of the chunk proofs, and the verifier recomputes the commitment from
+//! them, which is not proven in a circuit of its own.
+
+use std::time::Instant;
+
+use sha2::{Digest, Sha256 as StdSha256};
+
+use crate::config::RunConfig;
+use crate::error::ProofError;
+use crate::hash_circuit::{HashProver, digest_hex};
+use crate::sha256::{Sha256Hash, Sha256PublicInputs};
+use crate::timings::CircuitTimings;
+
+/// The chunked commitment to `image` computed in plain Rust: sha256 over the
+/// concatenated sha256 digests of its `chunk_size`-byte chunks. An empty image is a
+/// single empty chunk.
+pub fn chunked_commitment(image: &[u8], chunk_size: usize) -> [u8; 32] {
+    let mut hasher = StdSha256::new();
+    for chunk in chunks(image, chunk_size) {
+        hasher.update(StdSha256::digest(chunk));
+    }
+    hasher.finalize().into()
+}
+
+fn chunks(image: &[u8], chunk_size: usize) -> impl Iterator<Item = &[u8]> {
+    // `chunks` yields nothing for an empty slice
+    let empty: &[u8] = &[];
+    let first = image.is_empty().then_some(empty);
+    first.into_iter().chain(image.chunks(chunk_size))
+}
+
+/// Prove and verify the sha256 of every `chunk_size`-byte chunk of `image_bytes`, and
+/// check that the commitment the verified chunk digests give is `chunked_commitment`.
+///
+/// The returned timings are totals over all chunks, with `proof_bytes` the size of all
+/// chunk proofs together and the circuit size fields those of the single chunk circuit.
+pub fn chunked_hash_circuit(
+    image_bytes: &[u8],
+    chunk_size: usize,
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    if chunk_size == 0 {
+        return Err(ProofError::InvalidParameter(
+            "chunk size must be at least 1 byte".into(),
+        ));
+    }
+
+    let mut timings = CircuitTimings {
+        log_inv_rate: config.log_inv_rate,
+        ..Default::default()
+    };
+
+    let setup_timer = Instant::now();
+    let prover = HashProver::<Sha256Hash>::with_log_inv_rate(chunk_size, config.log_inv_rate)?;
+    timings.setup_ms = setup_timer.elapsed().as_millis();
+    timings.record_circuit_stats(prover.constraint_system());
+
+    let mut commitment = StdSha256::new();
+    for (i, chunk) in chunks(image_bytes, chunk_size).enumerate() {
+        let prove_timer = Instant::now();
+        let (proof, public_words) = prover.prove(chunk)?;
+        timings.prove_ms += prove_timer.elapsed().as_millis();
+        timings.proof_bytes += proof.len();
+
+        let verify_timer = Instant::now();
+        prover.verify(proof, &public_words)?;
+        timings.verify_ms += verify_timer.elapsed().as_millis();
+
+        commitment.update(Sha256PublicInputs::from_words(public_words)?.digest());
+        if config.print_progress() {
+            println!("✓ chunk {i} ({} bytes) proven and verified", chunk.len());
+        }
+    }
+
+    let commitment: [u8; 32] = commitment.finalize().into();
+    let expected = chunked_commitment(image_bytes, chunk_size);
+    if commitment != expected {
+        return Err(ProofError::DigestMismatch {
+            expected: expected.to_vec(),
+            committed: commitment.to_vec(),
+        });
+    }
+    if config.print_progress() {
+        println!("Chunked commitment {}", digest_hex(&commitment));
+    }
+
+    Ok(timings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commitment_is_hash_of_chunk_digests() {
+        let image: Vec<u8> = (0..150).map(|i| i as u8).collect();
+        let digests = [&image[..64], &image[64..128], &image[128..]]
+            .map(|chunk| StdSha256::digest(chunk).to_vec())
+            .concat();
+        assert_eq!(
+            chunked_commitment(&image, 64),
+            <[u8; 32]>::from(StdSha256::digest(&digests))
+        );
+        assert_ne!(
+            chunked_commitment(&image, 64),
+            chunked_commitment(&image, 100)
+        );
+    }
+
+    #[test]
+    fn chunks_are_proven() {
+        let image: Vec<u8> = (0..150).map(|i| i as u8).collect();
+        let timings = chunked_hash_circuit(&image, 64, &RunConfig::default()).unwrap();
+        assert!(timings.proof_bytes > 0);
+    }
+
+    #[test]
+    fn zero_chunk_size_is_rejected() {
+        assert!(matches!(
+            chunked_hash_circuit(b"abc", 0, &RunConfig::default()),
+            Err(ProofError::InvalidParameter(_))
+        ));
+    }
+}
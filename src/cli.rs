@@ -0,0 +1,336 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use sha256_example::Format;
+pub use sha256_example::algo::Algo;
+use sha256_example::hash_circuit::{DEFAULT_LOG_INV_RATE, LOG_INV_RATES};
+
+fn accepted_algos() -> String {
+    let mut names: Vec<_> = Algo::enabled_algos().iter().map(|a| a.name()).collect();
+    names.push("all");
+    names.join("|")
+}
+
+// "all" expands to every algorithm compiled in
+fn parse_algos(s: &str) -> Result<Vec<Algo>, ArgsError> {
+    if s == "all" {
+        return Ok(Algo::enabled_algos());
+    }
+    match Algo::from_name(s) {
+        Some(a) if a.enabled() => Ok(vec![a]),
+        Some(a) => Err(ArgsError(format!(
+            "algo '{s}' is not compiled in, rebuild with --features {}",
+            a.feature()
+        ))),
+        None => Err(ArgsError(format!(
+            "unknown algo '{s}', expected one of {}",
+            accepted_algos()
+        ))),
+    }
+}
+
+/// Seed for the random image when `--seed` is not given.
+pub const DEFAULT_SEED: u64 = 0;
+
+/// Command line options, e.g. `--algo keccak --size 262144 --iters 5 --seed 42`.
+#[derive(Debug)]
+pub struct Args {
+    pub algos: Vec<Algo>,
+    /// Sizes of the random image in bytes. Each algo is run on one image per size,
+    /// and with several sizes a table of proving time and proof size per size follows.
+    pub sizes: Vec<usize>,
+    /// Timed runs per algo. With more than one, an untimed warm-up run comes first and
+    /// the summary reports min/median/max of each phase.
+    pub iters: usize,
+    /// Seed for the random image and the lattice circuit's secret matrix.
+    pub seed: u64,
+    /// Hash the contents of this file instead of random images of `sizes` bytes, so it
+    /// excludes `--size`.
+    ///
+    /// The whole file is read into memory and every byte becomes part of the witness, so
+    /// memory use grows with the file size several times over (message wires, the
+    /// circuit's internal wires and the prover's committed witness). Files beyond a few
+    /// MiB are not practical.
+    pub input: Option<PathBuf>,
+    /// Run the selected algos concurrently and print only a summary line for each.
+    pub parallel: bool,
+    /// Only build the circuits and check the witness against the constraints, skipping
+    /// prover setup, proving and verifying.
+    pub check_only: bool,
+    /// Prove without the local constraint check first, see
+    /// `RunConfig::skip_local_check`. Compare `check` in the timings of a run without
+    /// it for the time saved.
+    pub skip_local_check: bool,
+    /// After the runs, print one table of all algos per input size, sorted by proving
+    /// time, to compare them side by side.
+    ///
+    /// Every algo hashes the same image of each size, generated once from `seed` or read
+    /// from `input`, except the lattice circuit, which takes the image's first bytes and
+    /// gets a table of its own. Each table names its input's length and fingerprint so
+    /// the comparison can be reproduced. The circuits depend only on the input length,
+    /// but populating the witness, and with it proving, is not guaranteed to take the
+    /// same time for any content, so timings are only compared on identical input.
+    pub compare: bool,
+    /// Print the peak resident set size after proving. Linux only, elsewhere it is
+    /// reported as unavailable. With `--parallel` the circuits share one process, so the
+    /// peak covers all of them.
+    pub mem: bool,
+    pub format: Format,
+    /// Write the `--format json` or `csv` report to this file instead of stdout.
+    pub out: Option<PathBuf>,
+    /// `log_inv_rate`s to set prover and verifier up with (`--log-inv-rate`, or `--rate`
+    /// for short), every algo is run once per value, see `DEFAULT_LOG_INV_RATE` for the
+    /// trade-off and `LOG_INV_RATES` for the accepted values.
+    pub log_inv_rates: Vec<usize>,
+    /// Size of rayon's global pool, which the prover parallelizes on, e.g. to match a
+    /// container's CPU quota. `None` uses rayon's default of one thread per core.
+    pub threads: Option<usize>,
+    /// Before running, print a summary of each algo's constraint system for the input
+    /// size: constraint counts by kind and word counts by kind. Proving is unaffected.
+    pub dump_cs: bool,
+    /// Instead of the usual runs, report each algo's cold start, building the circuit,
+    /// setting the prover up and the first proof, apart from its steady state, `iters`
+    /// further proofs with the same prover, as a long-running service would see them.
+    pub cold_start: bool,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            algos: Algo::enabled_algos(),
+            sizes: vec![1 << 17],
+            iters: 1,
+            seed: DEFAULT_SEED,
+            input: None,
+            parallel: false,
+            check_only: false,
+            skip_local_check: false,
+            compare: false,
+            mem: false,
+            format: Format::Text,
+            out: None,
+            log_inv_rates: vec![DEFAULT_LOG_INV_RATE],
+            threads: None,
+            dump_cs: false,
+            cold_start: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ArgsError(String);
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}\nusage: selftest\n       [--algo {}] [--size <bytes>[,<bytes>...]] [--iters <n>] [--seed <u64>] [--input <path>] [--parallel] [--check-only] [--skip-local-check] [--compare] [--mem] [--format text|json|csv] [--out <path>] [--log-inv-rate|--rate <n>[,<n>...]] [--threads <n>] [--dump-cs] [--cold-start]",
+            self.0,
+            accepted_algos()
+        )
+    }
+}
+
+impl std::error::Error for ArgsError {}
+
+impl Args {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, ArgsError> {
+        let mut parsed = Args::default();
+        // `sizes` has a default, so whether `--size` was given is tracked on its own
+        let mut sizes_given = false;
+
+        while let Some(flag) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| ArgsError(format!("missing value for {flag}")))
+            };
+            match flag.as_str() {
+                "--algo" => parsed.algos = parse_algos(&value()?)?,
+                "--size" => {
+                    parsed.sizes = value()?
+                        .split(',')
+                        .map(|v| parse_number("--size", v))
+                        .collect::<Result<_, _>>()?;
+                    sizes_given = true;
+                }
+                "--iters" => {
+                    parsed.iters = parse_number("--iters", &value()?)?;
+                    if parsed.iters == 0 {
+                        return Err(ArgsError("--iters must be at least 1".into()));
+                    }
+                }
+                "--threads" => {
+                    let threads = parse_number("--threads", &value()?)?;
+                    if threads == 0 {
+                        return Err(ArgsError("--threads must be at least 1".into()));
+                    }
+                    parsed.threads = Some(threads);
+                }
+                "--seed" => parsed.seed = parse_number("--seed", &value()?)?,
+                "--input" => parsed.input = Some(PathBuf::from(value()?)),
+                "--out" => parsed.out = Some(PathBuf::from(value()?)),
+                "--parallel" => parsed.parallel = true,
+                "--check-only" => parsed.check_only = true,
+                "--skip-local-check" => parsed.skip_local_check = true,
+                "--compare" => parsed.compare = true,
+                "--mem" => parsed.mem = true,
+                "--dump-cs" => parsed.dump_cs = true,
+                "--cold-start" => parsed.cold_start = true,
+                "--log-inv-rate" | "--rate" => {
+                    parsed.log_inv_rates = value()?
+                        .split(',')
+                        .map(|v| parse_number(&flag, v))
+                        .collect::<Result<_, _>>()?;
+                    if let Some(&r) = parsed
+                        .log_inv_rates
+                        .iter()
+                        .find(|r| !LOG_INV_RATES.contains(r))
+                    {
+                        return Err(ArgsError(format!(
+                            "{flag} must be between {} and {}, got {r}",
+                            LOG_INV_RATES.start(),
+                            LOG_INV_RATES.end()
+                        )));
+                    }
+                }
+                "--format" => {
+                    parsed.format = match value()?.as_str() {
+                        "text" => Format::Text,
+                        "json" => Format::Json,
+                        "csv" => Format::Csv,
+                        v => {
+                            return Err(ArgsError(format!(
+                                "invalid value '{v}' for --format, expected text, json or csv"
+                            )));
+                        }
+                    }
+                }
+                _ => return Err(ArgsError(format!("unknown argument '{flag}'"))),
+            }
+        }
+
+        if parsed.input.is_some() && sizes_given {
+            return Err(ArgsError(
+                "--input and --size exclude each other, the input file sets the size".into(),
+            ));
+        }
+        if parsed.check_only && parsed.skip_local_check {
+            return Err(ArgsError(
+                "--check-only and --skip-local-check exclude each other".into(),
+            ));
+        }
+        if parsed.compare && (parsed.check_only || parsed.format != Format::Text) {
+            return Err(ArgsError(
+                "--compare needs proofs and text output, it excludes --check-only and --format json or csv".into(),
+            ));
+        }
+        if parsed.dump_cs && parsed.format != Format::Text {
+            return Err(ArgsError(
+                "--dump-cs needs text output, it excludes --format json or csv".into(),
+            ));
+        }
+        if parsed.cold_start
+            && (parsed.check_only
+                || parsed.parallel
+                || parsed.compare
+                || parsed.format != Format::Text)
+        {
+            return Err(ArgsError(
+                "--cold-start needs sequential proofs and text output, it excludes --check-only, --parallel, --compare and --format json or csv".into(),
+            ));
+        }
+        if parsed.out.is_some() && parsed.format == Format::Text {
+            return Err(ArgsError("--out needs --format json or csv".into()));
+        }
+
+        Ok(parsed)
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, v: &str) -> Result<T, ArgsError> {
+    v.parse()
+        .map_err(|_| ArgsError(format!("invalid value '{v}' for {flag}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, ArgsError> {
+        Args::parse(args.iter().map(|a| a.to_string()))
+    }
+
+    // the message of a rejected command line, without the usage line
+    fn rejected(args: &[&str]) -> String {
+        match parse(args) {
+            Err(ArgsError(msg)) => msg,
+            Ok(parsed) => panic!("{args:?} was accepted as {parsed:?}"),
+        }
+    }
+
+    #[test]
+    fn no_arguments_give_the_defaults() {
+        let parsed = parse(&[]).unwrap();
+        assert_eq!(parsed.sizes, [1 << 17]);
+        assert_eq!(parsed.iters, 1);
+        assert_eq!(parsed.log_inv_rates, [DEFAULT_LOG_INV_RATE]);
+        assert_eq!(parsed.threads, None);
+    }
+
+    #[test]
+    fn comma_lists_are_split() {
+        let parsed = parse(&["--size", "0,64,1024", "--rate", "1,2,4"]).unwrap();
+        assert_eq!(parsed.sizes, [0, 64, 1024]);
+        assert_eq!(parsed.log_inv_rates, [1, 2, 4]);
+
+        let parsed = parse(&["--log-inv-rate", "3"]).unwrap();
+        assert_eq!(parsed.log_inv_rates, [3]);
+
+        assert!(rejected(&["--size", "64,,128"]).contains("invalid value '' for --size"));
+        assert!(rejected(&["--rate", "1,5"]).contains("--rate must be between 1 and 4, got 5"));
+    }
+
+    #[test]
+    fn zero_iters_and_threads_are_rejected() {
+        assert_eq!(rejected(&["--iters", "0"]), "--iters must be at least 1");
+        assert_eq!(
+            rejected(&["--threads", "0"]),
+            "--threads must be at least 1"
+        );
+        assert_eq!(parse(&["--threads", "2"]).unwrap().threads, Some(2));
+    }
+
+    #[test]
+    fn exclusive_flags_are_rejected() {
+        let cases: [(&[&str], &str); 10] = [
+            (&["--input", "msg.bin", "--size", "64"], "--input"),
+            (&["--check-only", "--skip-local-check"], "--check-only"),
+            (&["--compare", "--check-only"], "--compare"),
+            (&["--compare", "--format", "json"], "--compare"),
+            (&["--dump-cs", "--format", "csv"], "--dump-cs"),
+            (&["--cold-start", "--check-only"], "--cold-start"),
+            (&["--cold-start", "--parallel"], "--cold-start"),
+            (&["--cold-start", "--compare"], "--cold-start"),
+            (&["--cold-start", "--format", "json"], "--cold-start"),
+            (&["--out", "report.csv"], "--out"),
+        ];
+        for (args, flag) in cases {
+            let msg = rejected(args);
+            assert!(msg.starts_with(flag), "{args:?}: {msg}");
+        }
+
+        // --input and --out are fine on their own, or with the format --out needs
+        parse(&["--input", "msg.bin"]).unwrap();
+        parse(&["--out", "report.csv", "--format", "csv"]).unwrap();
+    }
+
+    #[test]
+    fn unknown_flag_and_missing_value_are_rejected() {
+        assert_eq!(rejected(&["--sizes", "64"]), "unknown argument '--sizes'");
+        assert_eq!(rejected(&["--iters"]), "missing value for --iters");
+        assert_eq!(
+            rejected(&["--seed", "1", "--format"]),
+            "missing value for --format"
+        );
+    }
+}
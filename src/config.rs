@@ -0,0 +1,113 @@
+use crate::hash_circuit::DEFAULT_LOG_INV_RATE;
+
+/// How results are reported, selected with `--format` in the demo binary.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    /// Progress lines per phase, the default.
+    #[default]
+    Text,
+    /// One JSON object per line and run on stdout, nothing else.
+    Json,
+    /// A header row, then one comma-separated row per run.
+    Csv,
+}
+
+/// Options of a circuit run, taken by the `*_circuit` entry points.
+///
+/// Built with chained setters, e.g. `RunConfig::new().verbose(true).seed(42)`; fields
+/// that are not set keep their `Default`. The transcript challenger is always
+/// `StdChallenger` here, see the `*_with_challenger` functions in `hash_circuit` for
+/// others, and the prover's packed field is picked at compile time with the `packed-*`
+/// features.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RunConfig {
+    /// Print progress and timings for each phase.
+    pub verbose: bool,
+    /// See `DEFAULT_LOG_INV_RATE`.
+    pub log_inv_rate: usize,
+    /// Seed for circuits that sample private randomness, currently the lattice matrix.
+    /// `None` samples fresh randomness on every run.
+    pub seed: Option<u64>,
+    /// How the caller reports the results. Progress is only printed for `Format::Text`,
+    /// so that JSON or CSV on stdout stays parseable.
+    pub format: Format,
+    /// Prove without first checking the witness against the constraints locally.
+    ///
+    /// The check catches a bad witness with a message naming the failing constraint,
+    /// while the prover would only produce a proof that fails verification, or fail
+    /// with a less specific error. Only skip it for witnesses that are already known to
+    /// be valid; it saves the `constraint_check_ms` a checked run reports.
+    pub skip_local_check: bool,
+    /// Build the circuit for messages of up to this many bytes instead of exactly the
+    /// message's length, so that one circuit, and one verifier setup for it, covers every
+    /// shorter message too. The verifier needs the same value. `None` builds for the
+    /// message length.
+    ///
+    /// Only gadgets with a length wire (sha256, sha512, keccak and their combinations)
+    /// hash a shorter message; the others hash exactly `max_len` bytes.
+    pub max_len: Option<usize>,
+    /// Domain-separation tag absorbed into the transcript before proving and verifying,
+    /// see `prove_hash_in_domain`. Empty, the default, absorbs nothing.
+    pub domain: Vec<u8>,
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        Self {
+            verbose: false,
+            log_inv_rate: DEFAULT_LOG_INV_RATE,
+            seed: None,
+            format: Format::Text,
+            skip_local_check: false,
+            max_len: None,
+            domain: Vec::new(),
+        }
+    }
+}
+
+impl RunConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    pub fn log_inv_rate(mut self, log_inv_rate: usize) -> Self {
+        self.log_inv_rate = log_inv_rate;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn skip_local_check(mut self, skip: bool) -> Self {
+        self.skip_local_check = skip;
+        self
+    }
+
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    pub fn domain(mut self, domain: impl Into<Vec<u8>>) -> Self {
+        self.domain = domain.into();
+        self
+    }
+
+    /// Whether to print progress: `verbose` with text output.
+    pub fn print_progress(&self) -> bool {
+        self.verbose && self.format == Format::Text
+    }
+}
@@ -0,0 +1,105 @@
+//! Startup check that the binary was compiled for the SIMD features this CPU has.
+//!
+//! The binius field arithmetic picks its implementation from the `target_feature`s
+//! enabled at compile time, not at runtime. A binary built without
+//! `-C target-cpu=native` runs, but falls back to portable code that is several times
+//! slower, which is easy to miss.
+
+use std::sync::Once;
+
+/// Print a warning to stderr when the CPU supports features the binary was not compiled
+/// for: a table of the detected and compiled-in features, and the exact command to
+/// rebuild and rerun the current invocation with them. Only the first call in a process
+/// prints anything, so it can be called ahead of every proof.
+///
+/// There is no fallback to pick at runtime, the prover's packed field and arithmetic
+/// are fixed at compile time, so rebuilding is the only fix.
+pub fn warn_missing_target_features() {
+    static WARNED: Once = Once::new();
+    WARNED.call_once(|| {
+        let features = target_features();
+        if !features
+            .iter()
+            .any(|&(_, compiled, detected)| detected && !compiled)
+        {
+            return;
+        }
+
+        let yes_no = |b: bool| if b { "yes" } else { "no" };
+        eprintln!();
+        eprintln!("warning: this binary was not compiled for all SIMD features of this CPU,");
+        eprintln!("         proving will be several times slower than it could be.");
+        eprintln!();
+        eprintln!("  {:<12} {:>8} {:>8}", "feature", "detected", "compiled");
+        for (name, compiled, detected) in features {
+            eprintln!(
+                "  {name:<12} {:>8} {:>8}",
+                yes_no(detected),
+                yes_no(compiled)
+            );
+        }
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        eprintln!();
+        eprintln!("  rerun with:");
+        eprintln!(
+            "    RUSTFLAGS=\"-C target-cpu=native\" cargo run --release -- {}",
+            args.join(" ")
+        );
+        eprintln!();
+    });
+}
+
+// (name, compiled in, detected at runtime) for the features binius makes use of
+#[cfg(target_arch = "x86_64")]
+fn target_features() -> Vec<(&'static str, bool, bool)> {
+    [
+        (
+            "pclmulqdq",
+            cfg!(target_feature = "pclmulqdq"),
+            std::is_x86_feature_detected!("pclmulqdq"),
+        ),
+        (
+            "avx2",
+            cfg!(target_feature = "avx2"),
+            std::is_x86_feature_detected!("avx2"),
+        ),
+        (
+            "gfni",
+            cfg!(target_feature = "gfni"),
+            std::is_x86_feature_detected!("gfni"),
+        ),
+        (
+            "vpclmulqdq",
+            cfg!(target_feature = "vpclmulqdq"),
+            std::is_x86_feature_detected!("vpclmulqdq"),
+        ),
+        (
+            "avx512f",
+            cfg!(target_feature = "avx512f"),
+            std::is_x86_feature_detected!("avx512f"),
+        ),
+    ]
+    .into()
+}
+
+#[cfg(target_arch = "aarch64")]
+fn target_features() -> Vec<(&'static str, bool, bool)> {
+    [
+        (
+            "neon",
+            cfg!(target_feature = "neon"),
+            std::arch::is_aarch64_feature_detected!("neon"),
+        ),
+        (
+            "aes",
+            cfg!(target_feature = "aes"),
+            std::arch::is_aarch64_feature_detected!("aes"),
+        ),
+    ]
+    .into()
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn target_features() -> Vec<(&'static str, bool, bool)> {
+    Vec::new()
+}
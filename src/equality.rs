@@ -0,0 +1,141 @@
+//! Two secret preimages with the same SHA-256 digest, for "same file" proofs: the
+//! verifier learns the shared digest and that the prover knows two messages hashing to
+//! it, but neither message.
+//!
+//! Two sha256 gadgets, each with its own message and length wires, are built over the
+//! same four digest wires. Each gadget asserts its output equal to those wires, so the
+//! two digests are constrained equal without a separate comparison, and the digest is
+//! public only once. Both gadgets are built for the longer of the two messages with the
+//! lengths as witnesses, so the verifier only needs that maximum and learns neither
+//! length exactly.
+
+use binius_circuits::sha256::Sha256;
+use binius_core::{constraint_system::ValueVec, word::Word};
+use binius_frontend::{Circuit, CircuitBuilder, Wire};
+use binius_verifier::config::StdChallenger;
+use sha2::{Digest, Sha256 as StdSha256};
+
+use crate::config::RunConfig;
+use crate::error::ProofError;
+use crate::hash_circuit::{prove_and_verify_circuit, prove_circuit, verify_circuit};
+use crate::timings::CircuitTimings;
+
+const NAME: &str = "sha256-equality";
+
+// One of the two gadgets with its length wire.
+struct Preimage {
+    sha256: Sha256,
+    len_bytes: Wire,
+}
+
+// Both gadgets for messages of up to `max_len` bytes, over shared digest wires.
+fn build(max_len: usize) -> (Circuit, [Preimage; 2]) {
+    let builder = CircuitBuilder::new();
+    let digest: [_; 4] = core::array::from_fn(|_| builder.add_inout());
+    let preimages = core::array::from_fn(|_| {
+        // at least one message wire, as for `Sha256Hash`
+        let message: Vec<_> = (0..max_len.div_ceil(8).max(1))
+            .map(|_| builder.add_witness())
+            .collect();
+        let len_bytes = builder.add_witness();
+        Preimage {
+            sha256: Sha256::new(&builder, len_bytes, digest, message),
+            len_bytes,
+        }
+    });
+    (builder.build(), preimages)
+}
+
+// Build the circuit for `a` and `b` and populate its witness, with the digest of `a` as
+// the shared digest. If `b` hashes to something else its gadget's assertion fails.
+fn witness(a: &[u8], b: &[u8]) -> Result<(Circuit, ValueVec), ProofError> {
+    let max_len = a.len().max(b.len());
+    let (circuit, preimages) = build(max_len);
+
+    let digest: [u8; 32] = StdSha256::digest(a).into();
+    let mut witness = circuit.new_witness_filler();
+    for (preimage, msg) in preimages.iter().zip([a, b]) {
+        witness[preimage.len_bytes] = Word(msg.len() as u64);
+        preimage.sha256.populate_message(&mut witness, msg);
+        // both gadgets write the same values to the shared digest wires
+        preimage.sha256.populate_digest(&mut witness, digest);
+    }
+    circuit
+        .populate_wire_witness(&mut witness)
+        .map_err(|e| ProofError::from(e).in_circuit(NAME, max_len))?;
+    Ok((circuit, witness.into_value_vec()))
+}
+
+/// Prove sha256(a) = sha256(b) = h and return the proof bytes with the public words,
+/// whose last four hold h as for `Sha256PublicInputs`.
+///
+/// If the digests differ this fails with `ProofError::ConstraintFailed` before anything
+/// is proven.
+pub fn hash_equality_prove(
+    a: &[u8],
+    b: &[u8],
+    verbose: bool,
+) -> Result<(Vec<u8>, Vec<Word>, CircuitTimings), ProofError> {
+    let (circuit, witness_vec) = witness(a, b)?;
+    prove_circuit(&circuit, witness_vec, StdChallenger::default(), verbose)
+        .map_err(|e| e.in_circuit(NAME, a.len().max(b.len())))
+}
+
+/// Verify a proof produced by `hash_equality_prove`, where `max_len` is the length of
+/// the longer of the two messages.
+pub fn hash_equality_verify(
+    max_len: usize,
+    proof: Vec<u8>,
+    public_words: &[Word],
+    verbose: bool,
+) -> Result<CircuitTimings, ProofError> {
+    let (circuit, _) = build(max_len);
+    verify_circuit(
+        &circuit,
+        proof,
+        public_words,
+        StdChallenger::default(),
+        verbose,
+    )
+}
+
+/// Prove and verify that `a` and `b` have the same sha256 digest in one proof.
+pub fn hash_equality_circuit(
+    a: &[u8],
+    b: &[u8],
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    if config.print_progress() {
+        println!("Proof for {NAME} circuit: ");
+    }
+
+    let (circuit, witness_vec) = witness(a, b)?;
+    prove_and_verify_circuit(&circuit, witness_vec, config)
+        .map_err(|e| e.in_circuit(NAME, a.len().max(b.len())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha256::Sha256PublicInputs;
+
+    #[test]
+    fn equal_digests_prove_and_verify() {
+        // short of a sha256 collision, equal digests need the same message twice
+        let msg = b"same file";
+        let (proof, public_words, _) = hash_equality_prove(msg, msg, false).unwrap();
+        hash_equality_verify(msg.len(), proof, &public_words, false).unwrap();
+
+        let public = Sha256PublicInputs::from_words(public_words).unwrap();
+        assert_eq!(public.digest(), <[u8; 32]>::from(StdSha256::digest(msg)));
+    }
+
+    #[test]
+    fn different_digests_are_rejected() {
+        match hash_equality_prove(b"one file", b"another file", false) {
+            Err(ProofError::ConstraintFailed { .. }) => {}
+            Err(e) => panic!("expected ConstraintFailed, got {e}"),
+            Ok(_) => panic!("proved equality of two different digests"),
+        }
+    }
+}
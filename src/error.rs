@@ -0,0 +1,284 @@
+use std::{error::Error, fmt};
+
+use binius_frontend::PopulateError;
+
+/// Errors from building, proving or verifying a circuit.
+#[derive(Debug)]
+pub enum ProofError {
+    /// The witness could not be populated or does not satisfy the constraint system.
+    ///
+    /// `circuit` is the circuit's name and message length where known, `source` the
+    /// binius error: the failing `assert_eq`s when populating the witness, the violated
+    /// constraint when checking it.
+    ConstraintFailed {
+        circuit: Option<(&'static str, usize)>,
+        source: ConstraintError,
+    },
+    /// Verifier or prover setup was rejected, usually for its parameters: the
+    /// `log_inv_rate` it was given and, where known, the `max_len` the circuit was built
+    /// for.
+    SetupFailed {
+        log_inv_rate: usize,
+        max_len: Option<usize>,
+        source: Box<dyn Error + Send + Sync>,
+    },
+    /// The prover failed to produce a proof.
+    ProveFailed(binius_prover::Error),
+    /// The proof was rejected by the verifier.
+    VerifyFailed(Box<dyn Error + Send + Sync>),
+    /// The proof commits to another digest than the one the caller expected, whether or
+    /// not it verifies.
+    DigestMismatch {
+        expected: Vec<u8>,
+        committed: Vec<u8>,
+    },
+    /// The message is longer than the circuit was built for.
+    InputTooLong { got: usize, max: usize },
+    /// The message is shorter than a circuit that hashes exactly `expected` bytes.
+    InputTooShort { got: usize, expected: usize },
+    /// A circuit parameter is out of range.
+    InvalidParameter(String),
+    /// Reading or writing a proof or witness file failed.
+    Io(std::io::Error),
+    /// A proof file is truncated or not in the expected format.
+    InvalidProofFile(String),
+    /// A witness file is truncated, not in the expected format or for another circuit.
+    InvalidWitnessFile(String),
+}
+
+/// The binius error behind a `ProofError::ConstraintFailed`.
+#[derive(Debug)]
+pub enum ConstraintError {
+    /// `populate_wire_witness` failed, an `assert_eq` of the circuit does not hold.
+    Populate(PopulateError),
+    /// `verify_constraints` rejected the populated witness, with its description of the
+    /// violated constraint.
+    Violated(String),
+}
+
+impl fmt::Display for ConstraintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstraintError::Populate(e) => write!(f, "{e}"),
+            ConstraintError::Violated(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for ConstraintError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConstraintError::Populate(e) => Some(e),
+            ConstraintError::Violated(_) => None,
+        }
+    }
+}
+
+impl ProofError {
+    pub(crate) fn setup(e: impl Error + Send + Sync + 'static, log_inv_rate: usize) -> Self {
+        ProofError::SetupFailed {
+            log_inv_rate,
+            max_len: None,
+            source: Box::new(e),
+        }
+    }
+
+    // A witness rejected by `verify_constraints`.
+    pub(crate) fn violated(e: impl fmt::Display) -> Self {
+        ProofError::ConstraintFailed {
+            circuit: None,
+            source: ConstraintError::Violated(e.to_string()),
+        }
+    }
+
+    // Fill in the circuit and message length a `ConstraintFailed` came from and the
+    // `max_len` of a `SetupFailed`, other errors are returned unchanged.
+    pub(crate) fn in_circuit(self, name: &'static str, len: usize) -> Self {
+        match self {
+            ProofError::ConstraintFailed {
+                circuit: None,
+                source,
+            } => ProofError::ConstraintFailed {
+                circuit: Some((name, len)),
+                source,
+            },
+            ProofError::SetupFailed {
+                log_inv_rate,
+                max_len: None,
+                source,
+            } => ProofError::SetupFailed {
+                log_inv_rate,
+                max_len: Some(len),
+                source,
+            },
+            e => e,
+        }
+    }
+}
+
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProofError::ConstraintFailed {
+                circuit: Some((name, len)),
+                source,
+            } => write!(
+                f,
+                "constraint check failed: {name} circuit for {len} bytes: {source}"
+            ),
+            ProofError::ConstraintFailed {
+                circuit: None,
+                source,
+            } => write!(f, "constraint check failed: {source}"),
+            ProofError::SetupFailed {
+                log_inv_rate,
+                max_len: Some(max_len),
+                source,
+            } => write!(
+                f,
+                "setup failed for a circuit of {max_len} bytes at log_inv_rate {log_inv_rate}: {source}"
+            ),
+            ProofError::SetupFailed {
+                log_inv_rate,
+                max_len: None,
+                source,
+            } => write!(f, "setup failed at log_inv_rate {log_inv_rate}: {source}"),
+            ProofError::ProveFailed(e) => write!(f, "proving failed: {e}"),
+            ProofError::VerifyFailed(e) => write!(f, "verification failed: {e}"),
+            ProofError::DigestMismatch {
+                expected,
+                committed,
+            } => write!(
+                f,
+                "the proof commits to digest {} but {} was expected",
+                hex::encode(committed),
+                hex::encode(expected)
+            ),
+            ProofError::InputTooLong { got, max } => write!(
+                f,
+                "message is {got} bytes but the circuit was built for at most {max} bytes"
+            ),
+            ProofError::InputTooShort { got, expected } => write!(
+                f,
+                "message is {got} bytes but the circuit hashes exactly {expected} bytes"
+            ),
+            ProofError::InvalidParameter(e) => write!(f, "invalid parameter: {e}"),
+            ProofError::Io(e) => write!(f, "i/o error: {e}"),
+            ProofError::InvalidProofFile(e) => write!(f, "invalid proof file: {e}"),
+            ProofError::InvalidWitnessFile(e) => write!(f, "invalid witness file: {e}"),
+        }
+    }
+}
+
+impl Error for ProofError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ProofError::ConstraintFailed { source, .. } => Some(source),
+            ProofError::SetupFailed { source: e, .. } | ProofError::VerifyFailed(e) => {
+                Some(e.as_ref())
+            }
+            ProofError::ProveFailed(e) => Some(e),
+            ProofError::Io(e) => Some(e),
+            ProofError::DigestMismatch { .. }
+            | ProofError::InputTooLong { .. }
+            | ProofError::InputTooShort { .. }
+            | ProofError::InvalidParameter(_)
+            | ProofError::InvalidProofFile(_)
+            | ProofError::InvalidWitnessFile(_) => None,
+        }
+    }
+}
+
+impl From<PopulateError> for ProofError {
+    fn from(e: PopulateError) -> Self {
+        ProofError::ConstraintFailed {
+            circuit: None,
+            source: ConstraintError::Populate(e),
+        }
+    }
+}
+
+impl From<binius_prover::Error> for ProofError {
+    fn from(e: binius_prover::Error) -> Self {
+        ProofError::ProveFailed(e)
+    }
+}
+
+impl From<binius_verifier::Error> for ProofError {
+    fn from(e: binius_verifier::Error) -> Self {
+        ProofError::VerifyFailed(Box::new(e))
+    }
+}
+
+impl From<binius_transcript::Error> for ProofError {
+    fn from(e: binius_transcript::Error) -> Self {
+        ProofError::VerifyFailed(Box::new(e))
+    }
+}
+
+impl From<std::io::Error> for ProofError {
+    fn from(e: std::io::Error) -> Self {
+        ProofError::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use binius_core::word::Word;
+    use binius_frontend::CircuitBuilder;
+
+    #[test]
+    fn failed_assertion_keeps_the_binius_error() {
+        let builder = CircuitBuilder::new();
+        let (a, b) = (builder.add_witness(), builder.add_witness());
+        builder.assert_eq("a == b", a, b);
+        let circuit = builder.build();
+
+        let mut witness = circuit.new_witness_filler();
+        witness[a] = Word(1);
+        witness[b] = Word(2);
+        let e = ProofError::from(circuit.populate_wire_witness(&mut witness).unwrap_err())
+            .in_circuit("test", 16);
+
+        assert!(matches!(
+            e,
+            ProofError::ConstraintFailed {
+                circuit: Some(("test", 16)),
+                source: ConstraintError::Populate(_),
+            }
+        ));
+        let source = e.source().unwrap();
+        assert!(source.source().unwrap().is::<PopulateError>());
+        assert!(
+            e.to_string()
+                .starts_with("constraint check failed: test circuit for 16 bytes: ")
+        );
+    }
+
+    // 0 is outside `LOG_INV_RATES`, a rate 1 code the verifier refuses to set up
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn setup_failure_names_its_parameters() {
+        use crate::HashProver;
+        use crate::sha256::Sha256Hash;
+
+        let e = match HashProver::<Sha256Hash>::with_log_inv_rate(64, 0) {
+            Err(e) => e,
+            Ok(_) => panic!("set up a prover at log_inv_rate 0"),
+        };
+        assert!(matches!(
+            e,
+            ProofError::SetupFailed {
+                log_inv_rate: 0,
+                max_len: Some(64),
+                ..
+            }
+        ));
+        assert!(
+            e.to_string()
+                .starts_with("setup failed for a circuit of 64 bytes at log_inv_rate 0: ")
+        );
+        assert!(e.source().unwrap().is::<binius_verifier::Error>());
+    }
+}
@@ -0,0 +1,1939 @@
+use binius_core::{
+    constraint_system::{ConstraintSystem, ValueVec},
+    verify::verify_constraints,
+    word::Word,
+};
+use binius_frontend::{Circuit, CircuitBuilder, WitnessFiller};
+
+use binius_prover::{Prover, hash::parallel_compression::ParallelCompressionAdaptor};
+use binius_transcript::{ProverTranscript, VerifierTranscript, fiat_shamir::Challenger};
+use binius_verifier::{
+    Verifier,
+    config::StdChallenger,
+    hash::{StdCompression, StdDigest},
+};
+
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::config::RunConfig;
+use crate::error::ProofError;
+use crate::mem;
+use crate::timings::CircuitTimings;
+
+/// Packed field the prover works over, selected with at most one of the `packed-*`
+/// cargo features. Wider packings only pay off on CPUs with the matching SIMD support:
+///
+/// | feature       | packing                   | target CPU features                    |
+/// |---------------|---------------------------|----------------------------------------|
+/// | (none)        | `OptimalPackedB128`       | widest one enabled at compile time     |
+/// | `packed-1x128`| `PackedBinaryGhash1x128b` | portable, SSE2 + PCLMULQDQ, NEON + AES |
+/// | `packed-2x128`| `PackedBinaryGhash2x128b` | AVX2 + VPCLMULQDQ                      |
+/// | `packed-4x128`| `PackedBinaryGhash4x128b` | AVX-512F + VPCLMULQDQ                  |
+///
+/// `OptimalPackedB128` follows the `target_feature`s the crate is compiled with, so
+/// without `-C target-cpu=native` it falls back to the 128-bit packing.
+#[cfg(not(any(
+    feature = "packed-1x128",
+    feature = "packed-2x128",
+    feature = "packed-4x128"
+)))]
+pub type ProverPacking = binius_prover::OptimalPackedB128;
+#[cfg(feature = "packed-1x128")]
+pub type ProverPacking = binius_field::PackedBinaryGhash1x128b;
+#[cfg(feature = "packed-2x128")]
+pub type ProverPacking = binius_field::PackedBinaryGhash2x128b;
+#[cfg(feature = "packed-4x128")]
+pub type ProverPacking = binius_field::PackedBinaryGhash4x128b;
+
+#[cfg(any(
+    all(feature = "packed-1x128", feature = "packed-2x128"),
+    all(feature = "packed-1x128", feature = "packed-4x128"),
+    all(feature = "packed-2x128", feature = "packed-4x128"),
+))]
+compile_error!("enable at most one of the packed-1x128, packed-2x128 and packed-4x128 features");
+
+/// `log_inv_rate` used when none is given: log2 of the inverse rate of the Reed-Solomon
+/// code the prover encodes the witness with before committing to it, the second
+/// argument of `Verifier::setup`.
+///
+/// Each step up doubles the codeword length, so committing takes about twice the time
+/// and memory, but every FRI query then catches a cheating prover with higher
+/// probability, so fewer queries are needed for the same security and the proof gets
+/// smaller. 1 (rate 1/2) favours proving time.
+///
+/// The `sha256 log_inv_rate` group in `benches/hashes.rs` measures this on a 64 KiB
+/// message for each of 1 to 4 and prints the proof size next to it: proving time
+/// grows with the commit cost at every step, while the proof shrinks by less each time,
+/// since the query count falls roughly with `1 / log_inv_rate`.
+pub const DEFAULT_LOG_INV_RATE: usize = 1;
+
+/// `log_inv_rate`s the demo binary accepts. 0 would be a rate 1 code with no redundancy
+/// for the queries to check, and past 4 the codeword is 32 times the witness for little
+/// further saving in proof size.
+pub const LOG_INV_RATES: std::ops::RangeInclusive<usize> = 1..=4;
+
+type StdVerifier = Verifier<StdDigest, StdCompression>;
+type StdProver = Prover<ProverPacking, ParallelCompressionAdaptor<StdCompression>, StdDigest>;
+
+/// A hash gadget that can be driven by `run_hash_circuit`.
+///
+/// Implementors only wire up their gadget and fill its witness; building, constraint
+/// checking, proving and verifying are shared.
+pub trait HashCircuit: Sized {
+    /// Name printed in the demo output.
+    const NAME: &'static str;
+
+    /// Add the gadget to `builder` for messages of up to `max_len` bytes.
+    fn build(builder: &CircuitBuilder, max_len: usize) -> Self;
+
+    /// The `max_len` the gadget was built for. Gadgets with a length fixed at build time
+    /// hash exactly this many bytes.
+    fn max_len(&self) -> usize;
+
+    /// `ProofError::InputTooLong` if `msg` does not fit the gadget. Gadgets with a fixed
+    /// length override it with `check_exact_len`.
+    fn check_len(&self, msg: &[u8]) -> Result<(), ProofError> {
+        if msg.len() > self.max_len() {
+            return Err(ProofError::InputTooLong {
+                got: msg.len(),
+                max: self.max_len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Populate the message, length and expected digest wires for `msg`.
+    fn populate(&self, witness: &mut WitnessFiller, msg: &[u8]) {
+        self.populate_with_digest(witness, msg, &self.expected_digest(msg));
+    }
+
+    /// The digest `populate` commits to for `msg`. The default is `reference_digest`;
+    /// gadgets whose output depends on build parameters, such as an output length,
+    /// override it to compute the digest for theirs.
+    fn expected_digest(&self, msg: &[u8]) -> Vec<u8> {
+        Self::reference_digest(msg)
+    }
+
+    /// Length in bytes of the digest this gadget commits to, the length `digest` must
+    /// have in `populate_with_digest`.
+    fn digest_len(&self) -> usize {
+        self.expected_digest(&[]).len()
+    }
+
+    /// Populate the message and length wires for `msg`, with `digest` as the expected
+    /// digest instead of the one `reference_digest` computes. `digest` must have the
+    /// hash's output length; if it is not the digest of `msg` the witness does not
+    /// satisfy the constraints.
+    fn populate_with_digest(&self, witness: &mut WitnessFiller, msg: &[u8], digest: &[u8]);
+
+    /// Populate for a message given as 64-bit words instead of bytes, see
+    /// `words_to_bytes` for which bytes each word stands for. The default unpacks the
+    /// words and calls `populate`; gadgets whose message wires hold exactly these words
+    /// override it to assign them directly.
+    fn populate_words(&self, witness: &mut WitnessFiller, words: &[Word]) {
+        self.populate(witness, &words_to_bytes(words));
+    }
+
+    /// The digest committed to by `public`, the public words of a witness or proof of
+    /// this circuit, decoded from the gadget's own packing of its digest wires. Panics if
+    /// `public` is too short to hold the digest.
+    fn digest_from_public(&self, public: &[Word]) -> Vec<u8>;
+
+    /// Digest of `msg` computed by the CPU reference implementation.
+    fn reference_digest(msg: &[u8]) -> Vec<u8>;
+}
+
+/// The message bytes a sequence of words stands for: word `i` holds bytes `8i..8i + 8`
+/// in little-endian order.
+///
+/// This is the packing `populate_message` of the upstream sha256, sha512 and keccak
+/// gadgets uses for their message wires, so for those a `Word` message is exactly what
+/// ends up in the witness. The hand-built 32-bit gadgets (blake3, ripemd160) split each
+/// word into two message wires.
+pub fn words_to_bytes(words: &[Word]) -> Vec<u8> {
+    words.iter().flat_map(|w| w.0.to_le_bytes()).collect()
+}
+
+/// Byte order within each 64-bit word a digest is packed into as public words. Word `i`
+/// always holds digest bytes `8i..8i + 8`; this is which end of the word byte `8i` sits
+/// at.
+///
+/// Each gadget fixes its own order, following its hash's spec: SHA-2 reads its state
+/// words big-endian (`sha256::DIGEST_ENDIAN`), Keccak and BLAKE2 little-endian
+/// (`keccak::DIGEST_ENDIAN`, `blake2b::DIGEST_ENDIAN`). A consumer reading the public
+/// words with the other order gets every 8-byte group of the digest reversed, the usual
+/// cause of a digest that "doesn't match".
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WordEndian {
+    /// Byte `8i` is the most significant byte of word `i`.
+    Big,
+    /// Byte `8i` is the least significant byte of word `i`.
+    Little,
+}
+
+impl WordEndian {
+    pub fn word_from_bytes(self, bytes: [u8; 8]) -> Word {
+        match self {
+            WordEndian::Big => Word(u64::from_be_bytes(bytes)),
+            WordEndian::Little => Word(u64::from_le_bytes(bytes)),
+        }
+    }
+
+    pub fn word_to_bytes(self, word: Word) -> [u8; 8] {
+        match self {
+            WordEndian::Big => word.0.to_be_bytes(),
+            WordEndian::Little => word.0.to_le_bytes(),
+        }
+    }
+}
+
+/// Pack `digest` into words in `endian` order, the last word zero-padded if the digest
+/// is not a whole number of words.
+pub fn digest_to_words(digest: &[u8], endian: WordEndian) -> Vec<Word> {
+    digest
+        .chunks(8)
+        .map(|chunk| {
+            let mut bytes = [0u8; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            endian.word_from_bytes(bytes)
+        })
+        .collect()
+}
+
+/// The first `len` digest bytes packed into `words` in `endian` order, the inverse of
+/// `digest_to_words`.
+pub fn words_to_digest(words: &[Word], len: usize, endian: WordEndian) -> Vec<u8> {
+    let mut digest: Vec<u8> = words
+        .iter()
+        .flat_map(|&w| endian.word_to_bytes(w))
+        .collect();
+    digest.truncate(len);
+    digest
+}
+
+/// `digest` as lowercase hex without a `0x` prefix, as `sha256sum` and similar tools
+/// print it.
+pub fn digest_hex(digest: &[u8]) -> String {
+    hex::encode(digest)
+}
+
+/// `check_len` of a gadget that hashes exactly `len` bytes: `ProofError::InputTooLong`
+/// or `ProofError::InputTooShort` unless `msg` has that length. A shorter message would
+/// otherwise be zero padded and only fail as a digest mismatch deep in the constraints.
+pub fn check_exact_len(msg: &[u8], len: usize) -> Result<(), ProofError> {
+    if msg.len() > len {
+        return Err(ProofError::InputTooLong {
+            got: msg.len(),
+            max: len,
+        });
+    }
+    if msg.len() < len {
+        return Err(ProofError::InputTooShort {
+            got: msg.len(),
+            expected: len,
+        });
+    }
+    Ok(())
+}
+
+// The last `n` public words, where the digest commitment of a single hash circuit sits
+// since its inout wires are added last.
+pub(crate) fn digest_words(words: &[Word], n: usize) -> Result<&[Word], ProofError> {
+    if words.len() < n {
+        return Err(ProofError::InvalidParameter(format!(
+            "expected at least {n} public words for the digest, got {}",
+            words.len()
+        )));
+    }
+    Ok(&words[words.len() - n..])
+}
+
+// The `len`-byte digest packed into the last public words in `endian` order, the
+// layout of every gadget with a single digest of whole bytes per word.
+pub(crate) fn public_digest(public: &[Word], len: usize, endian: WordEndian) -> Vec<u8> {
+    let words = digest_words(public, len.div_ceil(8)).expect("public words hold the digest");
+    words_to_digest(words, len, endian)
+}
+
+// Build the circuit for messages of up to `max_len` bytes.
+// The constraint system only depends on `max_len`, so the verifier can rebuild it on its own.
+fn build_circuit<H: HashCircuit>(
+    max_len: usize,
+    build: impl FnOnce(&CircuitBuilder, usize) -> H,
+) -> (Circuit, H) {
+    let timer = Instant::now();
+    let builder = CircuitBuilder::new();
+    let hash = build(&builder, max_len);
+    let circuit = builder.build();
+    log::info!(
+        "{}: built circuit for {max_len} bytes in {:?}",
+        H::NAME,
+        timer.elapsed()
+    );
+    (circuit, hash)
+}
+
+/// The constraint system of `H` for messages of up to `max_len` bytes, without any
+/// witness, prover or verifier, e.g. to count constraints or to run `Verifier::setup`
+/// once and reuse the verifier for many proofs.
+///
+/// The constraint system depends only on `max_len`, never on the message content, so
+/// one built here matches the one any proof for a message of that length was made with.
+pub fn build_cs<H: HashCircuit>(max_len: usize) -> ConstraintSystem {
+    let (circuit, _) = build_circuit(max_len, H::build);
+    circuit.constraint_system().clone()
+}
+
+/// A human-readable summary of `cs` for debugging a circuit's size: its constraints by
+/// kind and its value vector layout by kind of word. Binius64 has two constraint kinds,
+/// bitwise AND and 64-bit MUL, which are the gates every gadget is built from.
+pub fn cs_summary(cs: &ConstraintSystem) -> String {
+    let layout = &cs.value_vec_layout;
+    format!(
+        "{} constraints: {} AND, {} MUL\n\
+         {} words: {} constant, {} inout, {} witness, {} internal",
+        cs.and_constraints.len() + cs.mul_constraints.len(),
+        cs.and_constraints.len(),
+        cs.mul_constraints.len(),
+        layout.n_const + layout.n_inout + layout.n_witness + layout.n_internal,
+        layout.n_const,
+        layout.n_inout,
+        layout.n_witness,
+        layout.n_internal
+    )
+}
+
+// Fill a fresh witness for `image_bytes` and derive the remaining wires. The expected
+// digest is `digest` if given, the reference digest otherwise. A message longer than the
+// gadget is rejected up front, the gadgets themselves would panic or fail somewhere
+// inside population.
+//
+// A supplied `digest` that the message does not hash to is reported as such, naming
+// both digests, instead of as the gadget's failing `assert_eq`, see
+// `check_supplied_digest`.
+fn populate_witness<H: HashCircuit>(
+    circuit: &Circuit,
+    hash: &H,
+    image_bytes: &[u8],
+    digest: Option<&[u8]>,
+) -> Result<ValueVec, ProofError> {
+    hash.check_len(image_bytes)?;
+    // the gadgets copy the digest into fixed-size arrays and would panic on another length
+    if let Some(digest) = digest.filter(|d| d.len() != hash.digest_len()) {
+        return Err(ProofError::InvalidParameter(format!(
+            "{} digests are {} bytes, got {}",
+            H::NAME,
+            hash.digest_len(),
+            digest.len()
+        )));
+    }
+
+    let timer = Instant::now();
+    let mut witness = circuit.new_witness_filler();
+    match digest {
+        Some(digest) => hash.populate_with_digest(&mut witness, image_bytes, digest),
+        None => hash.populate(&mut witness, image_bytes),
+    }
+    log::info!(
+        "{}: populated message and digest in {:?}",
+        H::NAME,
+        timer.elapsed()
+    );
+
+    let timer = Instant::now();
+    let derived = circuit.populate_wire_witness(&mut witness);
+    let witness_vec = derived.as_ref().ok().map(|_| witness.into_value_vec());
+    if let Some(digest) = digest {
+        check_supplied_digest(hash, image_bytes, digest, witness_vec.as_ref())
+            .map_err(|e| e.in_circuit(H::NAME, image_bytes.len()))?;
+    }
+    derived.map_err(|e| ProofError::from(e).in_circuit(H::NAME, image_bytes.len()))?;
+    log::info!("{}: derived wires in {:?}", H::NAME, timer.elapsed());
+    Ok(witness_vec.expect("the wires were derived"))
+}
+
+// The digest a caller supplied for `populate_with_digest` against the digest the
+// populated witness commits to, read from its public words. If deriving the wires
+// failed there is no such witness, and the supplied digest is compared against the
+// reference implementation's digest of `image_bytes` instead, which the error says.
+fn check_supplied_digest<H: HashCircuit>(
+    hash: &H,
+    image_bytes: &[u8],
+    digest: &[u8],
+    witness_vec: Option<&ValueVec>,
+) -> Result<(), ProofError> {
+    let (computed, source) = match witness_vec {
+        Some(witness_vec) => (
+            hash.digest_from_public(witness_vec.public()),
+            "committed by the witness",
+        ),
+        None => (
+            hash.expected_digest(image_bytes),
+            "of the message by the reference implementation, deriving the wires failed",
+        ),
+    };
+    if digest != computed {
+        return Err(ProofError::violated(format!(
+            "supplied digest {} does not match the digest {} {source}",
+            digest_hex(digest),
+            digest_hex(&computed)
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn setup_verifier(
+    circuit: &Circuit,
+    log_inv_rate: usize,
+) -> Result<StdVerifier, ProofError> {
+    let cs = circuit.constraint_system();
+    Verifier::<StdDigest, _>::setup(cs.clone(), log_inv_rate, StdCompression::default())
+        .map_err(|e| ProofError::setup(e, log_inv_rate))
+}
+
+fn setup(circuit: &Circuit, log_inv_rate: usize) -> Result<(StdVerifier, StdProver), ProofError> {
+    let compression = ParallelCompressionAdaptor::new(StdCompression::default());
+    let verifier = setup_verifier(circuit, log_inv_rate)?;
+    let prover = Prover::<ProverPacking, _, StdDigest>::setup(verifier.clone(), compression)
+        .map_err(|e| ProofError::setup(e, log_inv_rate))?;
+    Ok((verifier, prover))
+}
+
+/// Outcome of proving one message with `HashProver::prove_many`: the proof bytes and
+/// public words, or why that message could not be proven.
+pub type ProofResult = Result<(Vec<u8>, Vec<Word>), ProofError>;
+
+/// A hash circuit built once for messages of up to `max_len` bytes, with the verifier
+/// and prover already set up, so many messages can be proven without rebuilding the
+/// circuit each time.
+pub struct HashProver<H> {
+    max_len: usize,
+    circuit: Circuit,
+    hash: H,
+    verifier: StdVerifier,
+    prover: StdProver,
+}
+
+impl<H: HashCircuit> HashProver<H> {
+    pub fn new(max_len: usize) -> Result<Self, ProofError> {
+        Self::with_log_inv_rate(max_len, DEFAULT_LOG_INV_RATE)
+    }
+
+    /// Like `new`, with the prover and verifier set up for `log_inv_rate` instead of
+    /// `DEFAULT_LOG_INV_RATE`.
+    pub fn with_log_inv_rate(max_len: usize, log_inv_rate: usize) -> Result<Self, ProofError> {
+        let (circuit, hash) = build_circuit(max_len, H::build);
+        let (verifier, prover) =
+            setup(&circuit, log_inv_rate).map_err(|e| e.in_circuit(H::NAME, max_len))?;
+
+        Ok(Self {
+            max_len,
+            circuit,
+            hash,
+            verifier,
+            prover,
+        })
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// The constraint system proofs are made for, e.g. to report its size.
+    pub fn constraint_system(&self) -> &ConstraintSystem {
+        self.circuit.constraint_system()
+    }
+
+    /// Prove H(m) = h for `image_bytes`, returning the proof bytes and public words.
+    pub fn prove(&self, image_bytes: &[u8]) -> Result<(Vec<u8>, Vec<Word>), ProofError> {
+        self.prove_with_challenger(image_bytes, StdChallenger::default())
+    }
+
+    /// Like `prove`, with the prover transcript built from `challenger`.
+    pub fn prove_with_challenger<C: Challenger>(
+        &self,
+        image_bytes: &[u8],
+        challenger: C,
+    ) -> Result<(Vec<u8>, Vec<Word>), ProofError> {
+        let witness_vec = self.populate(image_bytes)?;
+        self.prove_witness_with_challenger(witness_vec, challenger)
+    }
+
+    /// The checked witness for `image_bytes`, populated by `H::populate`, that
+    /// `prove_witness` takes. Proving it later separates witness generation from the
+    /// proof itself, e.g. to time only the latter.
+    pub fn populate(&self, image_bytes: &[u8]) -> Result<ValueVec, ProofError> {
+        populate_witness(&self.circuit, &self.hash, image_bytes, None)
+    }
+
+    /// Prove a witness returned by `populate`, returning the proof bytes and public
+    /// words.
+    pub fn prove_witness(&self, witness_vec: ValueVec) -> Result<(Vec<u8>, Vec<Word>), ProofError> {
+        self.prove_witness_with_challenger(witness_vec, StdChallenger::default())
+    }
+
+    fn prove_witness_with_challenger<C: Challenger>(
+        &self,
+        witness_vec: ValueVec,
+        challenger: C,
+    ) -> Result<(Vec<u8>, Vec<Word>), ProofError> {
+        let mut prover_transcript = ProverTranscript::new(challenger);
+        let public_words = witness_vec.public().to_vec();
+
+        self.prover.prove(witness_vec, &mut prover_transcript)?;
+
+        Ok((prover_transcript.finalize(), public_words))
+    }
+
+    /// Prove every message in `messages` independently, with up to `max_parallel`
+    /// proofs in flight at a time, and return one result per message in input order.
+    /// One message failing does not stop the others.
+    ///
+    /// All tasks share this prover's single setup. `Prover::prove` takes `&self` and the
+    /// binius `Prover` only holds read-only setup data, so it is `Sync` and is not cloned
+    /// per task. Each proof still parallelizes internally on rayon's global pool, so
+    /// `max_parallel` bounds the number of witnesses held in memory and proofs competing
+    /// for the cores, not the total number of threads.
+    pub fn prove_many(&self, messages: &[Vec<u8>], max_parallel: usize) -> Vec<ProofResult>
+    where
+        H: Sync,
+    {
+        let n_tasks = max_parallel.clamp(1, messages.len().max(1));
+        let next = AtomicUsize::new(0);
+        let mut results: Vec<Option<ProofResult>> = messages.iter().map(|_| None).collect();
+
+        std::thread::scope(|scope| {
+            // each task takes the next unproven message until none are left
+            let tasks: Vec<_> = (0..n_tasks)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut proven = Vec::new();
+                        loop {
+                            let i = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(msg) = messages.get(i) else {
+                                break proven;
+                            };
+                            proven.push((i, self.prove(msg)));
+                        }
+                    })
+                })
+                .collect();
+
+            for task in tasks {
+                for (i, result) in task.join().expect("proving task panicked") {
+                    results[i] = Some(result);
+                }
+            }
+        });
+
+        results
+            .into_iter()
+            .map(|result| result.expect("every message was taken by a task"))
+            .collect()
+    }
+
+    /// Verify a proof produced by `prove`.
+    pub fn verify(&self, proof: Vec<u8>, public_words: &[Word]) -> Result<(), ProofError> {
+        self.verify_with_challenger(proof, public_words, StdChallenger::default())
+    }
+
+    /// Verify many proofs produced by `prove`, each given as its public words and proof
+    /// bytes, stopping at the first that fails with its index and error.
+    ///
+    /// The proofs are verified one after another against this prover's single verifier
+    /// setup, so the setup cost is paid once for the batch rather than per proof. They
+    /// are independent and could run on rayon's pool instead, but verifying is cheap next
+    /// to proving, and a parallel run would have to finish or cancel the proofs in
+    /// flight before it can report the first failure, which loses the early exit. See
+    /// `verify_all` to check every proof.
+    pub fn verify_batch(&self, proofs: &[(Vec<Word>, Vec<u8>)]) -> Result<(), (usize, ProofError)> {
+        for (i, (public_words, proof)) in proofs.iter().enumerate() {
+            self.verify(proof.clone(), public_words)
+                .map_err(|e| (i, e))?;
+        }
+        Ok(())
+    }
+
+    /// Like `verify_batch`, but verify every proof and return all failures with their
+    /// indices, in order. Empty if every proof verifies.
+    pub fn verify_all(&self, proofs: &[(Vec<Word>, Vec<u8>)]) -> Vec<(usize, ProofError)> {
+        proofs
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (public_words, proof))| {
+                self.verify(proof.clone(), public_words)
+                    .err()
+                    .map(|e| (i, e))
+            })
+            .collect()
+    }
+
+    /// Verify a proof produced by `prove_with_challenger`, `challenger` must match the
+    /// one the proof was made with.
+    pub fn verify_with_challenger<C: Challenger>(
+        &self,
+        proof: Vec<u8>,
+        public_words: &[Word],
+        challenger: C,
+    ) -> Result<(), ProofError> {
+        let mut verifier_transcript = VerifierTranscript::new(challenger, proof);
+        self.verifier
+            .verify(public_words, &mut verifier_transcript)?;
+        verifier_transcript.finalize()?;
+        Ok(())
+    }
+}
+
+/// Verifiers for `H`, set up once per `max_len` and reused for every later proof of the
+/// same length, for verifying many proofs without a `HashProver`.
+///
+/// `verify_hash` rebuilds the circuit and passes a clone of its constraint system to
+/// `Verifier::setup` on every call. Here both happen once per `max_len`, on the first
+/// proof for it, and the verifier is shared from then on. The cache is behind a mutex,
+/// so one instance can serve concurrent requests; the lock is only held to look up or
+/// insert a verifier, not while verifying. The `sha256 verify` group in
+/// `benches/hashes.rs` measures the difference to `verify_hash`.
+pub struct VerifierCache<H> {
+    log_inv_rate: usize,
+    verifiers: Mutex<HashMap<usize, Arc<StdVerifier>>>,
+    _hash: PhantomData<fn() -> H>,
+}
+
+impl<H: HashCircuit> Default for VerifierCache<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: HashCircuit> VerifierCache<H> {
+    pub fn new() -> Self {
+        Self::with_log_inv_rate(DEFAULT_LOG_INV_RATE)
+    }
+
+    /// Like `new`, for proofs made with `log_inv_rate`.
+    pub fn with_log_inv_rate(log_inv_rate: usize) -> Self {
+        Self {
+            log_inv_rate,
+            verifiers: Mutex::new(HashMap::new()),
+            _hash: PhantomData,
+        }
+    }
+
+    // The verifier for `max_len`, set up now if this is the first proof of that length.
+    fn verifier(&self, max_len: usize) -> Result<Arc<StdVerifier>, ProofError> {
+        let lock = || self.verifiers.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(verifier) = lock().get(&max_len) {
+            return Ok(verifier.clone());
+        }
+        // set up without the lock, two threads racing for the same length both set up
+        // and the second insert wins, which is harmless
+        let (circuit, _) = build_circuit(max_len, H::build);
+        let verifier = Arc::new(
+            setup_verifier(&circuit, self.log_inv_rate)
+                .map_err(|e| e.in_circuit(H::NAME, max_len))?,
+        );
+        lock().insert(max_len, verifier.clone());
+        Ok(verifier)
+    }
+
+    /// Verify a proof for a message of `max_len` bytes, as `verify_hash` with `size` =
+    /// `max_len` does.
+    pub fn verify(
+        &self,
+        max_len: usize,
+        proof: Vec<u8>,
+        public_words: &[Word],
+    ) -> Result<(), ProofError> {
+        let verifier = self.verifier(max_len)?;
+        let mut verifier_transcript = VerifierTranscript::new(StdChallenger::default(), proof);
+        verifier.verify(public_words, &mut verifier_transcript)?;
+        verifier_transcript.finalize()?;
+        Ok(())
+    }
+}
+
+/// Prove H(m) = h and return the finalized proof bytes with the public words.
+///
+/// Proofs are deterministic. `Prover::prove` takes no source of randomness, only the
+/// witness and the transcript, and every challenge comes from the challenger, so the
+/// same message, `log_inv_rate` and challenger give byte-identical proofs, whether from
+/// one setup or two. Proofs can be cached and compared by their bytes.
+pub fn prove_hash<H: HashCircuit>(
+    image_bytes: &[u8],
+    verbose: bool,
+) -> Result<(Vec<u8>, Vec<Word>, CircuitTimings), ProofError> {
+    prove_hash_with(image_bytes, verbose, H::build)
+}
+
+/// Like `prove_hash`, with the circuit built for messages of up to `max_len` bytes
+/// instead of exactly `image_bytes.len()`, for gadgets that take a variable length. The
+/// message is padded inside the gadget and its true length goes into the length wire.
+/// Pass `max_len` as the `size` of `verify_hash`: every message up to `max_len` bytes
+/// gets the same constraint system, so one verifier setup serves them all.
+pub fn prove_hash_with_max_len<H: HashCircuit>(
+    image_bytes: &[u8],
+    max_len: usize,
+    verbose: bool,
+) -> Result<(Vec<u8>, Vec<Word>, CircuitTimings), ProofError> {
+    let (circuit, hash) = build_circuit(max_len, H::build);
+
+    let witness_vec = populate_witness(&circuit, &hash, image_bytes, None)?;
+
+    prove_circuit(&circuit, witness_vec, StdChallenger::default(), verbose)
+        .map_err(|e| e.in_circuit(H::NAME, max_len))
+}
+
+/// Like `prove_hash`, but the gadget is added by `build` instead of `H::build`, for
+/// gadgets that take parameters beyond the message length.
+pub fn prove_hash_with<H: HashCircuit>(
+    image_bytes: &[u8],
+    verbose: bool,
+    build: impl FnOnce(&CircuitBuilder, usize) -> H,
+) -> Result<(Vec<u8>, Vec<Word>, CircuitTimings), ProofError> {
+    let (circuit, hash) = build_circuit(image_bytes.len(), build);
+
+    let witness_vec = populate_witness(&circuit, &hash, image_bytes, None)?;
+
+    prove_circuit(&circuit, witness_vec, StdChallenger::default(), verbose)
+        .map_err(|e| e.in_circuit(H::NAME, image_bytes.len()))
+}
+
+/// Prove knowledge of a preimage of an externally supplied `digest`, e.g. a hash
+/// published on-chain or in a header, without recomputing the digest from
+/// `image_bytes`.
+///
+/// `digest` is committed as the public output as is, so if `image_bytes` does not hash
+/// to it this fails with `ProofError::ConstraintFailed` before anything is proven, its
+/// message naming the supplied digest and the one the populated witness commits to, or
+/// the reference digest if the witness could not be derived. A `digest` that is not
+/// `H`'s digest length is `ProofError::InvalidParameter`.
+pub fn prove_hash_with_digest<H: HashCircuit>(
+    image_bytes: &[u8],
+    digest: &[u8],
+    verbose: bool,
+) -> Result<(Vec<u8>, Vec<Word>, CircuitTimings), ProofError> {
+    let (circuit, hash) = build_circuit(image_bytes.len(), H::build);
+
+    let witness_vec = populate_witness(&circuit, &hash, image_bytes, Some(digest))?;
+
+    prove_circuit(&circuit, witness_vec, StdChallenger::default(), verbose)
+        .map_err(|e| e.in_circuit(H::NAME, image_bytes.len()))
+}
+
+/// Like `prove_hash`, for a message of 64-bit words, e.g. data that is already word
+/// aligned. The circuit is built for `8 * words.len()` bytes, which is the `size` to
+/// pass to `verify_hash`.
+pub fn prove_hash_words<H: HashCircuit>(
+    words: &[Word],
+    verbose: bool,
+) -> Result<(Vec<u8>, Vec<Word>, CircuitTimings), ProofError> {
+    let len = 8 * words.len();
+    let (circuit, hash) = build_circuit(len, H::build);
+
+    let mut witness = circuit.new_witness_filler();
+    hash.populate_words(&mut witness, words);
+    circuit
+        .populate_wire_witness(&mut witness)
+        .map_err(|e| ProofError::from(e).in_circuit(H::NAME, len))?;
+
+    prove_circuit(
+        &circuit,
+        witness.into_value_vec(),
+        StdChallenger::default(),
+        verbose,
+    )
+    .map_err(|e| e.in_circuit(H::NAME, len))
+}
+
+/// Like `prove_hash`, with the prover set up for `log_inv_rate` instead of
+/// `DEFAULT_LOG_INV_RATE`. The proof only verifies with the same `log_inv_rate`.
+pub fn prove_hash_with_rate<H: HashCircuit>(
+    image_bytes: &[u8],
+    log_inv_rate: usize,
+    verbose: bool,
+) -> Result<(Vec<u8>, Vec<Word>, CircuitTimings), ProofError> {
+    let (circuit, hash) = build_circuit(image_bytes.len(), H::build);
+
+    let witness_vec = populate_witness(&circuit, &hash, image_bytes, None)?;
+
+    prove_circuit_with_rate(
+        &circuit,
+        witness_vec,
+        StdChallenger::default(),
+        log_inv_rate,
+        verbose,
+    )
+    .map_err(|e| e.in_circuit(H::NAME, image_bytes.len()))
+}
+
+/// Like `prove_hash`, with the prover transcript built from `challenger` instead of
+/// `StdChallenger`, e.g. to match an external verifier's Fiat-Shamir setup.
+pub fn prove_hash_with_challenger<H: HashCircuit, C: Challenger>(
+    image_bytes: &[u8],
+    challenger: C,
+    verbose: bool,
+) -> Result<(Vec<u8>, Vec<Word>, CircuitTimings), ProofError> {
+    let (circuit, hash) = build_circuit(image_bytes.len(), H::build);
+
+    let witness_vec = populate_witness(&circuit, &hash, image_bytes, None)?;
+
+    prove_circuit(&circuit, witness_vec, challenger, verbose)
+        .map_err(|e| e.in_circuit(H::NAME, image_bytes.len()))
+}
+
+/// Like `prove_hash`, with the domain-separation tag `domain` absorbed into the
+/// transcript before anything else, so that every challenge depends on it. The proof
+/// only verifies with `verify_hash_in_domain` and the same tag, which keeps a proof made
+/// for one context from being replayed in another that uses a different tag.
+///
+/// The tag is absorbed with its length, so no tag is a prefix of another. An empty tag
+/// absorbs nothing and gives the same proof as `prove_hash`.
+pub fn prove_hash_in_domain<H: HashCircuit>(
+    image_bytes: &[u8],
+    domain: &[u8],
+    verbose: bool,
+) -> Result<(Vec<u8>, Vec<Word>, CircuitTimings), ProofError> {
+    let (circuit, hash) = build_circuit(image_bytes.len(), H::build);
+
+    let witness_vec = populate_witness(&circuit, &hash, image_bytes, None)?;
+
+    prove_circuit_checked(
+        &circuit,
+        witness_vec,
+        StdChallenger::default(),
+        DEFAULT_LOG_INV_RATE,
+        domain,
+        true,
+        verbose,
+    )
+    .map_err(|e| e.in_circuit(H::NAME, image_bytes.len()))
+}
+
+// What a non-empty domain tag absorbs: its length as 8 little-endian bytes, then the tag.
+fn domain_prefix(domain: &[u8]) -> Vec<u8> {
+    let mut prefix = (domain.len() as u64).to_le_bytes().to_vec();
+    prefix.extend_from_slice(domain);
+    prefix
+}
+
+/// Constraint-check, set up and prove an already populated witness for `circuit`.
+///
+/// This is the part of `prove_hash` shared with circuits that are not a single
+/// `HashCircuit`, such as batches of hashes.
+pub fn prove_circuit<C: Challenger>(
+    circuit: &Circuit,
+    witness_vec: ValueVec,
+    challenger: C,
+    verbose: bool,
+) -> Result<(Vec<u8>, Vec<Word>, CircuitTimings), ProofError> {
+    prove_circuit_with_rate(
+        circuit,
+        witness_vec,
+        challenger,
+        DEFAULT_LOG_INV_RATE,
+        verbose,
+    )
+}
+
+/// Like `prove_circuit`, with the prover set up for `log_inv_rate`.
+pub fn prove_circuit_with_rate<C: Challenger>(
+    circuit: &Circuit,
+    witness_vec: ValueVec,
+    challenger: C,
+    log_inv_rate: usize,
+    verbose: bool,
+) -> Result<(Vec<u8>, Vec<Word>, CircuitTimings), ProofError> {
+    prove_circuit_checked(
+        circuit,
+        witness_vec,
+        challenger,
+        log_inv_rate,
+        &[],
+        true,
+        verbose,
+    )
+}
+
+// Like `prove_circuit_with_rate`, with `domain` absorbed as in `prove_hash_in_domain` and
+// the local constraint check only if `check`.
+fn prove_circuit_checked<C: Challenger>(
+    circuit: &Circuit,
+    witness_vec: ValueVec,
+    challenger: C,
+    log_inv_rate: usize,
+    domain: &[u8],
+    check: bool,
+    verbose: bool,
+) -> Result<(Vec<u8>, Vec<Word>, CircuitTimings), ProofError> {
+    let mut timings = if check {
+        check_circuit(circuit, &witness_vec, verbose)?
+    } else {
+        let mut timings = CircuitTimings::default();
+        timings.record_circuit_stats(circuit.constraint_system());
+        if verbose {
+            println!("Skipped local constraint check");
+        }
+        timings
+    };
+    timings.log_inv_rate = log_inv_rate;
+
+    // Start timer for setup
+    let setup_timer = Instant::now();
+    let (_, prover) = setup(circuit, log_inv_rate)?;
+
+    timings.setup_ms = setup_timer.elapsed().as_millis();
+    log::info!("set up prover in {:?}", setup_timer.elapsed());
+    if verbose {
+        println!("Setup time {}ms", timings.setup_ms);
+    }
+
+    let mut prover_transcript = ProverTranscript::new(challenger);
+    if !domain.is_empty() {
+        prover_transcript
+            .observe()
+            .write_bytes(&domain_prefix(domain));
+    }
+    let public_words = witness_vec.public().to_vec();
+
+    let prove_timer = Instant::now();
+
+    prover.prove(witness_vec, &mut prover_transcript)?;
+    let proof = prover_transcript.finalize();
+
+    timings.prove_ms = prove_timer.elapsed().as_millis();
+    timings.proof_bytes = proof.len();
+    timings.peak_rss_bytes = mem::peak_rss_bytes();
+    log::info!("proved in {:?}", prove_timer.elapsed());
+    if verbose {
+        println!("Proof time {}ms", timings.prove_ms);
+        println!("Proof size {} bytes", timings.proof_bytes);
+    }
+
+    Ok((proof, public_words, timings))
+}
+
+/// Build the circuit for `image_bytes`, populate the witness and check it against the
+/// constraint system, without any prover or verifier setup. Fails with the same
+/// `ProofError::ConstraintFailed` as `prove_hash` would.
+pub fn check_hash<H: HashCircuit>(
+    image_bytes: &[u8],
+    verbose: bool,
+) -> Result<CircuitTimings, ProofError> {
+    check_hash_with(image_bytes, verbose, H::build)
+}
+
+/// Like `check_hash`, with `digest` as the expected digest as in `prove_hash_with_digest`.
+/// Passes exactly when the digest the circuit computes for `image_bytes` is `digest`.
+pub fn check_hash_with_digest<H: HashCircuit>(
+    image_bytes: &[u8],
+    digest: &[u8],
+    verbose: bool,
+) -> Result<CircuitTimings, ProofError> {
+    let (circuit, hash) = build_circuit(image_bytes.len(), H::build);
+    let witness_vec = populate_witness(&circuit, &hash, image_bytes, Some(digest))?;
+    check_circuit(&circuit, &witness_vec, verbose)
+        .map_err(|e| e.in_circuit(H::NAME, image_bytes.len()))
+}
+
+/// Like `check_hash`, with the gadget added by `build` as in `prove_hash_with`.
+pub fn check_hash_with<H: HashCircuit>(
+    image_bytes: &[u8],
+    verbose: bool,
+    build: impl FnOnce(&CircuitBuilder, usize) -> H,
+) -> Result<CircuitTimings, ProofError> {
+    if verbose {
+        println!("Constraint check for {} circuit: ", H::NAME);
+    }
+
+    let (circuit, hash) = build_circuit(image_bytes.len(), build);
+    let witness_vec = populate_witness(&circuit, &hash, image_bytes, None)?;
+    let timings = check_circuit(&circuit, &witness_vec, verbose)
+        .map_err(|e| e.in_circuit(H::NAME, image_bytes.len()))?;
+    if verbose {
+        print_digest(&hash, image_bytes);
+    }
+    Ok(timings)
+}
+
+// The digest wires hold the reference digest and the gadget asserts its own output equal
+// to them, so once the constraints hold this is the digest the circuit computed.
+fn print_digest<H: HashCircuit>(hash: &H, image_bytes: &[u8]) {
+    println!("Digest {}", digest_hex(&hash.expected_digest(image_bytes)));
+}
+
+/// The public words of `H`'s circuit for `image_bytes`, after the witness has been
+/// derived and checked against the constraints, without any prover setup or proof.
+///
+/// The digest wires are filled with the reference digest and the gadget asserts its own
+/// output equal to them, so the digest words among these are the ones the gadget
+/// computes: the check fails with `ProofError::ConstraintFailed` if they differ.
+pub fn digest_only_words<H: HashCircuit>(image_bytes: &[u8]) -> Result<Vec<Word>, ProofError> {
+    let (_, words) = checked_public_words::<H>(image_bytes)?;
+    Ok(words)
+}
+
+/// The digest of `image_bytes` as derived by `H`'s circuit, a cheap way to validate a
+/// gadget against reference vectors without the prover, see `digest_only_words`. The
+/// digest is read back from the checked witness's public words with
+/// `HashCircuit::digest_from_public`.
+pub fn digest_only<H: HashCircuit>(image_bytes: &[u8]) -> Result<Vec<u8>, ProofError> {
+    let (hash, words) = checked_public_words::<H>(image_bytes)?;
+    Ok(hash.digest_from_public(&words))
+}
+
+// Build `H`'s circuit for `image_bytes`, populate and check the witness and return the
+// gadget with the witness's public words.
+fn checked_public_words<H: HashCircuit>(image_bytes: &[u8]) -> Result<(H, Vec<Word>), ProofError> {
+    let (circuit, hash) = build_circuit(image_bytes.len(), H::build);
+    let witness_vec = populate_witness(&circuit, &hash, image_bytes, None)?;
+    verify_constraints(circuit.constraint_system(), &witness_vec)
+        .map_err(|e| ProofError::violated(e).in_circuit(H::NAME, image_bytes.len()))?;
+    Ok((hash, witness_vec.public().to_vec()))
+}
+
+/// Check that `witness_vec` satisfies the constraints of `circuit`.
+/// Only the constraint check and circuit size fields of the returned timings are
+/// filled in.
+pub fn check_circuit(
+    circuit: &Circuit,
+    witness_vec: &ValueVec,
+    verbose: bool,
+) -> Result<CircuitTimings, ProofError> {
+    let mut timings = CircuitTimings::default();
+
+    let cs = circuit.constraint_system();
+    timings.record_circuit_stats(cs);
+    if verbose {
+        println!(
+            "Circuit: {} constraints, {} witness words, {} public words",
+            timings.n_constraints, timings.n_witness, timings.n_public
+        );
+    }
+    let constraint_timer = Instant::now();
+    verify_constraints(cs, witness_vec).map_err(ProofError::violated)?;
+    timings.constraint_check_ms = constraint_timer.elapsed().as_millis();
+    log::info!("checked constraints in {:?}", constraint_timer.elapsed());
+
+    if verbose {
+        println!("✓ constraint verified");
+    }
+
+    Ok(timings)
+}
+
+/// Verify a proof produced by `prove_hash` for a message of `size` bytes.
+/// Only the setup and verify fields of the returned timings are filled in.
+pub fn verify_hash<H: HashCircuit>(
+    size: usize,
+    proof: Vec<u8>,
+    public_words: &[Word],
+    verbose: bool,
+) -> Result<CircuitTimings, ProofError> {
+    verify_hash_with(size, proof, public_words, verbose, H::build)
+}
+
+/// Like `verify_hash`, with the gadget added by `build` as in `prove_hash_with`.
+pub fn verify_hash_with<H: HashCircuit>(
+    size: usize,
+    proof: Vec<u8>,
+    public_words: &[Word],
+    verbose: bool,
+    build: impl FnOnce(&CircuitBuilder, usize) -> H,
+) -> Result<CircuitTimings, ProofError> {
+    let (circuit, _) = build_circuit(size, build);
+    verify_circuit(
+        &circuit,
+        proof,
+        public_words,
+        StdChallenger::default(),
+        verbose,
+    )
+    .map_err(|e| e.in_circuit(H::NAME, size))
+}
+
+/// Like `verify_hash`, and check that the proof commits to `expected`, failing with
+/// `ProofError::DigestMismatch` if it commits to any other digest. A proof that verifies
+/// only shows that some message hashes to the digest in its public words, so a client
+/// that knows which digest it wants must compare it as well.
+pub fn verify_hash_with_expected_digest<H: HashCircuit>(
+    size: usize,
+    proof: Vec<u8>,
+    public_words: &[Word],
+    expected: &[u8],
+    verbose: bool,
+) -> Result<CircuitTimings, ProofError> {
+    // the gadget alone decodes the public words, the cheap check goes first so a proof
+    // for the wrong digest is rejected without verifying it
+    let hash = H::build(&CircuitBuilder::new(), size);
+    digest_words(public_words, hash.digest_len().div_ceil(8))?;
+    let committed = hash.digest_from_public(public_words);
+    if committed != expected {
+        return Err(ProofError::DigestMismatch {
+            expected: expected.to_vec(),
+            committed,
+        });
+    }
+
+    verify_hash::<H>(size, proof, public_words, verbose)
+}
+
+/// Verify a proof produced by `prove_hash_with_rate` with the same `log_inv_rate`.
+pub fn verify_hash_with_rate<H: HashCircuit>(
+    size: usize,
+    proof: Vec<u8>,
+    public_words: &[Word],
+    log_inv_rate: usize,
+    verbose: bool,
+) -> Result<CircuitTimings, ProofError> {
+    let (circuit, _) = build_circuit(size, H::build);
+    verify_circuit_with_rate(
+        &circuit,
+        proof,
+        public_words,
+        StdChallenger::default(),
+        log_inv_rate,
+        verbose,
+    )
+    .map_err(|e| e.in_circuit(H::NAME, size))
+}
+
+/// Verify a proof produced by `prove_hash_with_challenger`, `challenger` must match the
+/// one the proof was made with.
+pub fn verify_hash_with_challenger<H: HashCircuit, C: Challenger>(
+    size: usize,
+    proof: Vec<u8>,
+    public_words: &[Word],
+    challenger: C,
+    verbose: bool,
+) -> Result<CircuitTimings, ProofError> {
+    let (circuit, _) = build_circuit(size, H::build);
+    verify_circuit(&circuit, proof, public_words, challenger, verbose)
+        .map_err(|e| e.in_circuit(H::NAME, size))
+}
+
+/// Verify a proof produced by `prove_hash_in_domain`. Fails unless `domain` is the tag
+/// the proof was made with.
+pub fn verify_hash_in_domain<H: HashCircuit>(
+    size: usize,
+    proof: Vec<u8>,
+    public_words: &[Word],
+    domain: &[u8],
+    verbose: bool,
+) -> Result<CircuitTimings, ProofError> {
+    let (circuit, _) = build_circuit(size, H::build);
+    verify_circuit_in_domain(
+        &circuit,
+        proof,
+        public_words,
+        StdChallenger::default(),
+        DEFAULT_LOG_INV_RATE,
+        domain,
+        verbose,
+    )
+    .map_err(|e| e.in_circuit(H::NAME, size))
+}
+
+/// Set up a verifier for `circuit` and verify `proof` against `public_words`.
+/// Only the setup and verify fields of the returned timings are filled in.
+pub fn verify_circuit<C: Challenger>(
+    circuit: &Circuit,
+    proof: Vec<u8>,
+    public_words: &[Word],
+    challenger: C,
+    verbose: bool,
+) -> Result<CircuitTimings, ProofError> {
+    verify_circuit_with_rate(
+        circuit,
+        proof,
+        public_words,
+        challenger,
+        DEFAULT_LOG_INV_RATE,
+        verbose,
+    )
+}
+
+/// Like `verify_circuit`, with the verifier set up for `log_inv_rate`, which must be
+/// the one the proof was made with.
+pub fn verify_circuit_with_rate<C: Challenger>(
+    circuit: &Circuit,
+    proof: Vec<u8>,
+    public_words: &[Word],
+    challenger: C,
+    log_inv_rate: usize,
+    verbose: bool,
+) -> Result<CircuitTimings, ProofError> {
+    verify_circuit_in_domain(
+        circuit,
+        proof,
+        public_words,
+        challenger,
+        log_inv_rate,
+        &[],
+        verbose,
+    )
+}
+
+// Like `verify_circuit_with_rate`, with `domain` absorbed as in `verify_hash_in_domain`.
+fn verify_circuit_in_domain<C: Challenger>(
+    circuit: &Circuit,
+    proof: Vec<u8>,
+    public_words: &[Word],
+    challenger: C,
+    log_inv_rate: usize,
+    domain: &[u8],
+    verbose: bool,
+) -> Result<CircuitTimings, ProofError> {
+    let mut timings = CircuitTimings {
+        log_inv_rate,
+        ..Default::default()
+    };
+
+    let setup_timer = Instant::now();
+    let verifier = setup_verifier(circuit, log_inv_rate)?;
+    timings.setup_ms = setup_timer.elapsed().as_millis();
+    log::info!("set up verifier in {:?}", setup_timer.elapsed());
+
+    let mut verifier_transcript = VerifierTranscript::new(challenger, proof);
+    if !domain.is_empty() {
+        verifier_transcript
+            .observe()
+            .write_bytes(&domain_prefix(domain));
+    }
+
+    let verify_timer = Instant::now();
+
+    verifier.verify(public_words, &mut verifier_transcript)?;
+    verifier_transcript.finalize()?;
+
+    timings.verify_ms = verify_timer.elapsed().as_millis();
+    log::info!("verified in {:?}", verify_timer.elapsed());
+    if verbose {
+        println!("Verify time {}ms", timings.verify_ms);
+    }
+
+    Ok(timings)
+}
+
+/// Build, populate, constraint-check, prove and verify H(image) = h.
+pub fn run_hash_circuit<H: HashCircuit>(
+    image_bytes: &[u8],
+    verbose: bool,
+) -> Result<CircuitTimings, ProofError> {
+    run_hash_circuit_with(image_bytes, verbose, H::build)
+}
+
+/// Like `run_hash_circuit`, with the gadget added by `build` as in `prove_hash_with`.
+pub fn run_hash_circuit_with<H: HashCircuit>(
+    image_bytes: &[u8],
+    verbose: bool,
+    build: impl Fn(&CircuitBuilder, usize) -> H,
+) -> Result<CircuitTimings, ProofError> {
+    if verbose {
+        println!("Proof for {} circuit: ", H::NAME);
+    }
+
+    let (proof, public_words, mut timings) = prove_hash_with(image_bytes, verbose, &build)?;
+    timings.verify_ms =
+        verify_hash_with(image_bytes.len(), proof, &public_words, verbose, &build)?.verify_ms;
+
+    if verbose {
+        println!("✓ proof successfully verified");
+    }
+
+    Ok(timings)
+}
+
+/// Like `run_hash_circuit`, with prover and verifier set up for `log_inv_rate` instead
+/// of `DEFAULT_LOG_INV_RATE`, e.g. to trade proving time against proof size.
+pub fn run_hash_circuit_with_rate<H: HashCircuit>(
+    image_bytes: &[u8],
+    log_inv_rate: usize,
+    verbose: bool,
+) -> Result<CircuitTimings, ProofError> {
+    let config = RunConfig::new().verbose(verbose).log_inv_rate(log_inv_rate);
+    run_hash_circuit_with_config(image_bytes, &config, H::build)
+}
+
+/// Like `run_hash_circuit_with`, with the options in `config`. This is what the
+/// `*_circuit` entry points of the hash modules call.
+pub fn run_hash_circuit_with_config<H: HashCircuit>(
+    image_bytes: &[u8],
+    config: &RunConfig,
+    build: impl FnOnce(&CircuitBuilder, usize) -> H,
+) -> Result<CircuitTimings, ProofError> {
+    if config.print_progress() {
+        println!("Proof for {} circuit: ", H::NAME);
+    }
+
+    let max_len = config.max_len.unwrap_or(image_bytes.len());
+    let (circuit, hash) = build_circuit(max_len, build);
+    let witness_vec = populate_witness(&circuit, &hash, image_bytes, None)?;
+    let timings = prove_and_verify_circuit(&circuit, witness_vec, config)
+        .map_err(|e| e.in_circuit(H::NAME, max_len))?;
+    if config.print_progress() {
+        print_digest(&hash, image_bytes);
+    }
+    Ok(timings)
+}
+
+/// Constraint-check, prove and verify an already populated witness for `circuit` with
+/// the options in `config`, the whole flow for circuits that are not a single
+/// `HashCircuit`.
+pub fn prove_and_verify_circuit(
+    circuit: &Circuit,
+    witness_vec: ValueVec,
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    let verbose = config.print_progress();
+    let (proof, public_words, mut timings) = prove_circuit_checked(
+        circuit,
+        witness_vec,
+        StdChallenger::default(),
+        config.log_inv_rate,
+        &config.domain,
+        !config.skip_local_check,
+        verbose,
+    )?;
+    timings.verify_ms = verify_circuit_in_domain(
+        circuit,
+        proof,
+        &public_words,
+        StdChallenger::default(),
+        config.log_inv_rate,
+        &config.domain,
+        verbose,
+    )?
+    .verify_ms;
+
+    if verbose {
+        println!("✓ proof successfully verified");
+    }
+
+    Ok(timings)
+}
+
+/// Like `run_hash_circuit`, for the message `parts[0] || parts[1] || ...`, e.g. framed
+/// records, without the caller joining them first. The reference digest is the one of
+/// the concatenation.
+///
+/// The part boundaries are not part of the statement: the gadget only sees the
+/// concatenated bytes, so `["ab", "c"]` and `["a", "bc"]` prove the same digest. Inputs
+/// whose framing matters should carry it in the bytes, e.g. a length prefix per part.
+pub fn hash_parts_circuit<H: HashCircuit>(
+    parts: &[&[u8]],
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    run_hash_circuit_with_config(&parts.concat(), config, H::build)
+}
+
+/// Like `run_hash_circuit_with_config`, for the UTF-8 encoding of `s`: the statement is
+/// the hash of the string's bytes, as `sha256sum` would compute it for a file holding
+/// the string without a trailing newline, not of its characters or code points.
+///
+/// A `&str` is valid UTF-8 by construction, so there is nothing to reject in the
+/// encoding. Lengths are in bytes too: a non-ASCII character takes 2 to 4 of them, and
+/// with `config.max_len` set a string of fewer characters than that can still fail with
+/// `ProofError::InputTooLong`.
+pub fn hash_str_circuit<H: HashCircuit>(
+    s: &str,
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    run_hash_circuit_with_config(s.as_bytes(), config, H::build)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_too_long<H: HashCircuit>(max_len: usize) {
+        let (circuit, hash) = build_circuit(max_len, H::build);
+        let image = vec![0u8; max_len + 1];
+        match populate_witness(&circuit, &hash, &image, None) {
+            Err(ProofError::InputTooLong { got, max }) => {
+                assert_eq!((got, max), (max_len + 1, max_len));
+            }
+            Err(e) => panic!("expected InputTooLong, got {e}"),
+            Ok(_) => panic!("expected InputTooLong, the witness was populated"),
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn too_long_input_is_rejected() {
+        assert_too_long::<crate::sha256::Sha256Hash>(64);
+    }
+
+    // the blake2b gadget is built for exactly `max_len` bytes
+    #[cfg(feature = "blake2b")]
+    #[test]
+    fn too_long_input_is_rejected_for_fixed_length_gadget() {
+        assert_too_long::<crate::blake2b::Blake2bHash>(64);
+    }
+
+    // a shorter message would be zero padded to `max_len` and fail the digest check, so
+    // it is turned away before the witness is populated
+    #[cfg(feature = "blake2b")]
+    #[test]
+    fn short_input_is_rejected_for_fixed_length_gadget() {
+        let (circuit, hash) = build_circuit(64, crate::blake2b::Blake2bHash::build);
+        match populate_witness(&circuit, &hash, &[0u8; 63], None) {
+            Err(ProofError::InputTooShort { got, expected }) => {
+                assert_eq!((got, expected), (63, 64));
+            }
+            Err(e) => panic!("expected InputTooShort, got {e}"),
+            Ok(_) => panic!("expected InputTooShort, the witness was populated"),
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn wrong_supplied_digest_names_both_digests() {
+        use crate::error::ConstraintError;
+        use crate::sha256::Sha256Hash;
+
+        let msg = b"abc";
+        let mut digest = Sha256Hash::reference_digest(msg);
+        digest[0] ^= 0xff;
+        match check_hash_with_digest::<Sha256Hash>(msg, &digest, false) {
+            Err(ProofError::ConstraintFailed {
+                circuit,
+                source: ConstraintError::Violated(e),
+            }) => {
+                assert_eq!(circuit, Some(("sha256", 3)));
+                assert!(e.starts_with("supplied digest"));
+                assert!(e.contains(&digest_hex(&digest)));
+                assert!(e.contains(&digest_hex(&Sha256Hash::reference_digest(msg))));
+            }
+            Err(e) => panic!("expected ConstraintFailed, got {e}"),
+            Ok(_) => panic!("the wrong digest was accepted"),
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn supplied_digest_of_wrong_length_is_rejected() {
+        use crate::sha256::Sha256Hash;
+
+        let msg = b"abc";
+        let digest = Sha256Hash::reference_digest(msg);
+        for len in [0, 31, 33, 64] {
+            let mut wrong = digest.clone();
+            wrong.resize(len, 0);
+            assert!(matches!(
+                check_hash_with_digest::<Sha256Hash>(msg, &wrong, false),
+                Err(ProofError::InvalidParameter(_))
+            ));
+            assert!(matches!(
+                prove_hash_with_digest::<Sha256Hash>(msg, &wrong, false),
+                Err(ProofError::InvalidParameter(_))
+            ));
+        }
+    }
+
+    // the witness's own digest is the one compared, the reference digest only stands in
+    // when there is no derived witness, and the error says which it was
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn supplied_digest_is_checked_against_the_witness() {
+        use crate::sha256::Sha256Hash;
+
+        let msg = b"abc";
+        let (circuit, hash) = build_circuit(msg.len(), Sha256Hash::build);
+        let witness_vec = populate_witness(&circuit, &hash, msg, None).unwrap();
+        let committed = hash.digest_from_public(witness_vec.public());
+        assert_eq!(committed, Sha256Hash::reference_digest(msg));
+        check_supplied_digest(&hash, msg, &committed, Some(&witness_vec)).unwrap();
+
+        let mut digest = committed.clone();
+        digest[0] ^= 0xff;
+        for (witness_vec, source) in [
+            (Some(&witness_vec), "committed by the witness"),
+            (None, "reference implementation"),
+        ] {
+            let e = check_supplied_digest(&hash, msg, &digest, witness_vec)
+                .unwrap_err()
+                .to_string();
+            assert!(e.contains(&digest_hex(&committed)), "{e}");
+            assert!(e.contains(source), "{e}");
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn proof_for_another_digest_is_rejected() {
+        use crate::sha256::Sha256Hash;
+
+        let msg = b"abc";
+        let (proof, public_words, _) = prove_hash::<Sha256Hash>(msg, false).unwrap();
+        let reference = Sha256Hash::reference_digest(msg);
+        verify_hash_with_expected_digest::<Sha256Hash>(
+            msg.len(),
+            proof.clone(),
+            &public_words,
+            &reference,
+            false,
+        )
+        .unwrap();
+
+        // a valid proof, but of the digest of "abd"
+        let expected = Sha256Hash::reference_digest(b"abd");
+        match verify_hash_with_expected_digest::<Sha256Hash>(
+            msg.len(),
+            proof,
+            &public_words,
+            &expected,
+            false,
+        ) {
+            Err(ProofError::DigestMismatch {
+                expected: e,
+                committed,
+            }) => {
+                assert_eq!(e, expected);
+                assert_eq!(committed, reference);
+            }
+            Err(e) => panic!("expected DigestMismatch, got {e}"),
+            Ok(_) => panic!("accepted a proof for another digest"),
+        }
+    }
+
+    #[test]
+    fn digest_hex_is_lowercase_without_prefix() {
+        assert_eq!(digest_hex(&[0x00, 0xab, 0xCD, 0xff]), "00abcdff");
+        assert_eq!(digest_hex(&[]), "");
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn str_is_hashed_as_utf8_bytes() {
+        type H = crate::sha256::Sha256Hash;
+        // 2-, 3- and 4-byte characters
+        let s = "héllo, 世界 🌍";
+        assert_eq!(s.chars().count(), 11);
+        assert_eq!(s.len(), 19);
+
+        // the circuit's digest is the byte-level one
+        let expected: [u8; 32] = <sha2::Sha256 as sha2::Digest>::digest(s.as_bytes()).into();
+        assert_eq!(
+            crate::sha256::digest_only_sha256(s.as_bytes()).unwrap(),
+            expected
+        );
+
+        hash_str_circuit::<H>(s, &RunConfig::new().max_len(s.len())).unwrap();
+        match hash_str_circuit::<H>(s, &RunConfig::new().max_len(s.chars().count())) {
+            Err(ProofError::InputTooLong { got, max }) => assert_eq!((got, max), (19, 11)),
+            Err(e) => panic!("expected InputTooLong, got {e}"),
+            Ok(_) => panic!("a 19-byte string fit a circuit for 11 bytes"),
+        }
+    }
+
+    fn assert_tampered_public_words_rejected<H: HashCircuit>() {
+        let msg = b"public input integrity";
+        let (proof, mut public_words, _) = prove_hash::<H>(msg, false).unwrap();
+        verify_hash::<H>(msg.len(), proof.clone(), &public_words, false).unwrap();
+
+        // the digest wires are the last public words, flip a byte of the digest
+        let last = public_words.last_mut().unwrap();
+        *last = Word(last.0 ^ 0xff);
+        match verify_hash::<H>(msg.len(), proof, &public_words, false) {
+            Err(ProofError::VerifyFailed(_)) => {}
+            Err(e) => panic!("expected VerifyFailed, got {e}"),
+            Ok(_) => panic!("proof verified against a tampered digest"),
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn tampered_public_words_are_rejected_sha256() {
+        assert_tampered_public_words_rejected::<crate::sha256::Sha256Hash>();
+    }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn tampered_public_words_are_rejected_keccak() {
+        assert_tampered_public_words_rejected::<crate::keccak::Keccak256Hash>();
+    }
+
+    #[cfg(feature = "blake2b")]
+    #[test]
+    fn tampered_public_words_are_rejected_blake2b() {
+        assert_tampered_public_words_rejected::<crate::blake2b::Blake2bHash>();
+    }
+
+    // Soundness smoke test on the serialized proof: flip one random bit of a valid proof
+    // at a time and expect every mutation to be rejected. This is probabilistic, it
+    // samples `MUTATIONS` of the proof's bits with a fixed seed rather than trying all.
+    const MUTATIONS: usize = 32;
+
+    fn assert_bit_flips_rejected<H: HashCircuit>() {
+        use rand::{Rng, SeedableRng, rngs::StdRng};
+
+        let msg = b"bit flip injection";
+        let (proof, public_words, _) = prove_hash::<H>(msg, false).unwrap();
+        verify_hash::<H>(msg.len(), proof.clone(), &public_words, false).unwrap();
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..MUTATIONS {
+            let bit = rng.random_range(0..8 * proof.len());
+            let mut mutated = proof.clone();
+            mutated[bit / 8] ^= 1 << (bit % 8);
+            assert!(
+                verify_hash::<H>(msg.len(), mutated, &public_words, false).is_err(),
+                "{}: proof with bit {bit} flipped verified",
+                H::NAME
+            );
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn proof_bit_flips_are_rejected_sha256() {
+        assert_bit_flips_rejected::<crate::sha256::Sha256Hash>();
+    }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn proof_bit_flips_are_rejected_keccak() {
+        assert_bit_flips_rejected::<crate::keccak::Keccak256Hash>();
+    }
+
+    #[cfg(feature = "blake2b")]
+    #[test]
+    fn proof_bit_flips_are_rejected_blake2b() {
+        assert_bit_flips_rejected::<crate::blake2b::Blake2bHash>();
+    }
+
+    // One circuit built for `REUSE_MAX_LEN` bytes proves every shorter message, with
+    // proofs that verify against a fresh setup for the same `max_len`.
+    const REUSE_MAX_LEN: usize = 1024;
+
+    fn assert_shorter_messages_verify<H: HashCircuit>() {
+        let prover = HashProver::<H>::new(REUSE_MAX_LEN).unwrap();
+        for len in [0, 10, 500, REUSE_MAX_LEN] {
+            let msg: Vec<u8> = (0..len).map(|i| (i * 7 + 1) as u8).collect();
+            let (proof, public_words) = prover
+                .prove(&msg)
+                .unwrap_or_else(|e| panic!("{} {len}: {e}", H::NAME));
+            prover
+                .verify(proof.clone(), &public_words)
+                .unwrap_or_else(|e| panic!("{} {len}: {e}", H::NAME));
+            verify_hash::<H>(REUSE_MAX_LEN, proof, &public_words, false)
+                .unwrap_or_else(|e| panic!("{} {len}: {e}", H::NAME));
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn shorter_than_max_len_verifies_sha256() {
+        assert_shorter_messages_verify::<crate::sha256::Sha256Hash>();
+    }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn shorter_than_max_len_verifies_keccak() {
+        assert_shorter_messages_verify::<crate::keccak::Keccak256Hash>();
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn verify_batch_reports_first_bad_proof() {
+        let prover = HashProver::<crate::sha256::Sha256Hash>::new(16).unwrap();
+        let mut proofs: Vec<_> = (0..4u8)
+            .map(|i| {
+                let (proof, public_words) = prover.prove(&[i; 16]).unwrap();
+                (public_words, proof)
+            })
+            .collect();
+        prover.verify_batch(&proofs).unwrap();
+        assert!(prover.verify_all(&proofs).is_empty());
+
+        // swap the public words of proofs 1 and 3, both then fail
+        let words_1 = proofs[1].0.clone();
+        proofs[1].0 = proofs[3].0.clone();
+        proofs[3].0 = words_1;
+        match prover.verify_batch(&proofs) {
+            Err((1, ProofError::VerifyFailed(_))) => {}
+            Err((i, e)) => panic!("expected proof 1 to fail verification, got {i}: {e}"),
+            Ok(()) => panic!("batch with swapped public words verified"),
+        }
+        let failed: Vec<_> = prover
+            .verify_all(&proofs)
+            .into_iter()
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(failed, [1, 3]);
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn verifier_cache_sets_up_once_per_length() {
+        type H = crate::sha256::Sha256Hash;
+        let cache = VerifierCache::<H>::new();
+        for msg in [&b"first"[..], b"other", b"longer message"] {
+            let (proof, public_words, _) = prove_hash::<H>(msg, false).unwrap();
+            cache
+                .verify(msg.len(), proof.clone(), &public_words)
+                .unwrap();
+            // a proof for another length does not verify against this one's verifier
+            assert!(cache.verify(msg.len() + 1, proof, &public_words).is_err());
+        }
+        let mut lens: Vec<_> = cache.verifiers.lock().unwrap().keys().copied().collect();
+        lens.sort();
+        assert_eq!(lens, [5, 6, 14, 15]);
+    }
+
+    // The determinism contract documented on `prove_hash`.
+    fn assert_deterministic<H: HashCircuit>() {
+        let msg = b"same witness, same proof";
+        let (proof, public_words, _) = prove_hash::<H>(msg, false).unwrap();
+        let (again, again_words, _) = prove_hash::<H>(msg, false).unwrap();
+        assert_eq!(
+            proof,
+            again,
+            "{}: proofs from separate setups differ",
+            H::NAME
+        );
+        assert_eq!(public_words, again_words);
+
+        let prover = HashProver::<H>::new(msg.len()).unwrap();
+        let (first, _) = prover.prove(msg).unwrap();
+        let (second, _) = prover.prove(msg).unwrap();
+        assert_eq!(first, second, "{}: proofs from one setup differ", H::NAME);
+        assert_eq!(first, proof);
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn proving_is_deterministic_sha256() {
+        assert_deterministic::<crate::sha256::Sha256Hash>();
+    }
+
+    #[cfg(feature = "blake2b")]
+    #[test]
+    fn proving_is_deterministic_blake2b() {
+        assert_deterministic::<crate::blake2b::Blake2bHash>();
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn proof_only_verifies_in_its_domain() {
+        type H = crate::sha256::Sha256Hash;
+        let msg = b"domain separated";
+        let (proof, public_words, _) = prove_hash_in_domain::<H>(msg, b"app-a", false).unwrap();
+        verify_hash_in_domain::<H>(msg.len(), proof.clone(), &public_words, b"app-a", false)
+            .unwrap();
+
+        for other in [&b"app-b"[..], b"app-a\0", b""] {
+            assert!(
+                verify_hash_in_domain::<H>(msg.len(), proof.clone(), &public_words, other, false)
+                    .is_err(),
+                "proof for tag app-a verified with tag {other:?}"
+            );
+        }
+        assert!(verify_hash::<H>(msg.len(), proof, &public_words, false).is_err());
+    }
+
+    // Golden circuit sizes, one `<name> <len> <constraints> <proof bytes>` line per
+    // circuit. A circuit missing from the file or a change to a recorded one fails until
+    // it is accepted with `BLESS_GOLDEN=1 cargo test golden`, the only way the file is
+    // written, so a binius bump that changes the numbers shows up in its diff.
+    const GOLDEN_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/golden.txt");
+
+    // Proof sizes may move by a few bytes with the transcript encoding, the constraint
+    // count has to match exactly.
+    const PROOF_BYTES_TOLERANCE: f64 = 0.02;
+
+    // the golden tests run in parallel and rewrite the same file
+    static GOLDEN_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    fn assert_golden<H: HashCircuit>(len: usize) {
+        let msg = vec![0xa5u8; len];
+        let (_, _, timings) = prove_hash::<H>(&msg, false).unwrap();
+        let key = format!("{} {len}", H::NAME);
+        let line = format!("{key} {} {}", timings.n_constraints, timings.proof_bytes);
+
+        let _lock = GOLDEN_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let golden = std::fs::read_to_string(GOLDEN_PATH).unwrap_or_default();
+        let recorded = golden
+            .lines()
+            .find(|l| l.starts_with(&format!("{key} ")))
+            .map(|l| {
+                let fields: Vec<usize> = l[key.len()..]
+                    .split_whitespace()
+                    .map(|f| f.parse().expect("golden values are integers"))
+                    .collect();
+                (fields[0], fields[1])
+            });
+
+        if std::env::var_os("BLESS_GOLDEN").is_none() {
+            let Some((n_constraints, proof_bytes)) = recorded else {
+                panic!(
+                    "{key}: no line in testdata/golden.txt, record it with BLESS_GOLDEN=1 \
+                     cargo test golden"
+                );
+            };
+            assert_eq!(
+                timings.n_constraints, n_constraints,
+                "{key}: constraint count changed, rerun with BLESS_GOLDEN=1 if intended"
+            );
+            let drift = (timings.proof_bytes as f64 - proof_bytes as f64).abs();
+            assert!(
+                drift <= proof_bytes as f64 * PROOF_BYTES_TOLERANCE,
+                "{key}: proof is {} bytes, expected about {proof_bytes}, rerun with \
+                 BLESS_GOLDEN=1 if intended",
+                timings.proof_bytes
+            );
+            return;
+        }
+
+        let mut lines: Vec<_> = golden
+            .lines()
+            .filter(|l| !l.starts_with(&format!("{key} ")))
+            .map(str::to_string)
+            .collect();
+        lines.push(line);
+        std::fs::create_dir_all(std::path::Path::new(GOLDEN_PATH).parent().unwrap()).unwrap();
+        std::fs::write(GOLDEN_PATH, lines.join("\n") + "\n").unwrap();
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn golden_sha256() {
+        assert_golden::<crate::sha256::Sha256Hash>(64);
+    }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn golden_keccak() {
+        assert_golden::<crate::keccak::Keccak256Hash>(64);
+    }
+
+    #[cfg(feature = "blake2b")]
+    #[test]
+    fn golden_blake2b() {
+        assert_golden::<crate::blake2b::Blake2bHash>(64);
+    }
+
+    fn assert_wrong_digest_rejected<H: HashCircuit>() {
+        let msg = b"abc";
+        let (circuit, hash) = build_circuit(msg.len(), H::build);
+
+        // populate the expected digest with one that has a flipped byte
+        let mut digest = H::reference_digest(msg);
+        digest[0] ^= 0xff;
+        let mut witness = circuit.new_witness_filler();
+        hash.populate_with_digest(&mut witness, msg, &digest);
+
+        let rejected = circuit.populate_wire_witness(&mut witness).is_err()
+            || verify_constraints(circuit.constraint_system(), &witness.into_value_vec()).is_err();
+        assert!(rejected, "{} accepted a wrong digest", H::NAME);
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn wrong_digest_is_rejected_sha256() {
+        assert_wrong_digest_rejected::<crate::sha256::Sha256Hash>();
+    }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn wrong_digest_is_rejected_keccak() {
+        assert_wrong_digest_rejected::<crate::keccak::Keccak256Hash>();
+    }
+
+    #[cfg(feature = "blake2b")]
+    #[test]
+    fn wrong_digest_is_rejected_blake2b() {
+        assert_wrong_digest_rejected::<crate::blake2b::Blake2bHash>();
+    }
+
+    // The empty message satisfies the circuit built for length 0, and the reference
+    // digest it is checked against is the published one.
+    fn assert_empty_input_proves<H: HashCircuit>(digest_hex: &str) {
+        let (circuit, hash) = build_circuit(0, H::build);
+        let mut witness = circuit.new_witness_filler();
+        hash.populate(&mut witness, &[]);
+        circuit.populate_wire_witness(&mut witness).unwrap();
+        verify_constraints(circuit.constraint_system(), &witness.into_value_vec()).unwrap();
+
+        assert_eq!(hex::encode(H::reference_digest(&[])), digest_hex);
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn empty_input_proves_known_digest_sha256() {
+        assert_empty_input_proves::<crate::sha256::Sha256Hash>(
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        );
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn empty_input_proves_known_digest_sha256d() {
+        assert_empty_input_proves::<crate::sha256::Sha256dHash>(
+            "5df6e0e2761359d30a8275058e299fcc0381534545f55cf43e41983f5d4c9456",
+        );
+    }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn empty_input_proves_known_digest_keccak() {
+        assert_empty_input_proves::<crate::keccak::Keccak256Hash>(
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+        );
+    }
+
+    // Build the circuit for each of `lens` and populate it for a random message of that
+    // length. The digest wires hold the reference digest and the gadget asserts its own
+    // output equal to them, so satisfied constraints mean a byte-for-byte match.
+    fn assert_matches_reference_at<H: HashCircuit>(lens: &[usize]) {
+        use rand::{Rng, SeedableRng, rngs::StdRng};
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for &len in lens {
+            let mut msg = vec![0u8; len];
+            rng.fill(&mut msg[..]);
+
+            let (circuit, hash) = build_circuit(len, H::build);
+            let mut witness = circuit.new_witness_filler();
+            hash.populate(&mut witness, &msg);
+            circuit
+                .populate_wire_witness(&mut witness)
+                .unwrap_or_else(|e| panic!("{} len {len}: {e:?}", H::NAME));
+            verify_constraints(circuit.constraint_system(), &witness.into_value_vec())
+                .unwrap_or_else(|e| panic!("{} len {len}: {e:?}", H::NAME));
+        }
+    }
+
+    // lengths around the 64-byte block and the 56-byte padding limit, past which the
+    // length encoding needs an extra block, in the first and the second block
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn digest_matches_reference_around_block_boundaries_sha256() {
+        assert_matches_reference_at::<crate::sha256::Sha256Hash>(&[
+            0, 1, 55, 56, 57, 63, 64, 65, 119, 120, 121, 127, 128, 129,
+        ]);
+    }
+
+    // lengths around the 136-byte rate and the 8-byte message words
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn digest_matches_reference_around_block_boundaries_keccak() {
+        assert_matches_reference_at::<crate::keccak::Keccak256Hash>(&[
+            0, 1, 7, 8, 9, 134, 135, 136, 137, 271, 272, 273,
+        ]);
+    }
+
+    // lengths around the 128-byte block, the last block is compressed with the final
+    // flag and only zero padded, so a full block must not be followed by another
+    #[cfg(feature = "blake2b")]
+    #[test]
+    fn digest_matches_reference_around_block_boundaries_blake2b() {
+        assert_matches_reference_at::<crate::blake2b::Blake2bHash>(&[
+            0, 1, 127, 128, 129, 255, 256, 257,
+        ]);
+    }
+}
@@ -0,0 +1,197 @@
+//! SHA-256 of `header || body` with a public header and a secret body, for message
+//! formats that carry a fixed-size, authenticated header in front of their payload: the
+//! verifier learns the header and the digest, but not the body.
+//!
+//! The gadget reads the message as little-endian 64-bit words, word `i` holding bytes
+//! `8i..8i + 8`. For an `H`-byte header:
+//!
+//! - Each of the first `H / 8` words lies entirely in the header. These message wires are
+//!   inout wires themselves, so the header word is a public word.
+//! - With `H % 8 != 0`, word `H / 8` holds the last `H % 8` header bytes in its low bytes
+//!   and the first body bytes above them. That message wire stays a witness, and a public
+//!   word holding only the header bytes, zero above, is asserted equal to it masked to
+//!   its low `H % 8` bytes.
+//! - The remaining words hold only body bytes and are witnesses.
+//!
+//! So the header takes `H.div_ceil(8)` public words, see `header_words`, which sit right
+//! before the 4 digest words at the end of the public words. Both lengths are fixed at
+//! build time, so the body length is part of the statement and the verifier needs it.
+
+use binius_circuits::sha256::Sha256;
+use binius_core::{constraint_system::ValueVec, word::Word};
+use binius_frontend::{Circuit, CircuitBuilder, Wire};
+use binius_verifier::config::StdChallenger;
+use sha2::{Digest, Sha256 as StdSha256};
+
+use crate::config::RunConfig;
+use crate::error::ProofError;
+use crate::hash_circuit::{
+    digest_words, prove_and_verify_circuit, prove_circuit, verify_circuit, words_to_bytes,
+};
+use crate::timings::CircuitTimings;
+
+const NAME: &str = "sha256-header-body";
+
+/// The public words an `H`-byte header takes, see the module docs: the header bytes as
+/// little-endian words, the last one zero-padded.
+pub fn header_words(header: &[u8]) -> Vec<Word> {
+    header
+        .chunks(8)
+        .map(|chunk| {
+            let mut word = [0u8; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            Word(u64::from_le_bytes(word))
+        })
+        .collect()
+}
+
+// The gadget with the public header words.
+struct HeaderBody {
+    sha256: Sha256,
+    header: Vec<Wire>,
+}
+
+fn build(header_len: usize, body_len: usize) -> (Circuit, HeaderBody) {
+    let builder = CircuitBuilder::new();
+    let len = header_len + body_len;
+    let (full_words, rest) = (header_len / 8, header_len % 8);
+
+    let header: Vec<_> = (0..header_len.div_ceil(8))
+        .map(|_| builder.add_inout())
+        .collect();
+    let digest: [_; 4] = core::array::from_fn(|_| builder.add_inout());
+
+    // at least one message wire, as for `Sha256Hash`
+    let message: Vec<_> = (0..len.div_ceil(8).max(1))
+        .map(|i| {
+            if i < full_words {
+                return header[i];
+            }
+            let w = builder.add_witness();
+            if i == full_words && rest != 0 {
+                let mask = builder.add_constant_64((1 << (8 * rest)) - 1);
+                builder.assert_eq("header tail", builder.band(w, mask), header[i]);
+            }
+            w
+        })
+        .collect();
+
+    let len_bytes = builder.add_constant_64(len as u64);
+    let sha256 = Sha256::new(&builder, len_bytes, digest, message);
+    (builder.build(), HeaderBody { sha256, header })
+}
+
+fn witness(header: &[u8], body: &[u8]) -> Result<(Circuit, ValueVec), ProofError> {
+    let (circuit, gadget) = build(header.len(), body.len());
+    let msg = [header, body].concat();
+
+    let mut witness = circuit.new_witness_filler();
+    for (&wire, word) in gadget.header.iter().zip(header_words(header)) {
+        witness[wire] = word;
+    }
+    // writes the same values to the message wires that are header wires
+    gadget.sha256.populate_message(&mut witness, &msg);
+    gadget
+        .sha256
+        .populate_digest(&mut witness, StdSha256::digest(&msg).into());
+    circuit
+        .populate_wire_witness(&mut witness)
+        .map_err(|e| ProofError::from(e).in_circuit(NAME, msg.len()))?;
+    Ok((circuit, witness.into_value_vec()))
+}
+
+/// Prove sha256(header || body) = h with `header` public and `body` secret, returning
+/// the proof bytes and public words, which end with the header words and then h.
+pub fn header_body_prove<const H: usize>(
+    header: &[u8; H],
+    body: &[u8],
+    verbose: bool,
+) -> Result<(Vec<u8>, Vec<Word>, CircuitTimings), ProofError> {
+    let (circuit, witness_vec) = witness(header, body)?;
+    prove_circuit(&circuit, witness_vec, StdChallenger::default(), verbose)
+        .map_err(|e| e.in_circuit(NAME, H + body.len()))
+}
+
+/// The header bytes a proof of `header_body_prove` was made for, read back from its
+/// public words.
+pub fn header_from_words<const H: usize>(public_words: &[Word]) -> Result<[u8; H], ProofError> {
+    let words = digest_words(public_words, H.div_ceil(8) + 4)?;
+    let bytes = words_to_bytes(&words[..H.div_ceil(8)]);
+    Ok(bytes[..H]
+        .try_into()
+        .expect("H.div_ceil(8) words hold H bytes"))
+}
+
+/// Verify a proof produced by `header_body_prove` for `header` and a `body_len`-byte
+/// body. Fails with `ProofError::VerifyFailed` if the proof is for another header.
+pub fn header_body_verify<const H: usize>(
+    header: &[u8; H],
+    body_len: usize,
+    proof: Vec<u8>,
+    public_words: &[Word],
+    verbose: bool,
+) -> Result<CircuitTimings, ProofError> {
+    if header_from_words::<H>(public_words)? != *header {
+        return Err(ProofError::VerifyFailed(
+            "the proof is for a different header".into(),
+        ));
+    }
+    let (circuit, _) = build(H, body_len);
+    verify_circuit(
+        &circuit,
+        proof,
+        public_words,
+        StdChallenger::default(),
+        verbose,
+    )
+}
+
+/// Prove and verify sha256(header || body) with a public header in one run.
+pub fn header_body_circuit<const H: usize>(
+    header: &[u8; H],
+    body: &[u8],
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    if config.print_progress() {
+        println!("Proof for {NAME} circuit: ");
+    }
+
+    let (circuit, witness_vec) = witness(header, body)?;
+    prove_and_verify_circuit(&circuit, witness_vec, config)
+        .map_err(|e| e.in_circuit(NAME, H + body.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha256::Sha256PublicInputs;
+
+    #[test]
+    fn header_is_public_and_digest_matches_reference() {
+        let body = b"secret payload";
+        // a whole number of words and a header ending mid-word
+        let aligned = *b"HDR-v1\0\x10";
+        let (proof, public_words, _) = header_body_prove(&aligned, body, false).unwrap();
+        assert_eq!(header_from_words::<8>(&public_words).unwrap(), aligned);
+        let digest = Sha256PublicInputs::from_words(public_words.clone())
+            .unwrap()
+            .digest();
+        assert_eq!(
+            digest,
+            <[u8; 32]>::from(StdSha256::digest([&aligned[..], body].concat()))
+        );
+        header_body_verify(&aligned, body.len(), proof, &public_words, false).unwrap();
+
+        let unaligned = *b"HDR-v2\x01\x02\x03\x04\x05";
+        let (proof, public_words, _) = header_body_prove(&unaligned, body, false).unwrap();
+        assert_eq!(header_from_words::<11>(&public_words).unwrap(), unaligned);
+        header_body_verify(&unaligned, body.len(), proof, &public_words, false).unwrap();
+    }
+
+    #[test]
+    fn proof_for_other_header_is_rejected() {
+        let body = b"body";
+        let (proof, public_words, _) = header_body_prove(b"type=a", body, false).unwrap();
+        assert!(header_body_verify(b"type=b", body.len(), proof, &public_words, false).is_err());
+    }
+}
@@ -0,0 +1,242 @@
+//! HMAC-SHA256 circuit.
+//!
+//! HMAC(K, m) = SHA256((K ^ opad) || SHA256((K ^ ipad) || m)), built from two upstream
+//! sha256 gadgets in one circuit. The key and message are private witnesses, only the
+//! 32-byte MAC is public.
+//!
+//! Keys longer than the 64-byte block would first have to be hashed down to 32 bytes,
+//! which needs a third gadget. They are rejected instead.
+
+use binius_circuits::sha256::Sha256;
+use binius_core::word::Word;
+use binius_frontend::{CircuitBuilder, Wire, WitnessFiller};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256 as StdSha256};
+
+use crate::config::RunConfig;
+use crate::error::ProofError;
+use crate::hash_circuit::prove_and_verify_circuit;
+use crate::sha256::bswap64;
+use crate::timings::CircuitTimings;
+
+const NAME: &str = "hmac-sha256";
+const BLOCK_LEN: usize = 64;
+const IPAD: u64 = 0x3636_3636_3636_3636;
+const OPAD: u64 = 0x5c5c_5c5c_5c5c_5c5c;
+
+// Little-endian packing of 8 bytes per word, as the sha256 gadget reads its message.
+fn pack_le(bytes: &[u8], n_words: usize) -> Vec<Word> {
+    (0..n_words)
+        .map(|i| {
+            let mut word = [0u8; 8];
+            for (k, byte) in word.iter_mut().enumerate() {
+                *byte = bytes.get(8 * i + k).copied().unwrap_or(0);
+            }
+            Word(u64::from_le_bytes(word))
+        })
+        .collect()
+}
+
+/// HMAC-SHA256 over a key of up to 64 bytes and a message of exactly `len` bytes.
+///
+/// The inner hash length is a constant fixed by `len`, like the outer one, so a prover
+/// cannot shorten the inner message and drop part of the ipad key block.
+pub struct HmacSha256 {
+    len: usize,
+    key: [Wire; BLOCK_LEN / 8],
+    message: Vec<Wire>,
+    inner: Sha256,
+    outer: Sha256,
+}
+
+impl HmacSha256 {
+    pub fn new(builder: &CircuitBuilder, len: usize) -> Self {
+        let key: [_; BLOCK_LEN / 8] = core::array::from_fn(|_| builder.add_witness());
+        let message: Vec<_> = (0..len.div_ceil(8))
+            .map(|_| builder.add_witness())
+            .collect();
+        let mac: [_; 4] = core::array::from_fn(|_| builder.add_inout());
+
+        let ipad = builder.add_constant_64(IPAD);
+        let opad = builder.add_constant_64(OPAD);
+
+        // inner: sha256((K ^ ipad) || m)
+        let inner_message = key
+            .iter()
+            .map(|&k| builder.bxor(k, ipad))
+            .chain(message.iter().copied())
+            .collect();
+        let inner_digest: [_; 4] = core::array::from_fn(|_| builder.add_witness());
+        let inner_len = builder.add_constant_64((BLOCK_LEN + len) as u64);
+        let inner = Sha256::new(builder, inner_len, inner_digest, inner_message);
+
+        // outer: sha256((K ^ opad) || inner digest)
+        let outer_message = key
+            .iter()
+            .map(|&k| builder.bxor(k, opad))
+            // the inner digest words are big-endian, the outer message words little-endian
+            .chain(inner_digest.iter().map(|&d| bswap64(builder, d)))
+            .collect();
+        let outer_len = builder.add_constant_64((BLOCK_LEN + 32) as u64);
+        let outer = Sha256::new(builder, outer_len, mac, outer_message);
+
+        Self {
+            len,
+            key,
+            message,
+            inner,
+            outer,
+        }
+    }
+
+    /// Populate the key, message, intermediate digest and MAC wires.
+    pub fn populate(&self, witness: &mut WitnessFiller, key: &[u8], msg: &[u8]) {
+        debug_assert!(key.len() <= BLOCK_LEN);
+        debug_assert_eq!(msg.len(), self.len);
+
+        let mut padded_key = [0u8; BLOCK_LEN];
+        padded_key[..key.len()].copy_from_slice(key);
+
+        for (&w, v) in self.key.iter().zip(pack_le(&padded_key, BLOCK_LEN / 8)) {
+            witness[w] = v;
+        }
+        for (&w, v) in self.message.iter().zip(pack_le(msg, self.message.len())) {
+            witness[w] = v;
+        }
+
+        let inner_message: Vec<u8> = padded_key
+            .iter()
+            .map(|k| k ^ 0x36)
+            .chain(msg.iter().copied())
+            .collect();
+        let inner_digest: [u8; 32] = StdSha256::digest(&inner_message).into();
+        self.inner.populate_message(witness, &inner_message);
+        self.inner.populate_digest(witness, inner_digest);
+
+        let outer_message: Vec<u8> = padded_key
+            .iter()
+            .map(|k| k ^ 0x5c)
+            .chain(inner_digest)
+            .collect();
+        self.outer.populate_message(witness, &outer_message);
+
+        let mut mac = [0u8; 32];
+        mac.copy_from_slice(&reference_mac(key, msg));
+        self.outer.populate_digest(witness, mac);
+    }
+}
+
+/// HMAC-SHA256 of `msg` under `key` computed by the `hmac` crate.
+pub fn reference_mac(key: &[u8], msg: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<StdSha256>::new_from_slice(key).expect("hmac accepts any key length");
+    mac.update(msg);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Build, populate, constraint-check, prove and verify HMAC-SHA256(key, message) = mac.
+pub fn hmac_sha256_circuit(
+    key: &[u8],
+    message: &[u8],
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    if key.len() > BLOCK_LEN {
+        return Err(ProofError::InvalidParameter(format!(
+            "hmac-sha256 keys longer than {BLOCK_LEN} bytes are not supported, got {}",
+            key.len()
+        )));
+    }
+
+    if config.print_progress() {
+        println!("Proof for hmac-sha256 circuit: ");
+    }
+
+    let builder = CircuitBuilder::new();
+    let hmac = HmacSha256::new(&builder, message.len());
+    let circuit = builder.build();
+
+    let mut witness = circuit.new_witness_filler();
+    hmac.populate(&mut witness, key, message);
+    circuit
+        .populate_wire_witness(&mut witness)
+        .map_err(|e| ProofError::from(e).in_circuit(NAME, message.len()))?;
+
+    prove_and_verify_circuit(&circuit, witness.into_value_vec(), config)
+        .map_err(|e| e.in_circuit(NAME, message.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use binius_core::verify::verify_constraints;
+
+    // Whether the circuit for `msg` accepts `mac` as HMAC(key, msg).
+    fn accepts(key: &[u8], msg: &[u8], mac: &[u8]) -> bool {
+        let builder = CircuitBuilder::new();
+        let hmac = HmacSha256::new(&builder, msg.len());
+        let circuit = builder.build();
+
+        let mut witness = circuit.new_witness_filler();
+        hmac.populate(&mut witness, key, msg);
+        hmac.outer
+            .populate_digest(&mut witness, mac.try_into().expect("32-byte mac"));
+        circuit.populate_wire_witness(&mut witness).is_ok()
+            && verify_constraints(circuit.constraint_system(), &witness.into_value_vec()).is_ok()
+    }
+
+    // RFC 4231 test cases 1, 2 and 3
+    #[test]
+    fn mac_matches_rfc_4231() {
+        let cases: [(&[u8], &[u8], &str); 3] = [
+            (
+                &[0x0b; 20],
+                b"Hi There",
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+            ),
+            (
+                b"Jefe",
+                b"what do ya want for nothing?",
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            ),
+            (
+                &[0xaa; 20],
+                &[0xdd; 50],
+                "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe",
+            ),
+        ];
+        for (key, msg, mac) in cases {
+            assert_eq!(hex::encode(reference_mac(key, msg)), mac);
+            assert!(accepts(key, msg, &reference_mac(key, msg)), "{mac}");
+        }
+    }
+
+    // the shortest key is all zero padding, the longest fills the block exactly
+    #[test]
+    fn empty_and_full_block_keys_match_reference() {
+        let key: Vec<u8> = (0..BLOCK_LEN as u8).collect();
+        for key in [&[][..], &key[..]] {
+            let msg = b"key length edge";
+            assert!(accepts(key, msg, &reference_mac(key, msg)), "{key:?}");
+        }
+    }
+
+    #[test]
+    fn wrong_mac_is_rejected() {
+        let (key, msg) = (b"Jefe", b"what do ya want for nothing?");
+        let mut mac = reference_mac(key, msg);
+        mac[0] ^= 0xff;
+        assert!(!accepts(key, msg, &mac));
+    }
+
+    // RFC 4231 test case 6 uses a 131-byte key, hashed down first, which is not wired
+    #[test]
+    fn key_longer_than_block_is_rejected() {
+        assert!(matches!(
+            hmac_sha256_circuit(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key",
+                &RunConfig::default()
+            ),
+            Err(ProofError::InvalidParameter(_))
+        ));
+    }
+}
@@ -0,0 +1,571 @@
+//! On-disk proof files, so a proof can be produced on one machine and verified on another.
+//!
+//! Layout (all integers little-endian):
+//!
+//! | field        | size                   |
+//! |--------------|------------------------|
+//! | magic        | 4 bytes, `b"BPRF"`     |
+//! | version      | 1 byte, currently `1`  |
+//! | algo len     | 1 byte                 |
+//! | algo         | `algo len` bytes, utf8 |
+//! | max_len      | u64                    |
+//! | n_public     | u64                    |
+//! | public words | `n_public` x u64       |
+//! | proof len    | u64                    |
+//! | proof        | `proof len` bytes      |
+//!
+//! `algo` is the `HashCircuit::NAME` of the circuit and `max_len` the message length it
+//! was built for, which together are enough to rebuild the verifier. A `Blake2bOutHash`
+//! circuit also depends on its output and key lengths, a proof for one is saved with
+//! `save_blake2b_out_proof` under the algo `blake2b-out-{out_len}-{key_len}`, while plain
+//! `blake2b-out` is the 64-byte unkeyed circuit `Blake2bOutHash::build` makes.
+//!
+//! The public words alone, apart from any proof, have a canonical encoding too, see
+//! `public_words_to_bytes`: the same u64 little-endian words as in a proof file, without
+//! the count, so that a digest or other public input can be hashed or compared byte for
+//! byte by a system that knows nothing of this crate.
+//!
+//! Populated witnesses can be saved too, so the prover can be rerun or benchmarked
+//! without repeating witness generation. A witness file is `b"BWIT"`, a version byte,
+//! then the public and the non-public words of the `ValueVec`, each as a u64 count
+//! followed by the words. The file holds no description of the circuit: it can only be
+//! loaded against the constraint system it was populated for, and the value vector
+//! layout is internal to binius, so a witness saved with one version of binius or of
+//! these circuits is not guaranteed to load with another. Treat witness files as a
+//! cache, not an archive format.
+
+use binius_core::{
+    constraint_system::{ConstraintSystem, ValueVec},
+    word::Word,
+};
+
+use std::{fs, path::Path};
+
+#[cfg(feature = "blake2b")]
+use crate::blake2b::{Blake2bHash, Blake2bOutHash, MAX_KEY_LEN, MAX_OUT_LEN};
+#[cfg(feature = "blake2s")]
+use crate::blake2s::Blake2sHash;
+#[cfg(feature = "blake3")]
+use crate::blake3::Blake3Hash;
+use crate::error::ProofError;
+use crate::hash_circuit::{HashCircuit, verify_hash};
+#[cfg(feature = "keccak")]
+use crate::keccak::Keccak256Hash;
+#[cfg(feature = "legacy")]
+use crate::md5::Md5Hash;
+#[cfg(all(feature = "sha256", feature = "keccak"))]
+use crate::multi_hash::Sha256KeccakHash;
+#[cfg(feature = "ripemd")]
+use crate::ripemd::{Hash160Hash, Ripemd160Hash};
+#[cfg(feature = "sha2-truncated")]
+use crate::sha2_truncated::{Sha224Hash, Sha384Hash};
+#[cfg(feature = "sha3")]
+use crate::sha3::{Keccak512Hash, Sha3_256Hash};
+#[cfg(feature = "sha256")]
+use crate::sha256::{Sha256Hash, Sha256dHash};
+#[cfg(feature = "sha512")]
+use crate::sha512::Sha512Hash;
+
+const MAGIC: &[u8; 4] = b"BPRF";
+const VERSION: u8 = 1;
+const WITNESS_MAGIC: &[u8; 4] = b"BWIT";
+const WITNESS_VERSION: u8 = 1;
+
+/// A proof loaded from disk.
+#[derive(Debug, Clone)]
+pub struct ProofFile {
+    pub algo: String,
+    pub max_len: usize,
+    pub public_words: Vec<Word>,
+    pub proof: Vec<u8>,
+}
+
+/// Write a proof for circuit `H`, built for messages of `max_len` bytes, to `path`.
+pub fn save_proof<H: HashCircuit>(
+    path: impl AsRef<Path>,
+    max_len: usize,
+    proof: &[u8],
+    public_words: &[Word],
+) -> Result<(), ProofError> {
+    write_proof(path, H::NAME, max_len, proof, public_words)
+}
+
+/// Write a proof for a `Blake2bOutHash` circuit with an `out_len`-byte digest and a
+/// `key_len`-byte key, 0 for the unkeyed hash, built for messages of `max_len` bytes.
+/// Both lengths go into the file, so `verify_from_file` rebuilds the same circuit. Fails
+/// with `ProofError::InvalidParameter` if `out_len` is not in `1..=64` or `key_len` is
+/// over 64.
+#[cfg(feature = "blake2b")]
+pub fn save_blake2b_out_proof(
+    path: impl AsRef<Path>,
+    max_len: usize,
+    out_len: usize,
+    key_len: usize,
+    proof: &[u8],
+    public_words: &[Word],
+) -> Result<(), ProofError> {
+    if !(1..=MAX_OUT_LEN).contains(&out_len) || key_len > MAX_KEY_LEN {
+        return Err(ProofError::InvalidParameter(format!(
+            "blake2b output length must be in 1..={MAX_OUT_LEN} and key length at most \
+             {MAX_KEY_LEN}, got {out_len} and {key_len}"
+        )));
+    }
+    let algo = format!("{}-{out_len}-{key_len}", Blake2bOutHash::NAME);
+    write_proof(path, &algo, max_len, proof, public_words)
+}
+
+// The output and key lengths of a `save_blake2b_out_proof` algo, `None` for any other algo.
+#[cfg(feature = "blake2b")]
+fn blake2b_out_params(algo: &str) -> Option<(usize, usize)> {
+    let params = algo.strip_prefix(Blake2bOutHash::NAME)?.strip_prefix('-')?;
+    let (out_len, key_len) = params.split_once('-')?;
+    let (out_len, key_len) = (out_len.parse().ok()?, key_len.parse().ok()?);
+    ((1..=MAX_OUT_LEN).contains(&out_len) && key_len <= MAX_KEY_LEN).then_some((out_len, key_len))
+}
+
+fn write_proof(
+    path: impl AsRef<Path>,
+    algo: &str,
+    max_len: usize,
+    proof: &[u8],
+    public_words: &[Word],
+) -> Result<(), ProofError> {
+    let mut buf = Vec::with_capacity(32 + algo.len() + 8 * public_words.len() + proof.len());
+    buf.extend_from_slice(MAGIC);
+    buf.push(VERSION);
+    buf.push(algo.len() as u8);
+    buf.extend_from_slice(algo.as_bytes());
+    buf.extend_from_slice(&(max_len as u64).to_le_bytes());
+    buf.extend_from_slice(&(public_words.len() as u64).to_le_bytes());
+    buf.extend_from_slice(&public_words_to_bytes(public_words));
+    buf.extend_from_slice(&(proof.len() as u64).to_le_bytes());
+    buf.extend_from_slice(proof);
+
+    fs::write(path, buf)?;
+    Ok(())
+}
+
+/// The canonical byte encoding of public words: each word as 8 little-endian bytes, in
+/// order, nothing else. `n` words always take exactly `8 * n` bytes.
+///
+/// A sha256 digest in its last 4 words comes out with each 8-byte group byte-reversed
+/// relative to the digest, since the gadget packs the digest big-endian, see
+/// `Sha256PublicInputs::digest` to read it out in the usual order.
+pub fn public_words_to_bytes(words: &[Word]) -> Vec<u8> {
+    words.iter().flat_map(|w| w.0.to_le_bytes()).collect()
+}
+
+/// The inverse of `public_words_to_bytes`. Fails with `ProofError::InvalidParameter` if
+/// the length is not a multiple of 8.
+pub fn public_words_from_bytes(bytes: &[u8]) -> Result<Vec<Word>, ProofError> {
+    if bytes.len() % 8 != 0 {
+        return Err(ProofError::InvalidParameter(format!(
+            "public words take a multiple of 8 bytes, got {}",
+            bytes.len()
+        )));
+    }
+    Ok(bytes
+        .chunks_exact(8)
+        .map(|b| Word(u64::from_le_bytes(b.try_into().expect("chunk of 8 bytes"))))
+        .collect())
+}
+
+// Cursor over the file contents that reports truncation instead of panicking.
+struct Reader<'a> {
+    buf: &'a [u8],
+    invalid: fn(String) -> ProofError,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize, what: &str) -> Result<&'a [u8], ProofError> {
+        if self.buf.len() < n {
+            return Err((self.invalid)(format!("truncated {what}")));
+        }
+        let (head, tail) = self.buf.split_at(n);
+        self.buf = tail;
+        Ok(head)
+    }
+
+    fn u64(&mut self, what: &str) -> Result<u64, ProofError> {
+        let bytes = self.take(8, what)?;
+        Ok(u64::from_le_bytes(bytes.try_into().expect("took 8 bytes")))
+    }
+
+    fn words(&mut self, what: &str) -> Result<Vec<Word>, ProofError> {
+        let n = self.u64(what)? as usize;
+        let bytes = self.take(
+            n.checked_mul(8)
+                .ok_or_else(|| (self.invalid)(format!("{what} count overflows")))?,
+            what,
+        )?;
+        Ok(bytes
+            .chunks_exact(8)
+            .map(|b| Word(u64::from_le_bytes(b.try_into().expect("chunk of 8 bytes"))))
+            .collect())
+    }
+
+    fn finish(&self) -> Result<(), ProofError> {
+        if self.buf.is_empty() {
+            Ok(())
+        } else {
+            Err((self.invalid)(format!("{} trailing bytes", self.buf.len())))
+        }
+    }
+
+    fn len(&mut self, what: &str) -> Result<usize, ProofError> {
+        let n = self.u64(what)?;
+        usize::try_from(n)
+            .ok()
+            .filter(|&n| n <= self.buf.len())
+            .ok_or_else(|| (self.invalid)(format!("{what} {n} exceeds file size")))
+    }
+}
+
+/// Read a proof written by `save_proof`.
+pub fn load_proof(path: impl AsRef<Path>) -> Result<ProofFile, ProofError> {
+    let data = fs::read(path)?;
+    let mut r = Reader {
+        buf: &data,
+        invalid: ProofError::InvalidProofFile,
+    };
+
+    if r.take(4, "magic")? != MAGIC {
+        return Err(ProofError::InvalidProofFile("bad magic".into()));
+    }
+    let version = r.take(1, "version")?[0];
+    if version != VERSION {
+        return Err(ProofError::InvalidProofFile(format!(
+            "unsupported version {version}"
+        )));
+    }
+
+    let algo_len = r.take(1, "algo length")?[0] as usize;
+    let algo = std::str::from_utf8(r.take(algo_len, "algo")?)
+        .map_err(|_| ProofError::InvalidProofFile("algo is not utf8".into()))?
+        .to_string();
+    let max_len = r.u64("max_len")? as usize;
+
+    let public_words = r.words("public words")?;
+
+    let proof_len = r.len("proof length")?;
+    let proof = r.take(proof_len, "proof")?.to_vec();
+
+    r.finish()?;
+
+    Ok(ProofFile {
+        algo,
+        max_len,
+        public_words,
+        proof,
+    })
+}
+
+/// Write a populated witness to `path`, see the module docs for the format and its
+/// stability caveats.
+pub fn save_witness(path: impl AsRef<Path>, witness: &ValueVec) -> Result<(), ProofError> {
+    let (public, private) = (witness.public(), witness.non_public());
+    let mut buf = Vec::with_capacity(21 + 8 * (public.len() + private.len()));
+    buf.extend_from_slice(WITNESS_MAGIC);
+    buf.push(WITNESS_VERSION);
+    for words in [public, private] {
+        buf.extend_from_slice(&(words.len() as u64).to_le_bytes());
+        for word in words {
+            buf.extend_from_slice(&word.0.to_le_bytes());
+        }
+    }
+
+    fs::write(path, buf)?;
+    Ok(())
+}
+
+/// Read a witness written by `save_witness` for the circuit with constraint system `cs`.
+///
+/// Fails if the word counts do not match the layout of `cs`, which catches a witness
+/// from a different circuit or message length but not one from a circuit of the same
+/// shape.
+pub fn load_witness(path: impl AsRef<Path>, cs: &ConstraintSystem) -> Result<ValueVec, ProofError> {
+    let data = fs::read(path)?;
+    let mut r = Reader {
+        buf: &data,
+        invalid: ProofError::InvalidWitnessFile,
+    };
+
+    if r.take(4, "magic")? != WITNESS_MAGIC {
+        return Err(ProofError::InvalidWitnessFile("bad magic".into()));
+    }
+    let version = r.take(1, "version")?[0];
+    if version != WITNESS_VERSION {
+        return Err(ProofError::InvalidWitnessFile(format!(
+            "unsupported version {version}"
+        )));
+    }
+
+    let public = r.words("public words")?;
+    let private = r.words("non-public words")?;
+    r.finish()?;
+
+    ValueVec::new_from_data(cs.value_vec_layout.clone(), public, private)
+        .map_err(|e| ProofError::InvalidWitnessFile(format!("does not fit the circuit: {e}")))
+}
+
+/// Load a proof from `path`, rebuild the verifier for the circuit it names and verify it.
+pub fn verify_from_file(path: impl AsRef<Path>) -> Result<(), ProofError> {
+    let ProofFile {
+        algo,
+        max_len,
+        public_words,
+        proof,
+    } = load_proof(path)?;
+
+    #[cfg(feature = "blake2b")]
+    if let Some((out_len, key_len)) = blake2b_out_params(&algo) {
+        crate::hash_circuit::verify_hash_with(
+            max_len,
+            proof,
+            &public_words,
+            false,
+            |builder, len| Blake2bOutHash::new_keyed(builder, len, out_len, key_len),
+        )?;
+        return Ok(());
+    }
+
+    match algo.as_str() {
+        #[cfg(feature = "sha256")]
+        Sha256Hash::NAME => verify_hash::<Sha256Hash>(max_len, proof, &public_words, false)?,
+        #[cfg(feature = "sha256")]
+        Sha256dHash::NAME => verify_hash::<Sha256dHash>(max_len, proof, &public_words, false)?,
+        #[cfg(feature = "sha512")]
+        Sha512Hash::NAME => verify_hash::<Sha512Hash>(max_len, proof, &public_words, false)?,
+        #[cfg(feature = "sha2-truncated")]
+        Sha224Hash::NAME => verify_hash::<Sha224Hash>(max_len, proof, &public_words, false)?,
+        #[cfg(feature = "sha2-truncated")]
+        Sha384Hash::NAME => verify_hash::<Sha384Hash>(max_len, proof, &public_words, false)?,
+        #[cfg(feature = "keccak")]
+        Keccak256Hash::NAME => verify_hash::<Keccak256Hash>(max_len, proof, &public_words, false)?,
+        #[cfg(all(feature = "sha256", feature = "keccak"))]
+        Sha256KeccakHash::NAME => {
+            verify_hash::<Sha256KeccakHash>(max_len, proof, &public_words, false)?
+        }
+        #[cfg(feature = "blake2b")]
+        Blake2bHash::NAME => verify_hash::<Blake2bHash>(max_len, proof, &public_words, false)?,
+        #[cfg(feature = "blake2b")]
+        Blake2bOutHash::NAME => {
+            verify_hash::<Blake2bOutHash>(max_len, proof, &public_words, false)?
+        }
+        #[cfg(feature = "blake2s")]
+        Blake2sHash::NAME => verify_hash::<Blake2sHash>(max_len, proof, &public_words, false)?,
+        #[cfg(feature = "sha3")]
+        Sha3_256Hash::NAME => verify_hash::<Sha3_256Hash>(max_len, proof, &public_words, false)?,
+        #[cfg(feature = "sha3")]
+        Keccak512Hash::NAME => verify_hash::<Keccak512Hash>(max_len, proof, &public_words, false)?,
+        #[cfg(feature = "blake3")]
+        Blake3Hash::NAME => verify_hash::<Blake3Hash>(max_len, proof, &public_words, false)?,
+        #[cfg(feature = "ripemd")]
+        Ripemd160Hash::NAME => verify_hash::<Ripemd160Hash>(max_len, proof, &public_words, false)?,
+        #[cfg(feature = "ripemd")]
+        Hash160Hash::NAME => verify_hash::<Hash160Hash>(max_len, proof, &public_words, false)?,
+        #[cfg(feature = "legacy")]
+        Md5Hash::NAME => verify_hash::<Md5Hash>(max_len, proof, &public_words, false)?,
+        _ => {
+            // also reached for an algo whose feature is not enabled
+            return Err(ProofError::InvalidProofFile(format!(
+                "unknown algo '{algo}'"
+            )));
+        }
+    };
+
+    Ok(())
+}
+
+#[cfg(all(test, feature = "sha256"))]
+mod tests {
+    use super::*;
+    use crate::hash_circuit::prove_hash;
+
+    use std::path::PathBuf;
+
+    // unique per test process, so parallel test runs do not share files
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("binius-io-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn proof_round_trips_through_file() {
+        let msg = b"round trip";
+        let (proof, public_words, _) = prove_hash::<Sha256Hash>(msg, false).unwrap();
+
+        let path = temp_path("round-trip.bprf");
+        save_proof::<Sha256Hash>(&path, msg.len(), &proof, &public_words).unwrap();
+
+        let loaded = load_proof(&path).unwrap();
+        assert_eq!(loaded.algo, Sha256Hash::NAME);
+        assert_eq!(loaded.max_len, msg.len());
+        assert_eq!(loaded.public_words, public_words);
+        assert_eq!(loaded.proof, proof);
+
+        let verified = verify_from_file(&path);
+        fs::remove_file(&path).unwrap();
+        verified.unwrap();
+    }
+
+    // `Blake2bOutHash` is a different circuit from `Blake2bHash`, the file has to name
+    // the one that verifies it
+    #[cfg(feature = "blake2b")]
+    #[test]
+    fn blake2b_out_proof_round_trips_through_file() {
+        let msg = b"blake2b out";
+        let (proof, public_words, _) = prove_hash::<Blake2bOutHash>(msg, false).unwrap();
+
+        let path = temp_path("blake2b-out.bprf");
+        save_proof::<Blake2bOutHash>(&path, msg.len(), &proof, &public_words).unwrap();
+
+        let loaded = load_proof(&path).unwrap();
+        assert_eq!(loaded.algo, Blake2bOutHash::NAME);
+        assert_ne!(loaded.algo, Blake2bHash::NAME);
+
+        let verified = verify_from_file(&path);
+        fs::remove_file(&path).unwrap();
+        verified.unwrap();
+    }
+
+    // a shorter digest is a different circuit, not a truncation of the 64-byte one, so
+    // the file has to record the output length to verify
+    #[cfg(feature = "blake2b")]
+    #[test]
+    fn blake2b_out_len_round_trips_through_file() {
+        use crate::hash_circuit::prove_hash_with;
+
+        let msg = b"blake2b out_len";
+        let (proof, public_words, _) = prove_hash_with(msg, false, |builder, len| {
+            Blake2bOutHash::new(builder, len, 32)
+        })
+        .unwrap();
+
+        let path = temp_path("blake2b-out-32.bprf");
+        save_blake2b_out_proof(&path, msg.len(), 32, 0, &proof, &public_words).unwrap();
+        let loaded = load_proof(&path).unwrap();
+        assert_eq!(loaded.algo, "blake2b-out-32-0");
+        let verified = verify_from_file(&path);
+        fs::remove_file(&path).unwrap();
+        verified.unwrap();
+
+        assert!(matches!(
+            save_blake2b_out_proof(&path, msg.len(), 65, 0, &proof, &public_words),
+            Err(ProofError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn public_words_round_trip_through_bytes() {
+        let (proof, public_words, _) = prove_hash::<Sha256Hash>(b"canonical", false).unwrap();
+        let bytes = public_words_to_bytes(&public_words);
+        assert_eq!(bytes.len(), 8 * public_words.len());
+        let decoded = public_words_from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, public_words);
+        verify_hash::<Sha256Hash>(b"canonical".len(), proof, &decoded, false).unwrap();
+
+        // fixed little-endian layout, independent of the host
+        assert_eq!(
+            public_words_to_bytes(&[Word(0x0102030405060708), Word(u64::MAX)]),
+            [
+                8, 7, 6, 5, 4, 3, 2, 1, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff
+            ]
+        );
+        assert!(public_words_from_bytes(&[]).unwrap().is_empty());
+        assert!(matches!(
+            public_words_from_bytes(&bytes[1..]),
+            Err(ProofError::InvalidParameter(_))
+        ));
+    }
+
+    // The sha256 circuit for `msg` and its populated witness.
+    fn populated(msg: &[u8]) -> (binius_frontend::Circuit, ValueVec) {
+        let builder = binius_frontend::CircuitBuilder::new();
+        let hash = Sha256Hash::build(&builder, msg.len());
+        let circuit = builder.build();
+        let mut witness = circuit.new_witness_filler();
+        hash.populate(&mut witness, msg);
+        circuit.populate_wire_witness(&mut witness).unwrap();
+        (circuit, witness.into_value_vec())
+    }
+
+    #[test]
+    fn witness_round_trips_through_file() {
+        use crate::hash_circuit::{prove_circuit, verify_circuit};
+        use binius_verifier::config::StdChallenger;
+
+        let (circuit, witness) = populated(b"saved witness");
+        let path = temp_path("round-trip.bwit");
+        save_witness(&path, &witness).unwrap();
+        let loaded = load_witness(&path, circuit.constraint_system());
+        fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.public(), witness.public());
+        assert_eq!(loaded.non_public(), witness.non_public());
+
+        let (proof, public_words, _) =
+            prove_circuit(&circuit, loaded, StdChallenger::default(), false).unwrap();
+        assert_eq!(public_words, witness.public());
+        verify_circuit(
+            &circuit,
+            proof,
+            &public_words,
+            StdChallenger::default(),
+            false,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn witness_for_another_circuit_is_rejected() {
+        // a longer message has more message wires, so a different layout
+        let (_, witness) = populated(b"a witness for 32 bytes of input");
+        let (other, _) = populated(b"eight b");
+
+        let path = temp_path("other-circuit.bwit");
+        save_witness(&path, &witness).unwrap();
+        let loaded = load_witness(&path, other.constraint_system());
+        // and a file that ends early
+        let data = fs::read(&path).unwrap();
+        fs::write(&path, &data[..data.len() - 8]).unwrap();
+        let truncated = load_witness(&path, other.constraint_system());
+        fs::remove_file(&path).unwrap();
+
+        for result in [loaded, truncated] {
+            match result {
+                Err(ProofError::InvalidWitnessFile(_)) => {}
+                Err(e) => panic!("expected InvalidWitnessFile, got {e}"),
+                Ok(_) => panic!("loaded a witness for another circuit"),
+            }
+        }
+    }
+
+    #[test]
+    fn truncated_proof_file_is_rejected() {
+        let msg = b"truncated";
+        let (proof, public_words, _) = prove_hash::<Sha256Hash>(msg, false).unwrap();
+
+        let path = temp_path("truncated.bprf");
+        save_proof::<Sha256Hash>(&path, msg.len(), &proof, &public_words).unwrap();
+        let data = fs::read(&path).unwrap();
+
+        // cut inside the header, the public words and the proof
+        let cuts = [3, 20, data.len() - proof.len() - 1, data.len() - 1];
+        let results: Vec<_> = cuts
+            .iter()
+            .map(|&cut| {
+                fs::write(&path, &data[..cut]).unwrap();
+                load_proof(&path)
+            })
+            .collect();
+        fs::remove_file(&path).unwrap();
+
+        for (cut, result) in cuts.iter().zip(results) {
+            match result {
+                Err(ProofError::InvalidProofFile(_)) => {}
+                Err(e) => panic!("cut at {cut}: expected InvalidProofFile, got {e}"),
+                Ok(_) => panic!("cut at {cut}: truncated file loaded"),
+            }
+        }
+    }
+}
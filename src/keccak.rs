@@ -1,84 +1,196 @@
 use binius_circuits::keccak::Keccak256;
-use binius_core::{verify::verify_constraints, word::Word};
-use binius_frontend::CircuitBuilder;
+use binius_core::{constraint_system::ConstraintSystem, word::Word};
+use binius_frontend::{CircuitBuilder, Wire, WitnessFiller};
 use sha3::{Digest, Keccak256 as CpuKeccak256};
 
-use binius_prover::{
-    OptimalPackedB128, Prover, hash::parallel_compression::ParallelCompressionAdaptor,
+use crate::config::RunConfig;
+use crate::error::ProofError;
+use crate::hash_circuit::{
+    HashCircuit, WordEndian, build_cs, digest_only_words, digest_words, prove_hash_with_digest,
+    prove_hash_with_max_len, public_digest, run_hash_circuit_with_config, verify_hash,
+    words_to_bytes, words_to_digest,
 };
-use binius_transcript::{ProverTranscript, VerifierTranscript};
-use binius_verifier::{
-    Verifier,
-    config::StdChallenger,
-    hash::{StdCompression, StdDigest},
-};
-
-use std::time::Instant;
+use crate::timings::CircuitTimings;
+
+/// The keccak-256 gadget together with the wire carrying the preimage length.
+pub struct Keccak256Hash {
+    keccak: Keccak256,
+    message: Vec<Wire>,
+    len_bytes: Wire,
+    max_len: usize,
+}
 
-pub fn keccak_circuit(image_bytes: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Proof for keccak circuit: ");
-    // Start timer for setup
-    //let setup_timer = Instant::now();
-    // New Circuit
-    let builder = CircuitBuilder::new();
+impl HashCircuit for Keccak256Hash {
+    const NAME: &'static str = "keccak";
 
-    let size = image_bytes.len();
+    fn build(builder: &CircuitBuilder, max_len: usize) -> Self {
+        // the gadget needs at least one message wire, an empty message still gets one
+        // zero word that the length wire marks as unused
+        let n_wires = max_len.div_ceil(8).max(1);
 
-    let n_wires = (size + 7) / 8;
+        let message: Vec<_> = (0..n_wires).map(|_| builder.add_witness()).collect();
 
-    let message: Vec<_> = (0..n_wires).map(|_| builder.add_witness()).collect();
+        let commitment: [_; 4] = core::array::from_fn(|_| builder.add_inout());
 
-    let commitment: [_; 4] = core::array::from_fn(|_| builder.add_inout());
+        let len_bytes = builder.add_witness();
 
-    let len_bytes = builder.add_witness();
+        let keccak = Keccak256::new(builder, len_bytes, commitment, message.clone());
 
-    let keccak = Keccak256::new(&builder, len_bytes, commitment, message);
-    let circuit = builder.build();
-    let mut witness = circuit.new_witness_filler();
-    witness[len_bytes] = Word(size as u64);
-    keccak.populate_message(&mut witness, image_bytes);
+        Self {
+            keccak,
+            message,
+            len_bytes,
+            max_len,
+        }
+    }
 
-    let digest = CpuKeccak256::digest(image_bytes);
-    let mut digest_bytes = [0u8; 32];
-    digest_bytes.copy_from_slice(&digest);
-    keccak.populate_digest(&mut witness, digest_bytes);
+    fn max_len(&self) -> usize {
+        self.max_len
+    }
 
-    circuit.populate_wire_witness(&mut witness)?;
+    fn populate_with_digest(&self, witness: &mut WitnessFiller, msg: &[u8], digest: &[u8]) {
+        witness[self.len_bytes] = Word(msg.len() as u64);
+        self.keccak.populate_message(witness, msg);
 
-    let cs = circuit.constraint_system();
-    let witness_vec = witness.into_value_vec();
-    verify_constraints(cs, &witness_vec)?;
+        let mut digest_bytes = [0u8; 32];
+        digest_bytes.copy_from_slice(digest);
+        self.keccak.populate_digest(witness, digest_bytes);
+    }
 
-    println!("✓ constraint verified");
+    // the message wires are these words, assign them without going through bytes
+    fn populate_words(&self, witness: &mut WitnessFiller, words: &[Word]) {
+        debug_assert!(words.len() <= self.message.len());
+        for (i, &w) in self.message.iter().enumerate() {
+            witness[w] = words.get(i).copied().unwrap_or(Word(0));
+        }
+        witness[self.len_bytes] = Word(8 * words.len() as u64);
 
-    // prove / verify
-    let compression = ParallelCompressionAdaptor::new(StdCompression::default());
-    let verifier = Verifier::<StdDigest, _>::setup(cs.clone(), 1, StdCompression::default())?;
-    let prover = Prover::<OptimalPackedB128, _, StdDigest>::setup(verifier.clone(), compression)?;
+        // the reference implementation still needs the bytes
+        let msg = words_to_bytes(words);
+        self.keccak
+            .populate_digest(witness, CpuKeccak256::digest(&msg).into());
+    }
 
-    let challenger = StdChallenger::default();
-    let mut prover_transcript = ProverTranscript::new(challenger.clone());
-    let public_words = witness_vec.public().to_vec();
+    fn digest_from_public(&self, public: &[Word]) -> Vec<u8> {
+        public_digest(public, 32, DIGEST_ENDIAN)
+    }
 
-    //println!("Setup time {}ms", setup_timer.elapsed().as_millis());
+    fn reference_digest(msg: &[u8]) -> Vec<u8> {
+        CpuKeccak256::digest(msg).to_vec()
+    }
+}
 
-    let prove_timer = Instant::now();
+/// Byte order of the keccak-256 digest words: Keccak's lanes are little-endian, so
+/// digest byte `8i` is the least significant byte of digest word `i`.
+pub const DIGEST_ENDIAN: WordEndian = WordEndian::Little;
+
+/// The public words of a keccak-256 proof, with the digest at its known position.
+///
+/// Use this instead of indexing the raw words, whose leading entries are the circuit's
+/// constants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeccakPublicInputs {
+    words: Vec<Word>,
+}
 
-    prover.prove(witness_vec, &mut prover_transcript)?;
-    let proof = prover_transcript.finalize();
+impl KeccakPublicInputs {
+    /// Wrap the public words of a proof, e.g. as returned by `keccak_prove_digest`.
+    pub fn from_words(words: Vec<Word>) -> Result<Self, ProofError> {
+        digest_words(&words, 4)?;
+        Ok(Self { words })
+    }
+
+    /// The 32-byte digest, the last 4 public words, each little-endian.
+    pub fn digest(&self) -> [u8; 32] {
+        let words = digest_words(&self.words, 4).expect("checked in from_words");
+        words_to_digest(words, 32, DIGEST_ENDIAN)
+            .try_into()
+            .expect("4 words hold 32 bytes")
+    }
+
+    /// All public words, as passed to the verifier.
+    pub fn words(&self) -> &[Word] {
+        &self.words
+    }
+
+    pub fn into_words(self) -> Vec<Word> {
+        self.words
+    }
+}
 
-    println!("Proof time {}ms", prove_timer.elapsed().as_millis());
+/// Prove keccak256(m) = h with the circuit built for messages of up to `max_len` bytes,
+/// see `prove_hash_with_max_len`. Verify with `verify_keccak(max_len, ..)`.
+pub fn keccak_prove_with_max_len(
+    image_bytes: &[u8],
+    max_len: usize,
+    verbose: bool,
+) -> Result<(Vec<u8>, Vec<Word>, CircuitTimings), ProofError> {
+    prove_hash_with_max_len::<Keccak256Hash>(image_bytes, max_len, verbose)
+}
 
-    let mut verifier_transcript = VerifierTranscript::new(challenger, proof);
+/// The keccak-256 digest of `image_bytes` read from the public words of the checked
+/// circuit witness, without proving, see `hash_circuit::digest_only_words`.
+pub fn digest_only_keccak(image_bytes: &[u8]) -> Result<[u8; 32], ProofError> {
+    let words = digest_only_words::<Keccak256Hash>(image_bytes)?;
+    Ok(KeccakPublicInputs::from_words(words)?.digest())
+}
 
-    let verify_timer = Instant::now();
+/// Prove knowledge of a preimage of the given keccak-256 `digest`, see
+/// `prove_hash_with_digest`.
+pub fn keccak_prove_digest(
+    image_bytes: &[u8],
+    digest: &[u8; 32],
+    verbose: bool,
+) -> Result<(Vec<u8>, Vec<Word>, CircuitTimings), ProofError> {
+    prove_hash_with_digest::<Keccak256Hash>(image_bytes, digest, verbose)
+}
 
-    verifier.verify(&public_words, &mut verifier_transcript)?;
-    verifier_transcript.finalize()?;
+/// Verify a keccak proof from its public words alone, without the witness or any
+/// prover setup. `max_len` is the length the circuit was built for, the message
+/// length or the `max_len` given to `keccak_prove_with_max_len`.
+pub fn verify_keccak(
+    max_len: usize,
+    proof: Vec<u8>,
+    public_words: &[Word],
+) -> Result<(), ProofError> {
+    verify_hash::<Keccak256Hash>(max_len, proof, public_words, false).map(|_| ())
+}
 
-    println!("Verify time {}ms", verify_timer.elapsed().as_millis());
+/// The keccak-256 constraint system for messages of up to `max_len` bytes, see
+/// `hash_circuit::build_cs`.
+pub fn build_cs_keccak(max_len: usize) -> ConstraintSystem {
+    build_cs::<Keccak256Hash>(max_len)
+}
 
-    println!("✓ proof successfully verified");
+pub fn keccak_circuit(
+    image_bytes: &[u8],
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    run_hash_circuit_with_config(image_bytes, config, Keccak256Hash::build)
+}
 
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn public_inputs_digest_matches_reference() {
+        let msg = b"abc";
+        let (_, public_words, _) =
+            crate::hash_circuit::prove_hash::<Keccak256Hash>(msg, false).unwrap();
+        let public = KeccakPublicInputs::from_words(public_words.clone()).unwrap();
+        assert_eq!(
+            public.digest().to_vec(),
+            Keccak256Hash::reference_digest(msg)
+        );
+        assert_eq!(public.words(), &public_words[..]);
+    }
+
+    #[test]
+    fn digest_only_matches_known_answer() {
+        assert_eq!(
+            hex::encode(digest_only_keccak(b"abc").unwrap()),
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+        );
+    }
 }
@@ -1,18 +1,19 @@
-use binius_core::{verify::verify_constraints, word::Word};
+use binius_core::{
+    constraint_system::{ConstraintSystem, ValueVec},
+    word::Word,
+};
 use binius_field::{BinaryField128bGhash as F, Field};
-use binius_frontend::{CircuitBuilder, Wire};
+use binius_frontend::{Circuit, CircuitBuilder, Wire};
+use binius_verifier::config::StdChallenger;
 
-use binius_prover::{
-    OptimalPackedB128, Prover, hash::parallel_compression::ParallelCompressionAdaptor,
-};
-use binius_transcript::{ProverTranscript, VerifierTranscript};
-use binius_verifier::{
-    Verifier,
-    config::StdChallenger,
-    hash::{StdCompression, StdDigest},
-};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use std::{fmt, time::Instant};
 
-use rand::Rng;
+use crate::config::RunConfig;
+use crate::error::ProofError;
+use crate::hash_circuit::{check_circuit, prove_and_verify_circuit, prove_circuit, verify_circuit};
+use crate::timings::CircuitTimings;
 
 // -------------------- 128-bit “wire element” --------------------
 
@@ -98,13 +99,135 @@ fn gfmul_by_public_byte(b: &CircuitBuilder, a: U128, x: U128) -> U128 {
 
 // -------------------- The matrix-hash circuit: H = A · I over GF(2^128) --------------------
 
-pub fn lattice_circuit(image_bytes: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Proof for lattice hash circuit: ");
-    let m = 128usize;
-    let n = image_bytes.len();
+/// Rows of `A`, i.e. field elements in the digest `H`.
+pub const LATTICE_ROWS: usize = 128;
+
+/// Reduction polynomial of the field the matrix hash is computed over, the analogue of
+/// the modulus `q` of an SIS hash over Z_q.
+pub const LATTICE_MODULUS: &str = "x^128 + x^7 + x^2 + x + 1";
+
+/// The parameters a lattice circuit is instantiated with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatticeParams {
+    /// Rows of `A`, the digest length in field elements.
+    pub m: usize,
+    /// Columns of `A`, the image length in bytes.
+    pub n: usize,
+}
+
+impl LatticeParams {
+    pub fn for_image(image_len: usize) -> Self {
+        LatticeParams {
+            m: LATTICE_ROWS,
+            n: image_len,
+        }
+    }
+}
+
+impl fmt::Display for LatticeParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "m = {}, n = {}, field GF(2^128) modulo {}",
+            self.m, self.n, LATTICE_MODULUS
+        )
+    }
+}
+
+/// Proves knowledge of a secret matrix `A` (128 x n, entries in GF(2^128)) such that
+/// `H = A · I`, where `I` is the image lifted byte-wise into GF(2^128) and `H` is the
+/// resulting 128-element digest. `I` and `H` are public, `A` stays private.
+///
+/// This is the linear matrix-hash relation behind SIS-style lattice hashes, instantiated
+/// over a binary field instead of Z_q. The circuit has m * n * 8 multiply-by-x steps, so
+/// it is only practical for short images.
+pub fn lattice_circuit(
+    image_bytes: &[u8],
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    if config.print_progress() {
+        println!("Proof for lattice hash circuit: ");
+        println!(
+            "Lattice parameters: {}",
+            LatticeParams::for_image(image_bytes.len())
+        );
+    }
+    let (circuit, witness_vec) = lattice_witness(image_bytes, config.seed)?;
+    prove_and_verify_circuit(&circuit, witness_vec, config)
+        .map_err(|e| e.in_circuit("lattice", image_bytes.len()))
+}
+
+/// Build and populate the lattice circuit for `image_bytes` and check its constraints,
+/// without proving.
+pub fn lattice_check(image_bytes: &[u8], config: &RunConfig) -> Result<CircuitTimings, ProofError> {
+    let verbose = config.print_progress();
+    if verbose {
+        println!("Constraint check for lattice hash circuit: ");
+        println!(
+            "Lattice parameters: {}",
+            LatticeParams::for_image(image_bytes.len())
+        );
+    }
+    let (circuit, witness_vec) = lattice_witness(image_bytes, config.seed)?;
+    check_circuit(&circuit, &witness_vec, verbose)
+        .map_err(|e| e.in_circuit("lattice", image_bytes.len()))
+}
+
+/// The lattice constraint system for `image_bytes`, e.g. to inspect its size. It only
+/// depends on the image length, the witness populated on the way uses a fixed seed.
+pub fn build_cs_lattice(image_bytes: &[u8]) -> Result<ConstraintSystem, ProofError> {
+    let (circuit, _) = lattice_witness(image_bytes, Some(0))?;
+    Ok(circuit.constraint_system().clone())
+}
+
+/// Prove the lattice relation for `image_bytes`, with `A` sampled from `seed` if given,
+/// returning the proof bytes and public words. Verify with
+/// `verify_lattice(image_bytes.len(), ..)`.
+pub fn lattice_prove(
+    image_bytes: &[u8],
+    seed: Option<u64>,
+) -> Result<(Vec<u8>, Vec<Word>), ProofError> {
+    let (circuit, witness_vec) = lattice_witness(image_bytes, seed)?;
+    let (proof, public_words, _) =
+        prove_circuit(&circuit, witness_vec, StdChallenger::default(), false)
+            .map_err(|e| e.in_circuit("lattice", image_bytes.len()))?;
+    Ok((proof, public_words))
+}
+
+/// Verify a lattice proof from its public words alone, which hold the image and the
+/// digest. The circuit is rebuilt from `image_len`, as in `build_cs_lattice`.
+pub fn verify_lattice(
+    image_len: usize,
+    proof: Vec<u8>,
+    public_words: &[Word],
+) -> Result<(), ProofError> {
+    let (circuit, _) = lattice_witness(&vec![0; image_len], Some(0))?;
+    verify_circuit(
+        &circuit,
+        proof,
+        public_words,
+        StdChallenger::default(),
+        false,
+    )
+    .map(|_| ())
+    .map_err(|e| e.in_circuit("lattice", image_len))
+}
+
+// Sample a secret A, from `seed` if given and fresh otherwise, build the circuit and
+// populate the witness for `image_bytes`.
+#[allow(non_snake_case)]
+fn lattice_witness(
+    image_bytes: &[u8],
+    seed: Option<u64>,
+) -> Result<(Circuit, ValueVec), ProofError> {
+    let LatticeParams { m, n } = LatticeParams::for_image(image_bytes.len());
 
     // ----- Host: sample A (private), lift I (public), compute H (public) -----
-    let mut rng = rand::rng();
+    let timer = Instant::now();
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_rng(&mut rand::rng()),
+    };
 
     // Secret random A in GF(2^128)
     let A: Vec<Vec<F>> = (0..m)
@@ -123,7 +246,10 @@ pub fn lattice_circuit(image_bytes: &[u8]) -> Result<(), Box<dyn std::error::Err
         }
         H[i] = acc;
     }
+    log::info!("lattice: sampled A and computed H in {:?}", timer.elapsed());
+
     // ----- Circuit build: A = witness, I/H = inout -----
+    let timer = Instant::now();
     let builder = CircuitBuilder::new();
 
     // A[i][j] (private) -> 2 wires each
@@ -169,7 +295,13 @@ pub fn lattice_circuit(image_bytes: &[u8]) -> Result<(), Box<dyn std::error::Err
     }
 
     let circuit = builder.build();
+    log::info!(
+        "lattice: built circuit for m = {m}, n = {n} in {:?}",
+        timer.elapsed()
+    );
+
     // ----- Fill wires -----
+    let timer = Instant::now();
     let mut filler = circuit.new_witness_filler();
 
     // A (private)
@@ -194,33 +326,10 @@ pub fn lattice_circuit(image_bytes: &[u8]) -> Result<(), Box<dyn std::error::Err
         filler[h_idx[i].hi] = Word(hi);
     }
 
-    circuit.populate_wire_witness(&mut filler)?;
-
-    // Optional local constraint check
-    let cs = circuit.constraint_system();
-    let witness_vec = filler.into_value_vec();
-    verify_constraints(cs, &witness_vec)?;
-    println!("✓ constraint verified");
-    // ----- Prove / Verify -----
-    let compression = ParallelCompressionAdaptor::new(StdCompression::default());
-    let verifier = Verifier::<StdDigest, _>::setup(cs.clone(), 1, StdCompression::default())?;
-    let prover = Prover::<OptimalPackedB128, _, StdDigest>::setup(verifier.clone(), compression)?;
-
-    let challenger = StdChallenger::default();
-    let mut prover_tx = ProverTranscript::new(challenger.clone());
-    let public_words = witness_vec.public().to_vec();
-
-    let prove_timer = std::time::Instant::now();
-    prover.prove(witness_vec, &mut prover_tx)?;
-    let proof = prover_tx.finalize();
-    eprintln!("Proof time {}ms", prove_timer.elapsed().as_millis());
-
-    let mut verifier_tx = VerifierTranscript::new(challenger, proof);
-    let verify_timer = std::time::Instant::now();
-    verifier.verify(&public_words, &mut verifier_tx)?;
-    verifier_tx.finalize()?;
-    eprintln!("Verify time {}ms", verify_timer.elapsed().as_millis());
-
-    println!("✓ proof successfully verified");
-    Ok(())
+    circuit
+        .populate_wire_witness(&mut filler)
+        .map_err(|e| ProofError::from(e).in_circuit("lattice", n))?;
+    log::info!("lattice: populated witness in {:?}", timer.elapsed());
+
+    Ok((circuit, filler.into_value_vec()))
 }
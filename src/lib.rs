@@ -0,0 +1,93 @@
+//! Binius64 proofs of hash preimages: one circuit per hash function, each proving
+//! knowledge of a message with a given public digest, plus the lattice matrix-hash
+//! relation.
+//!
+//! Every hash circuit implements [`HashCircuit`], so the generic drivers in
+//! [`hash_circuit`] build, prove and verify any of them. The `*_circuit` functions run
+//! the whole flow for a single message with the options in a [`RunConfig`] and return
+//! the timings.
+//!
+//! Each circuit module sits behind a cargo feature of the same name (`sha2-truncated`
+//! for `sha2_truncated`), all enabled by default. `multi_hash` needs both `sha256` and
+//! `keccak`. The exception is `md5`, behind the `legacy` feature and off by default,
+//! since MD5 is only there for compatibility with existing checksums. The `verify-core`
+//! feature adds [`verify_core`], the verify path without the crate's own use of `std`;
+//! the crate itself is not `no_std`.
+//!
+//! There is no Poseidon circuit. The pinned `binius_circuits` has no Poseidon gadget, and
+//! Poseidon's rounds are additions and powers over a prime field, which binius64 does
+//! not have natively: every field operation would have to be emulated with 64-bit
+//! integer multiplications and a modular reduction, giving up the in-circuit cost
+//! advantage that is the reason to pick Poseidon in the first place.
+//!
+//! There is no Groestl circuit either. `binius_circuits` has no Groestl gadget and no
+//! AES-style primitive to build one from, and unlike the hand-built ARX and
+//! Keccak-style gadgets here, Groestl's rounds apply the AES S-box to every state byte.
+//! Without a lookup argument each S-box is an inversion in GF(2^8) spelled out in AND
+//! constraints, and a Groestl-256 block runs 1280 of them (two 10-round permutations
+//! over 64 bytes), Groestl-512 3584, which would make it by far the most expensive
+//! circuit in the crate.
+
+#[cfg(feature = "verify-core")]
+extern crate alloc;
+
+pub mod algo;
+#[cfg(feature = "blake2b")]
+pub mod blake2b;
+#[cfg(feature = "blake2s")]
+pub mod blake2s;
+#[cfg(feature = "blake3")]
+pub mod blake3;
+#[cfg(feature = "sha256")]
+pub mod chunked;
+pub mod config;
+pub mod cpu;
+#[cfg(feature = "sha256")]
+pub mod equality;
+pub mod error;
+pub mod hash_circuit;
+#[cfg(feature = "sha256")]
+pub mod header_body;
+#[cfg(feature = "hmac")]
+pub mod hmac;
+pub mod io;
+#[cfg(feature = "keccak")]
+pub mod keccak;
+#[cfg(feature = "lattice")]
+pub mod lattice;
+#[cfg(feature = "legacy")]
+pub mod md5;
+pub mod mem;
+#[cfg(feature = "merkle")]
+pub mod merkle;
+#[cfg(all(feature = "sha256", feature = "keccak"))]
+pub mod multi_hash;
+pub mod panic_context;
+#[cfg(feature = "ripemd")]
+pub mod ripemd;
+#[cfg(feature = "sha256")]
+pub mod sha256;
+#[cfg(feature = "sha2-truncated")]
+pub mod sha2_truncated;
+#[cfg(feature = "sha3")]
+pub mod sha3;
+#[cfg(feature = "sha512")]
+pub mod sha512;
+#[cfg(feature = "sha256")]
+pub mod substring;
+pub mod timings;
+#[cfg(feature = "verify-core")]
+pub mod verify_core;
+
+#[cfg(feature = "blake2b")]
+pub use blake2b::blake2b_circuit;
+pub use config::{Format, RunConfig};
+pub use error::ProofError;
+pub use hash_circuit::{HashCircuit, HashProver};
+#[cfg(feature = "keccak")]
+pub use keccak::keccak_circuit;
+#[cfg(feature = "lattice")]
+pub use lattice::lattice_circuit;
+#[cfg(feature = "sha256")]
+pub use sha256::sha256_circuit;
+pub use timings::CircuitTimings;
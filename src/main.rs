@@ -1,28 +1,738 @@
-// run with RUSTFLAGS="-C target-cpu=native" cargo run --release
+// run with RUSTFLAGS="-C target-cpu=native" cargo run --release -- [options], see cli.rs for the options
 // edited from https://www.binius.xyz/building/example
 
-use crate::lattice::lattice_circuit;
-use crate::sha256::sha256_circuit;
-use crate::blake2b::blake2b_circuit;
-use crate::keccak::keccak_circuit;
+#[cfg(feature = "blake2b")]
+use sha256_example::blake2b::Blake2bHash;
+#[cfg(feature = "blake2s")]
+use sha256_example::blake2s::Blake2sHash;
+#[cfg(feature = "blake3")]
+use sha256_example::blake3::Blake3Hash;
+use sha256_example::hash_circuit::{
+    HashCircuit, HashProver, build_cs, check_hash, cs_summary, run_hash_circuit_with_config,
+};
+#[cfg(feature = "keccak")]
+use sha256_example::keccak::Keccak256Hash;
+#[cfg(feature = "lattice")]
+use sha256_example::lattice::{build_cs_lattice, lattice_check, lattice_circuit};
+#[cfg(feature = "legacy")]
+use sha256_example::md5::Md5Hash;
+#[cfg(all(feature = "sha256", feature = "keccak"))]
+use sha256_example::multi_hash::Sha256KeccakHash;
+#[cfg(feature = "ripemd")]
+use sha256_example::ripemd::{Hash160Hash, Ripemd160Hash};
+#[cfg(feature = "sha2-truncated")]
+use sha256_example::sha2_truncated::{Sha224Hash, Sha384Hash};
+#[cfg(feature = "sha3")]
+use sha256_example::sha3::{Keccak512Hash, Sha3_256Hash};
+#[cfg(feature = "sha256")]
+use sha256_example::sha256::{Sha256Hash, Sha256dHash};
+#[cfg(feature = "sha512")]
+use sha256_example::sha512::Sha512Hash;
+use sha256_example::timings::Spread;
+use sha256_example::{CircuitTimings, Format, ProofError, RunConfig, cpu, panic_context};
 
-mod lattice;
-mod sha256;
-mod blake2b;
-mod keccak;
-use rand::Rng;
+use crate::cli::{Algo, Args};
+
+mod cli;
+mod selftest;
+
+use binius_core::constraint_system::ConstraintSystem;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::Instant;
+
+const LATTICE_IMAGE_LEN: usize = 256;
+
+// The part of the image `algo` is run on: the lattice circuit grows with 128 * n, so
+// only a short prefix of the image is proven.
+fn algo_image(algo: Algo, image: &[u8]) -> &[u8] {
+    match algo {
+        Algo::Lattice => &image[..image.len().min(LATTICE_IMAGE_LEN)],
+        _ => image,
+    }
+}
+
+// One algo at one `log_inv_rate`.
+#[derive(Clone, Copy)]
+struct Job {
+    algo: Algo,
+    log_inv_rate: usize,
+}
+
+// Every requested algo at every requested rate, algo-major.
+fn jobs(args: &Args) -> Vec<Job> {
+    args.algos
+        .iter()
+        .flat_map(|&algo| {
+            args.log_inv_rates
+                .iter()
+                .map(move |&log_inv_rate| Job { algo, log_inv_rate })
+        })
+        .collect()
+}
+
+// Prove and verify, or with `check_only` only check the constraints.
+fn run<H: HashCircuit>(
+    image: &[u8],
+    check_only: bool,
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    if check_only {
+        check_hash::<H>(image, config.print_progress())
+    } else {
+        run_hash_circuit_with_config(image, config, H::build)
+    }
+}
+
+fn run_algo(
+    job: Job,
+    image: &[u8],
+    args: &Args,
+    verbose: bool,
+) -> Result<CircuitTimings, ProofError> {
+    let Job { algo, log_inv_rate } = job;
+    let image = algo_image(algo, image);
+    let _context = panic_context::enter(format!(
+        "{} on {} bytes at log_inv_rate {log_inv_rate}",
+        algo.name(),
+        image.len()
+    ));
+    let config = RunConfig::new()
+        .verbose(verbose)
+        .log_inv_rate(log_inv_rate)
+        .seed(args.seed)
+        .format(args.format)
+        .skip_local_check(args.skip_local_check);
+    let check_only = args.check_only;
+    match algo {
+        #[cfg(feature = "lattice")]
+        Algo::Lattice => {
+            if check_only {
+                lattice_check(image, &config)
+            } else {
+                lattice_circuit(image, &config)
+            }
+        }
+        #[cfg(feature = "sha256")]
+        Algo::Sha256 => run::<Sha256Hash>(image, check_only, &config),
+        #[cfg(feature = "sha256")]
+        Algo::Sha256d => run::<Sha256dHash>(image, check_only, &config),
+        #[cfg(feature = "sha512")]
+        Algo::Sha512 => run::<Sha512Hash>(image, check_only, &config),
+        #[cfg(feature = "sha2-truncated")]
+        Algo::Sha224 => run::<Sha224Hash>(image, check_only, &config),
+        #[cfg(feature = "sha2-truncated")]
+        Algo::Sha384 => run::<Sha384Hash>(image, check_only, &config),
+        #[cfg(feature = "blake2b")]
+        Algo::Blake2b => run::<Blake2bHash>(image, check_only, &config),
+        #[cfg(feature = "blake2s")]
+        Algo::Blake2s => run::<Blake2sHash>(image, check_only, &config),
+        #[cfg(feature = "blake3")]
+        Algo::Blake3 => run::<Blake3Hash>(image, check_only, &config),
+        #[cfg(feature = "keccak")]
+        Algo::Keccak => run::<Keccak256Hash>(image, check_only, &config),
+        #[cfg(all(feature = "sha256", feature = "keccak"))]
+        Algo::Sha256Keccak => run::<Sha256KeccakHash>(image, check_only, &config),
+        #[cfg(feature = "sha3")]
+        Algo::Sha3_256 => run::<Sha3_256Hash>(image, check_only, &config),
+        #[cfg(feature = "sha3")]
+        Algo::Keccak512 => run::<Keccak512Hash>(image, check_only, &config),
+        #[cfg(feature = "ripemd")]
+        Algo::Ripemd160 => run::<Ripemd160Hash>(image, check_only, &config),
+        #[cfg(feature = "ripemd")]
+        Algo::Hash160 => run::<Hash160Hash>(image, check_only, &config),
+        #[cfg(feature = "legacy")]
+        Algo::Md5 => run::<Md5Hash>(image, check_only, &config),
+        // `Args::parse` only accepts algos that are compiled in
+        #[allow(unreachable_patterns)]
+        _ => unreachable!("{} is not compiled in", algo.name()),
+    }
+}
+
+// The constraint system `algo` is run with on `image`, for `--dump-cs`.
+fn algo_cs(algo: Algo, image: &[u8]) -> Result<ConstraintSystem, ProofError> {
+    let len = algo_image(algo, image).len();
+    Ok(match algo {
+        #[cfg(feature = "lattice")]
+        Algo::Lattice => build_cs_lattice(algo_image(algo, image))?,
+        #[cfg(feature = "sha256")]
+        Algo::Sha256 => build_cs::<Sha256Hash>(len),
+        #[cfg(feature = "sha256")]
+        Algo::Sha256d => build_cs::<Sha256dHash>(len),
+        #[cfg(feature = "sha512")]
+        Algo::Sha512 => build_cs::<Sha512Hash>(len),
+        #[cfg(feature = "sha2-truncated")]
+        Algo::Sha224 => build_cs::<Sha224Hash>(len),
+        #[cfg(feature = "sha2-truncated")]
+        Algo::Sha384 => build_cs::<Sha384Hash>(len),
+        #[cfg(feature = "blake2b")]
+        Algo::Blake2b => build_cs::<Blake2bHash>(len),
+        #[cfg(feature = "blake2s")]
+        Algo::Blake2s => build_cs::<Blake2sHash>(len),
+        #[cfg(feature = "blake3")]
+        Algo::Blake3 => build_cs::<Blake3Hash>(len),
+        #[cfg(feature = "keccak")]
+        Algo::Keccak => build_cs::<Keccak256Hash>(len),
+        #[cfg(all(feature = "sha256", feature = "keccak"))]
+        Algo::Sha256Keccak => build_cs::<Sha256KeccakHash>(len),
+        #[cfg(feature = "sha3")]
+        Algo::Sha3_256 => build_cs::<Sha3_256Hash>(len),
+        #[cfg(feature = "sha3")]
+        Algo::Keccak512 => build_cs::<Keccak512Hash>(len),
+        #[cfg(feature = "ripemd")]
+        Algo::Ripemd160 => build_cs::<Ripemd160Hash>(len),
+        #[cfg(feature = "ripemd")]
+        Algo::Hash160 => build_cs::<Hash160Hash>(len),
+        #[cfg(feature = "legacy")]
+        Algo::Md5 => build_cs::<Md5Hash>(len),
+        // `Args::parse` only accepts algos that are compiled in
+        #[allow(unreachable_patterns)]
+        _ => unreachable!("{} is not compiled in", algo.name()),
+    })
+}
+
+// Time from nothing to the first proof, building the circuit and setting the prover up
+// included, then the proving time of `iters` more proofs with the same prover, in ms.
+fn cold_start<H: HashCircuit>(
+    image: &[u8],
+    log_inv_rate: usize,
+    iters: usize,
+) -> Result<(u128, Vec<u128>), ProofError> {
+    let timer = Instant::now();
+    let prover = HashProver::<H>::with_log_inv_rate(image.len(), log_inv_rate)?;
+    prover.prove(image)?;
+    let cold_ms = timer.elapsed().as_millis();
+
+    let warm_ms = (0..iters)
+        .map(|_| {
+            let timer = Instant::now();
+            prover.prove(image)?;
+            Ok(timer.elapsed().as_millis())
+        })
+        .collect::<Result<_, ProofError>>()?;
+    Ok((cold_ms, warm_ms))
+}
+
+// `cold_start` for `job`, `None` for the lattice circuit, which has no reusable prover.
+fn algo_cold_start(
+    job: Job,
+    image: &[u8],
+    iters: usize,
+) -> Result<Option<(u128, Vec<u128>)>, ProofError> {
+    let Job { algo, log_inv_rate } = job;
+    let image = algo_image(algo, image);
+    let run = match algo {
+        Algo::Lattice => return Ok(None),
+        #[cfg(feature = "sha256")]
+        Algo::Sha256 => cold_start::<Sha256Hash>,
+        #[cfg(feature = "sha256")]
+        Algo::Sha256d => cold_start::<Sha256dHash>,
+        #[cfg(feature = "sha512")]
+        Algo::Sha512 => cold_start::<Sha512Hash>,
+        #[cfg(feature = "sha2-truncated")]
+        Algo::Sha224 => cold_start::<Sha224Hash>,
+        #[cfg(feature = "sha2-truncated")]
+        Algo::Sha384 => cold_start::<Sha384Hash>,
+        #[cfg(feature = "blake2b")]
+        Algo::Blake2b => cold_start::<Blake2bHash>,
+        #[cfg(feature = "blake2s")]
+        Algo::Blake2s => cold_start::<Blake2sHash>,
+        #[cfg(feature = "blake3")]
+        Algo::Blake3 => cold_start::<Blake3Hash>,
+        #[cfg(feature = "keccak")]
+        Algo::Keccak => cold_start::<Keccak256Hash>,
+        #[cfg(all(feature = "sha256", feature = "keccak"))]
+        Algo::Sha256Keccak => cold_start::<Sha256KeccakHash>,
+        #[cfg(feature = "sha3")]
+        Algo::Sha3_256 => cold_start::<Sha3_256Hash>,
+        #[cfg(feature = "sha3")]
+        Algo::Keccak512 => cold_start::<Keccak512Hash>,
+        #[cfg(feature = "ripemd")]
+        Algo::Ripemd160 => cold_start::<Ripemd160Hash>,
+        #[cfg(feature = "ripemd")]
+        Algo::Hash160 => cold_start::<Hash160Hash>,
+        #[cfg(feature = "legacy")]
+        Algo::Md5 => cold_start::<Md5Hash>,
+        // `Args::parse` only accepts algos that are compiled in
+        #[allow(unreachable_patterns)]
+        _ => unreachable!("{} is not compiled in", algo.name()),
+    };
+    run(image, log_inv_rate, iters).map(Some)
+}
+
+// Print the cold start and steady state of every job on `image`, see `Args::cold_start`.
+fn print_cold_start(image: &[u8], args: &Args) -> Result<(), ProofError> {
+    for job in jobs(args) {
+        let mut label = job.algo.name().to_string();
+        if args.log_inv_rates.len() > 1 {
+            label += &format!(" at log_inv_rate {}", job.log_inv_rate);
+        }
+        let Some((cold_ms, warm_ms)) = algo_cold_start(job, image, args.iters)? else {
+            println!("{label}: no reusable prover, skipped");
+            continue;
+        };
+        println!("{label}: cold start (build, setup and first proof) {cold_ms}ms");
+        println!(
+            "{label}: steady state proof min/median/max over {} runs {}",
+            warm_ms.len(),
+            Spread::of(warm_ms)
+        );
+    }
+    Ok(())
+}
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // demo image vector
-    let n = 1 << 17;
-    let mut rng = rand::rng();
-    let mut image = vec![0u8; n];
-    rng.fill(&mut image[..]);
-    println!("Starting proofs for image size: {}", n);
-    //lattice_circuit(&image)?;
-    sha256_circuit(&image)?;
-    blake2b_circuit(&image)?;
-    keccak_circuit(&image)?;
-    
+    let mut argv = std::env::args().skip(1).peekable();
+    if argv.peek().map(String::as_str) == Some("selftest") {
+        let ok = selftest::run();
+        std::process::exit(if ok { 0 } else { 1 });
+    }
+
+    let args = match Args::parse(argv) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    };
+
+    cpu::warn_missing_target_features();
+    panic_context::install_hook();
+
+    // before anything runs on the global pool, it can only be configured once
+    if let Some(threads) = args.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()?;
+    }
+
+    let text = args.format == Format::Text;
+    if text {
+        println!("Proving on {} threads", rayon::current_num_threads());
+    }
+    let images = match &args.input {
+        Some(path) => {
+            let image = match std::fs::read(path) {
+                Ok(image) => image,
+                Err(e) => {
+                    eprintln!("cannot read {}: {e}", path.display());
+                    std::process::exit(1);
+                }
+            };
+            if text {
+                println!(
+                    "Starting proofs for {} ({} bytes)",
+                    path.display(),
+                    image.len()
+                );
+            }
+            vec![image]
+        }
+        // demo image vectors, one per size
+        None => args
+            .sizes
+            .iter()
+            .map(|&n| {
+                let mut rng = StdRng::seed_from_u64(args.seed);
+                let mut image = vec![0u8; n];
+                rng.fill(&mut image[..]);
+                image
+            })
+            .collect(),
+    };
+
+    if !text {
+        match run_report(&images, &args) {
+            Ok(true) => return Ok(()),
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("cannot write the report: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let mut rows = Vec::new();
+    for image in &images {
+        if args.input.is_none() {
+            println!(
+                "Starting proofs for image size: {} (seed {})",
+                image.len(),
+                args.seed
+            );
+        }
+        if args.cold_start {
+            print_cold_start(image, &args)?;
+            continue;
+        }
+        rows.extend(run_image(image, &args)?);
+    }
+    if images.len() > 1 && !args.cold_start {
+        print_scaling_table(&rows, &args);
+    }
+    if args.compare {
+        print_compare_table(&rows);
+    }
+
     Ok(())
-}
\ No newline at end of file
+}
+
+// One row of the scaling table: the mean over the runs of `job` at one input size.
+struct Row {
+    job: Job,
+    input_size: usize,
+    // `fingerprint` of the input, for `--compare`
+    input: u64,
+    mean: CircuitTimings,
+}
+
+// FNV-1a of `bytes`, a short stable name for an input. Not a cryptographic hash, only
+// printed so two comparisons can be checked to have run on the same input.
+fn fingerprint(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |h, &b| {
+        (h ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+// Run every job on `image` and print their summaries.
+fn run_image(image: &[u8], args: &Args) -> Result<Vec<Row>, ProofError> {
+    if args.dump_cs {
+        // the constraint system does not depend on the rate, one per algo
+        for &algo in &args.algos {
+            let cs = algo_cs(algo, image)?;
+            println!(
+                "{} constraint system for {} bytes:",
+                algo.name(),
+                algo_image(algo, image).len()
+            );
+            println!("{}", cs_summary(&cs));
+        }
+    }
+
+    let jobs = jobs(args);
+    let results = if args.parallel {
+        // The binius prover already parallelizes on rayon's global pool. Running the
+        // algos as tasks on that same pool only adds work to it rather than starting
+        // more threads, so there is no oversubscription, but the algos compete for the
+        // cores and their timings are not comparable to a sequential run. All circuits
+        // are also held in memory at once.
+        let results = jobs
+            .par_iter()
+            .map(|&job| run_iters(job, image, args, false))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // report in the requested order, whatever order the tasks finished in
+        for (&job, runs) in jobs.iter().zip(&results) {
+            print_summary(job, algo_image(job.algo, image).len(), runs, args);
+        }
+        results
+    } else {
+        let mut results = Vec::with_capacity(jobs.len());
+        for &job in &jobs {
+            let runs = run_iters(job, image, args, true)?;
+            if args.iters > 1 {
+                print_summary(job, algo_image(job.algo, image).len(), &runs, args);
+            }
+            results.push(runs);
+        }
+        results
+    };
+
+    Ok(jobs
+        .into_iter()
+        .zip(results)
+        .map(|(job, runs)| Row {
+            job,
+            input_size: algo_image(job.algo, image).len(),
+            input: fingerprint(algo_image(job.algo, image)),
+            mean: CircuitTimings::mean(&runs),
+        })
+        .collect())
+}
+
+// Proving time and proof size per algo over all swept sizes, for scaling curves.
+fn print_scaling_table(rows: &[Row], args: &Args) {
+    println!();
+    println!(
+        "{:<16} {:>13} {:>10} {:>12} {:>12} {:>12}",
+        "algo", "log_inv_rate", "size", "prove_ms", "MB/s", "proof_bytes"
+    );
+    // the rows come size-major, group them by algo in the requested order, keeping the
+    // rate and size order within each
+    let mut sorted: Vec<_> = rows.iter().collect();
+    sorted.sort_by_key(|r| args.algos.iter().position(|&a| a == r.job.algo));
+    for r in sorted {
+        println!(
+            "{:<16} {:>13} {:>10} {:>12} {:>12} {:>12}",
+            r.job.algo.name(),
+            r.job.log_inv_rate,
+            r.input_size,
+            r.mean.prove_ms,
+            r.mean
+                .throughput_mbps(r.input_size)
+                .map_or("-".to_string(), |t| format!("{t:.2}")),
+            r.mean.proof_bytes
+        );
+    }
+}
+
+// One table per input size with every algo and rate, fastest prover first.
+fn print_compare_table(rows: &[Row]) {
+    // every algo is run on the same image of each size, see `Args::compare`, only the
+    // lattice rows have their own, shorter image and so a table of their own
+    let mut inputs: Vec<_> = rows.iter().map(|r| (r.input_size, r.input)).collect();
+    inputs.sort();
+    inputs.dedup();
+    for (size, input) in inputs {
+        println!();
+        println!("Comparison at {size} bytes, input fingerprint {input:016x}:");
+        println!(
+            "{:<16} {:>13} {:>12} {:>12} {:>12} {:>12}",
+            "algo", "log_inv_rate", "constraints", "prove_ms", "verify_ms", "proof_bytes"
+        );
+        let mut sorted: Vec<_> = rows
+            .iter()
+            .filter(|r| (r.input_size, r.input) == (size, input))
+            .collect();
+        sorted.sort_by_key(|r| r.mean.prove_ms);
+        for r in sorted {
+            println!(
+                "{:<16} {:>13} {:>12} {:>12} {:>12} {:>12}",
+                r.job.algo.name(),
+                r.job.log_inv_rate,
+                r.mean.n_constraints,
+                r.mean.prove_ms,
+                r.mean.verify_ms,
+                r.mean.proof_bytes
+            );
+        }
+    }
+}
+
+// With several iterations, run once untimed first so allocator and CPU frequency
+// warm-up do not inflate the first measured run.
+fn warm_up(job: Job, image: &[u8], args: &Args) -> Result<(), ProofError> {
+    if args.iters > 1 {
+        run_algo(job, image, args, false)?;
+    }
+    Ok(())
+}
+
+fn run_iters(
+    job: Job,
+    image: &[u8],
+    args: &Args,
+    verbose: bool,
+) -> Result<Vec<CircuitTimings>, ProofError> {
+    warm_up(job, image, args)?;
+    (0..args.iters)
+        .map(|_| {
+            let timings = run_algo(job, image, args, verbose)?;
+            if verbose && !args.check_only {
+                let input_size = algo_image(job.algo, image).len();
+                println!(
+                    "{} proving",
+                    format_throughput(timings.throughput_mbps(input_size))
+                );
+            }
+            if args.mem && verbose {
+                println!("Peak RSS {}", format_rss(timings.peak_rss_bytes));
+            }
+            Ok(timings)
+        })
+        .collect()
+}
+
+fn format_rss(bytes: Option<u64>) -> String {
+    match bytes {
+        Some(bytes) => format!("{:.1} MiB", bytes as f64 / (1 << 20) as f64),
+        None => "unavailable on this platform".to_string(),
+    }
+}
+
+fn format_throughput(mbps: Option<f64>) -> String {
+    match mbps {
+        Some(mbps) => format!("{mbps:.2} MB/s"),
+        None => "unmeasurably fast".to_string(),
+    }
+}
+
+fn print_summary(job: Job, input_size: usize, runs: &[CircuitTimings], args: &Args) {
+    let mean = CircuitTimings::mean(runs);
+    let mut label = job.algo.name().to_string();
+    // only worth naming the rate when sweeping over several
+    if args.log_inv_rates.len() > 1 {
+        label += &format!(" at log_inv_rate {}", job.log_inv_rate);
+    }
+    if runs.len() > 1 {
+        let spread = |phase: fn(&CircuitTimings) -> u128| Spread::of(runs.iter().map(phase));
+        println!(
+            "{label}: min/median/max over {} runs after one untimed warm-up run",
+            runs.len()
+        );
+        println!(
+            "{label}: check {}, setup {}, proof {}, verify {}, proof size {} bytes, {} constraints",
+            spread(|t| t.constraint_check_ms),
+            spread(|t| t.setup_ms),
+            spread(|t| t.prove_ms),
+            spread(|t| t.verify_ms),
+            mean.proof_bytes,
+            mean.n_constraints
+        );
+    } else {
+        println!(
+            "{label}: check {}ms, setup {}ms, proof {}ms, verify {}ms, proof size {} bytes, {} constraints",
+            mean.constraint_check_ms,
+            mean.setup_ms,
+            mean.prove_ms,
+            mean.verify_ms,
+            mean.proof_bytes,
+            mean.n_constraints
+        );
+    }
+    if !args.check_only {
+        println!(
+            "{label}: {} proving",
+            format_throughput(mean.throughput_mbps(input_size))
+        );
+    }
+    if args.mem {
+        println!("{label}: peak RSS {}", format_rss(mean.peak_rss_bytes));
+    }
+}
+
+/// One line of `--format json` output.
+#[derive(Serialize)]
+struct JsonRun<'a> {
+    algo: &'a str,
+    input_size: usize,
+    ok: bool,
+    /// `input_size` over `prove_ms` in MB/s, null for a failed or sub-millisecond proof.
+    throughput_mbps: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(flatten)]
+    timings: CircuitTimings,
+}
+
+// Every timed run of every job on `image`, in the requested algo order. A failed run
+// keeps its error instead of aborting the remaining ones.
+fn collect_runs(image: &[u8], args: &Args) -> Vec<(Job, Vec<Result<CircuitTimings, ProofError>>)> {
+    let jobs = jobs(args);
+    let runs = |job: Job| -> Vec<_> {
+        // a failed warm-up shows up again in the timed runs
+        let _ = warm_up(job, image, args);
+        (0..args.iters)
+            .map(|_| run_algo(job, image, args, false))
+            .collect()
+    };
+    let results: Vec<_> = if args.parallel {
+        jobs.par_iter().map(|&job| runs(job)).collect()
+    } else {
+        jobs.iter().map(|&job| runs(job)).collect()
+    };
+    jobs.into_iter().zip(results).collect()
+}
+
+// The `CircuitTimings` fields as CSV columns, in declaration order. serde_json sorts
+// object keys without its `preserve_order` feature, so the order is spelled out here.
+const TIMING_COLUMNS: [&str; 10] = [
+    "setup_ms",
+    "prove_ms",
+    "verify_ms",
+    "constraint_check_ms",
+    "proof_bytes",
+    "n_constraints",
+    "n_witness",
+    "n_public",
+    "peak_rss_bytes",
+    "log_inv_rate",
+];
+
+// Quote a CSV field if it needs it, doubling embedded quotes.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Write the `--format json` or `csv` report for all images to stdout or `--out`: one
+// JSON object per line and run, or a header row and one CSV row per run. Numbers are
+// written plainly, without thousands separators. Returns whether every run succeeded.
+fn run_report(images: &[Vec<u8>], args: &Args) -> std::io::Result<bool> {
+    let mut out: Box<dyn Write> = match &args.out {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(std::io::stdout().lock()),
+    };
+
+    if args.format == Format::Csv {
+        debug_assert_eq!(
+            serde_json::to_value(CircuitTimings::default())
+                .ok()
+                .and_then(|v| v.as_object().map(|fields| fields.len())),
+            Some(TIMING_COLUMNS.len()),
+            "every `CircuitTimings` field has a CSV column"
+        );
+        writeln!(
+            out,
+            "algo,input_size,iter,ok,error,throughput_mbps,{}",
+            TIMING_COLUMNS.join(",")
+        )?;
+    }
+
+    let mut all_ok = true;
+    for image in images {
+        for (Job { algo, .. }, runs) in collect_runs(image, args) {
+            let input_size = algo_image(algo, image).len();
+            for (iter, result) in runs.into_iter().enumerate() {
+                all_ok &= result.is_ok();
+                let (timings, error) = match result {
+                    Ok(timings) => (timings, None),
+                    Err(e) => (CircuitTimings::default(), Some(e.to_string())),
+                };
+                let throughput_mbps = timings.throughput_mbps(input_size);
+
+                if args.format == Format::Csv {
+                    let values = serde_json::to_value(timings).expect("run report serializes");
+                    let values = TIMING_COLUMNS.iter().map(|&c| match &values[c] {
+                        serde_json::Value::Null => String::new(),
+                        v => v.to_string(),
+                    });
+                    let row: Vec<_> = [
+                        algo.name().to_string(),
+                        input_size.to_string(),
+                        iter.to_string(),
+                        error.is_none().to_string(),
+                        csv_field(error.as_deref().unwrap_or("")),
+                        throughput_mbps.map_or(String::new(), |t| t.to_string()),
+                    ]
+                    .into_iter()
+                    .chain(values)
+                    .collect();
+                    writeln!(out, "{}", row.join(","))?;
+                } else {
+                    let line = JsonRun {
+                        algo: algo.name(),
+                        input_size,
+                        ok: error.is_none(),
+                        throughput_mbps,
+                        error,
+                        timings,
+                    };
+                    writeln!(
+                        out,
+                        "{}",
+                        serde_json::to_string(&line).expect("run report serializes")
+                    )?;
+                }
+            }
+        }
+    }
+    out.flush()?;
+    Ok(all_ok)
+}
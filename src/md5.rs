@@ -0,0 +1,201 @@
+//! MD5, for proving legacy file checksums.
+//!
+//! **MD5 is broken.** Collisions can be computed in seconds and chosen-prefix collisions
+//! are practical, so a proof that some preimage hashes to an MD5 digest says nothing
+//! about *which* preimage it is once an attacker had a say in the data. Use this only to
+//! stay compatible with existing MD5 checksums, e.g. when migrating data whose integrity
+//! was recorded that way, never for new commitments. It sits behind the `legacy` feature,
+//! which is not enabled by default.
+//!
+//! `binius_circuits` has no MD5 gadget, so the compression function is built here from
+//! the 32-bit builder primitives, as for RIPEMD-160, whose padding it shares. The message
+//! length is fixed when the circuit is built. The 16-byte digest is exposed as 2 inout
+//! words, each the little-endian packing of 8 digest bytes.
+
+use binius_core::word::Word;
+use binius_frontend::{CircuitBuilder, Wire, WitnessFiller};
+use md5::{Digest, Md5};
+
+use crate::config::RunConfig;
+use crate::error::ProofError;
+use crate::hash_circuit::{
+    HashCircuit, WordEndian, check_exact_len, public_digest, run_hash_circuit_with_config,
+};
+use crate::timings::CircuitTimings;
+
+const IV: [u32; 4] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476];
+
+// floor(|sin(i + 1)| * 2^32)
+const K: [u32; 64] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, 0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+// left rotation amounts, four per round
+const S: [[u32; 4]; 4] = [
+    [7, 12, 17, 22],
+    [5, 9, 14, 20],
+    [4, 11, 16, 23],
+    [6, 10, 15, 21],
+];
+
+const BLOCK_LEN: usize = 64;
+const DIGEST_WORDS: usize = 2;
+
+fn rotl_32(b: &CircuitBuilder, x: Wire, n: u32) -> Wire {
+    b.rotr_32(x, 32 - n)
+}
+
+// 32-bit complement, the high half of the word is left zero
+fn not_32(b: &CircuitBuilder, x: Wire) -> Wire {
+    b.bxor(x, b.add_constant_64(0xffff_ffff))
+}
+
+fn or(b: &CircuitBuilder, x: Wire, y: Wire) -> Wire {
+    b.bxor(b.bxor(x, y), b.band(x, y))
+}
+
+// Boolean function and message word index of step `i`.
+fn f(b: &CircuitBuilder, i: usize, x: Wire, y: Wire, z: Wire) -> (Wire, usize) {
+    match i / 16 {
+        // (x & y) | (!x & z), the two terms never overlap
+        0 => (b.bxor(z, b.band(x, b.bxor(y, z))), i),
+        // (x & z) | (y & !z)
+        1 => (b.bxor(y, b.band(z, b.bxor(x, y))), (5 * i + 1) % 16),
+        2 => (b.bxor(b.bxor(x, y), z), (3 * i + 5) % 16),
+        _ => (b.bxor(y, or(b, x, not_32(b, z))), (7 * i) % 16),
+    }
+}
+
+// MD5 compression of one 16-word block into the state `h`.
+fn compress(b: &CircuitBuilder, h: &mut [Wire; 4], block: &[Wire]) {
+    let [mut a, mut bb, mut c, mut d] = *h;
+
+    for i in 0..64 {
+        let (fx, g) = f(b, i, bb, c, d);
+        let t = b.iadd_32(b.iadd_32(a, fx), block[g]);
+        let t = b.iadd_32(t, b.add_constant_64(K[i] as u64));
+        (a, d, c, bb) = (d, c, bb, b.iadd_32(bb, rotl_32(b, t, S[i / 16][i % 4])));
+    }
+
+    for (h, x) in h.iter_mut().zip([a, bb, c, d]) {
+        *h = b.iadd_32(*h, x);
+    }
+}
+
+// MD5 state after hashing `len` bytes held in `words`, one little-endian 32-bit word per
+// wire. The padding (0x80, zeros, 64-bit little-endian bit length) is the same as
+// RIPEMD-160's, fixed by `len` and added as constants.
+fn md5(b: &CircuitBuilder, words: &[Wire], len: usize) -> [Wire; 4] {
+    let padded_len = (len + 8) / BLOCK_LEN * BLOCK_LEN + BLOCK_LEN;
+    let mut pad_bytes = vec![0u8; padded_len - 4 * (len / 4)];
+    pad_bytes[len % 4] = 0x80;
+    let n = pad_bytes.len();
+    pad_bytes[n - 8..].copy_from_slice(&(8 * len as u64).to_le_bytes());
+
+    let padded: Vec<Wire> = (0..padded_len / 4)
+        .map(|k| {
+            let pad_word = if 4 * k + 4 > len {
+                let off = 4 * k - 4 * (len / 4);
+                u32::from_le_bytes(pad_bytes[off..off + 4].try_into().unwrap()) as u64
+            } else {
+                0
+            };
+            match words.get(k) {
+                // only the first len % 4 bytes of a partial last word are message
+                Some(&w) if 4 * k + 4 > len => {
+                    let mask = b.add_constant_64((1 << (8 * (len % 4))) - 1);
+                    b.bxor(b.band(w, mask), b.add_constant_64(pad_word))
+                }
+                Some(&w) => w,
+                None => b.add_constant_64(pad_word),
+            }
+        })
+        .collect();
+
+    let mut h: [Wire; 4] = core::array::from_fn(|i| b.add_constant_64(IV[i] as u64));
+    for block in padded.chunks(BLOCK_LEN / 4) {
+        compress(b, &mut h, block);
+    }
+    h
+}
+
+/// MD5 over a message whose length is fixed when the circuit is built. See the module
+/// docs for why this is for legacy checksums only.
+///
+/// Message wires hold one little-endian 32-bit word each.
+pub struct Md5Hash {
+    len: usize,
+    message: Vec<Wire>,
+    digest: [Wire; DIGEST_WORDS],
+}
+
+impl HashCircuit for Md5Hash {
+    const NAME: &'static str = "md5";
+
+    fn build(builder: &CircuitBuilder, max_len: usize) -> Self {
+        let message: Vec<_> = (0..max_len.div_ceil(4))
+            .map(|_| builder.add_witness())
+            .collect();
+        let digest: [_; DIGEST_WORDS] = core::array::from_fn(|_| builder.add_inout());
+
+        // pack two 32-bit state words into each 64-bit commitment word
+        let h = md5(builder, &message, max_len);
+        let mask = builder.add_constant_64(0xffff_ffff);
+        for (i, &d) in digest.iter().enumerate() {
+            let lo = builder.band(h[2 * i], mask);
+            let hi = builder.shl(builder.band(h[2 * i + 1], mask), 32);
+            builder.assert_eq(format!("md5 digest[{i}]"), builder.bxor(lo, hi), d);
+        }
+
+        Self {
+            len: max_len,
+            message,
+            digest,
+        }
+    }
+
+    fn max_len(&self) -> usize {
+        self.len
+    }
+
+    // the message length is fixed when the circuit is built
+    fn check_len(&self, msg: &[u8]) -> Result<(), ProofError> {
+        check_exact_len(msg, self.len)
+    }
+
+    fn populate_with_digest(&self, witness: &mut WitnessFiller, msg: &[u8], digest: &[u8]) {
+        debug_assert!(msg.len() <= self.len);
+        for (i, &w) in self.message.iter().enumerate() {
+            let mut word = [0u8; 4];
+            for (k, byte) in word.iter_mut().enumerate() {
+                *byte = msg.get(4 * i + k).copied().unwrap_or(0);
+            }
+            witness[w] = Word(u32::from_le_bytes(word) as u64);
+        }
+        for (i, &w) in self.digest.iter().enumerate() {
+            let bytes: [u8; 8] = digest[8 * i..8 * i + 8].try_into().unwrap();
+            witness[w] = Word(u64::from_le_bytes(bytes));
+        }
+    }
+
+    fn digest_from_public(&self, public: &[Word]) -> Vec<u8> {
+        public_digest(public, 16, WordEndian::Little)
+    }
+
+    fn reference_digest(msg: &[u8]) -> Vec<u8> {
+        Md5::digest(msg).to_vec()
+    }
+}
+
+/// Prove MD5(m) = h for `image_bytes`, with the 16-byte digest as the public output. Only
+/// for compatibility with existing MD5 checksums, see the module docs.
+pub fn md5_circuit(image_bytes: &[u8], config: &RunConfig) -> Result<CircuitTimings, ProofError> {
+    run_hash_circuit_with_config(image_bytes, config, Md5Hash::build)
+}
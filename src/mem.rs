@@ -0,0 +1,20 @@
+//! Peak memory reporting for the `--mem` option.
+
+/// Peak resident set size of the whole process so far, in bytes.
+///
+/// Read from `VmHWM` in `/proc/self/status`, so it is only available on Linux and
+/// `None` everywhere else. The value is a high-water mark: it never goes down, so after
+/// several circuits it reports the largest one so far rather than the last.
+#[cfg(target_os = "linux")]
+pub fn peak_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+    // "VmHWM:    123456 kB"
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn peak_rss_bytes() -> Option<u64> {
+    None
+}
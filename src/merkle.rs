@@ -0,0 +1,523 @@
+//! Merkle root of Keccak-256 leaf hashes, proven in one circuit.
+//!
+//! Each leaf is hashed with the upstream keccak-256 gadget as `L(x) = keccak256(0x00 || x)`
+//! and every inner node as `N(l, r) = keccak256(0x01 || l || r)` over the two 32-byte
+//! child digests. The prefixes separate the two domains as in RFC 6962: without them a
+//! 64-byte leaf equal to two child digests would hash to the same value as their inner
+//! node, a second preimage for the root. When a level has an odd number of nodes the last
+//! one is paired with itself (the Bitcoin convention), so `[a, b, c]` has root
+//! `N(N(L(a), L(b)), N(L(c), L(c)))`. A single leaf's hash is the root. Only the root is
+//! public, the leaves and inner nodes stay in the witness.
+//!
+//! `merkle_inclusion_circuit` proves membership of a single leaf instead, from its
+//! authentication path, with the same node hashing.
+
+use binius_circuits::keccak::Keccak256;
+use binius_core::word::Word;
+use binius_frontend::{Circuit, CircuitBuilder, Wire, WitnessFiller};
+use sha3::{Digest, Keccak256 as CpuKeccak256};
+
+use crate::config::RunConfig;
+use crate::error::ProofError;
+use crate::hash_circuit::{WordEndian, digest_to_words, prove_and_verify_circuit};
+use crate::timings::CircuitTimings;
+
+const ROOT_NAME: &str = "keccak-merkle-root";
+const INCLUSION_NAME: &str = "keccak-merkle-inclusion";
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+fn keccak(bytes: &[u8]) -> [u8; 32] {
+    CpuKeccak256::digest(bytes).into()
+}
+
+fn leaf_message(leaf: &[u8]) -> Vec<u8> {
+    [&[LEAF_PREFIX][..], leaf].concat()
+}
+
+fn node_message(left: [u8; 32], right: [u8; 32]) -> Vec<u8> {
+    [&[NODE_PREFIX][..], &left, &right].concat()
+}
+
+// The little-endian message words of `prefix || m`, where `words` hold the `len`-byte
+// message `m` packed 8 bytes per word, little-endian as the keccak gadget reads them.
+// Every byte moves up one position, so the prefix cannot be chosen by the prover.
+fn prefixed(b: &CircuitBuilder, prefix: u8, words: &[Wire], len: usize) -> Vec<Wire> {
+    (0..(len + 1).div_ceil(8))
+        .map(|k| {
+            let low = match k {
+                0 => b.add_constant_64(prefix as u64),
+                _ => b.shr(words[k - 1], 56),
+            };
+            match words.get(k) {
+                Some(&w) => b.bxor(low, b.shl(w, 8)),
+                None => low,
+            }
+        })
+        .collect()
+}
+
+// The keccak gadget hashing `prefix || m` for the message words `words` of `m`.
+fn keccak_prefixed(
+    b: &CircuitBuilder,
+    prefix: u8,
+    words: &[Wire],
+    len: usize,
+    digest: [Wire; 4],
+) -> Keccak256 {
+    let len_bytes = b.add_constant_64((len + 1) as u64);
+    Keccak256::new(b, len_bytes, digest, prefixed(b, prefix, words, len))
+}
+
+/// All levels of the tree over `leaves`, from the leaf hashes up to the root.
+pub fn reference_levels(leaves: &[Vec<u8>]) -> Vec<Vec<[u8; 32]>> {
+    let mut levels = vec![
+        leaves
+            .iter()
+            .map(|leaf| keccak(&leaf_message(leaf)))
+            .collect::<Vec<_>>(),
+    ];
+    while levels.last().unwrap().len() > 1 {
+        let next = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| {
+                let (left, right) = (pair[0], *pair.last().unwrap());
+                keccak(&node_message(left, right))
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+/// Merkle root of `leaves` computed in plain Rust, `leaves` must not be empty.
+pub fn reference_root(leaves: &[Vec<u8>]) -> [u8; 32] {
+    reference_levels(leaves).last().unwrap()[0]
+}
+
+/// The gadgets of a Merkle tree over leaves of fixed lengths.
+pub struct KeccakMerkle {
+    // leaf gadgets with the wires of each leaf's message
+    leaves: Vec<(Keccak256, Vec<Wire>)>,
+    // inner node gadgets, bottom level first
+    levels: Vec<Vec<Keccak256>>,
+}
+
+impl KeccakMerkle {
+    /// Add the tree for leaves of `lens` bytes, `lens` must not be empty.
+    pub fn new(builder: &CircuitBuilder, lens: &[usize]) -> Self {
+        // the node that ends up as the root gets inout digest wires, all others are private
+        let digest = |is_root: bool| -> [Wire; 4] {
+            core::array::from_fn(|_| {
+                if is_root {
+                    builder.add_inout()
+                } else {
+                    builder.add_witness()
+                }
+            })
+        };
+
+        let mut level = Vec::with_capacity(lens.len());
+        let leaves = lens
+            .iter()
+            .map(|&len| {
+                let message: Vec<_> = (0..len.div_ceil(8))
+                    .map(|_| builder.add_witness())
+                    .collect();
+                let d = digest(lens.len() == 1);
+                level.push(d);
+                let keccak = keccak_prefixed(builder, LEAF_PREFIX, &message, len, d);
+                (keccak, message)
+            })
+            .collect();
+
+        let mut levels = Vec::new();
+        while level.len() > 1 {
+            let is_root = level.len() <= 2;
+            let (nodes, digests): (Vec<_>, Vec<_>) = level
+                .chunks(2)
+                .map(|pair| {
+                    let (left, right) = (pair[0], *pair.last().unwrap());
+                    let message: Vec<_> = left.iter().chain(&right).copied().collect();
+                    let d = digest(is_root);
+                    (keccak_prefixed(builder, NODE_PREFIX, &message, 64, d), d)
+                })
+                .unzip();
+            levels.push(nodes);
+            level = digests;
+        }
+
+        Self { leaves, levels }
+    }
+
+    /// Populate every leaf and inner node for `leaves`, with the lengths the tree was
+    /// built for.
+    pub fn populate(&self, witness: &mut WitnessFiller, leaves: &[Vec<u8>]) {
+        debug_assert_eq!(leaves.len(), self.leaves.len());
+        let levels = reference_levels(leaves);
+
+        for (((keccak, message), leaf), &digest) in self.leaves.iter().zip(leaves).zip(&levels[0]) {
+            for (&w, v) in message
+                .iter()
+                .zip(digest_to_words(leaf, WordEndian::Little))
+            {
+                witness[w] = v;
+            }
+            keccak.populate_message(witness, &leaf_message(leaf));
+            keccak.populate_digest(witness, digest);
+        }
+
+        for (nodes, (children, digests)) in self.levels.iter().zip(levels.iter().zip(&levels[1..]))
+        {
+            for ((keccak, pair), &digest) in nodes.iter().zip(children.chunks(2)).zip(digests) {
+                let (left, right) = (pair[0], *pair.last().unwrap());
+                keccak.populate_message(witness, &node_message(left, right));
+                keccak.populate_digest(witness, digest);
+            }
+        }
+    }
+}
+
+fn build_tree(lens: &[usize]) -> (Circuit, KeccakMerkle) {
+    let builder = CircuitBuilder::new();
+    let tree = KeccakMerkle::new(&builder, lens);
+    (builder.build(), tree)
+}
+
+/// Prove that the public root is the Merkle root of `leaves`.
+///
+/// The witness is populated from `reference_root` and its inner nodes, so the root the
+/// circuit computes is checked against the plain Rust one by the local constraint check
+/// before anything is proven.
+pub fn merkle_root_circuit(
+    leaves: &[Vec<u8>],
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    if leaves.is_empty() {
+        return Err(ProofError::InvalidParameter(
+            "a merkle tree needs at least one leaf".into(),
+        ));
+    }
+
+    if config.print_progress() {
+        println!(
+            "Proof for keccak merkle root {} of {} leaves: ",
+            hex::encode(reference_root(leaves)),
+            leaves.len()
+        );
+    }
+
+    let lens: Vec<_> = leaves.iter().map(Vec::len).collect();
+    let (circuit, tree) = build_tree(&lens);
+
+    let mut witness = circuit.new_witness_filler();
+    tree.populate(&mut witness, leaves);
+    circuit
+        .populate_wire_witness(&mut witness)
+        .map_err(|e| ProofError::from(e).in_circuit(ROOT_NAME, leaves.len()))?;
+
+    prove_and_verify_circuit(&circuit, witness.into_value_vec(), config)
+        .map_err(|e| e.in_circuit(ROOT_NAME, leaves.len()))
+}
+
+/// Root reached from `leaf` at position `index` by hashing up the authentication path
+/// `siblings`, bottom level first.
+pub fn reference_inclusion_root(leaf: &[u8], siblings: &[[u8; 32]], index: usize) -> [u8; 32] {
+    siblings
+        .iter()
+        .enumerate()
+        .fold(keccak(&leaf_message(leaf)), |node, (level, sibling)| {
+            if (index >> level) & 1 == 1 {
+                keccak(&node_message(*sibling, node))
+            } else {
+                keccak(&node_message(node, *sibling))
+            }
+        })
+}
+
+/// An authentication path from a leaf of fixed length up to the root.
+///
+/// The leaf, its position and the sibling digests are private, only the root is public.
+/// Bit `k` of the index says whether the node at level `k` is the right child, in which
+/// case it is hashed as `N(sibling, node)` instead of `N(node, sibling)`.
+pub struct KeccakInclusion {
+    leaf: Keccak256,
+    message: Vec<Wire>,
+    index: Wire,
+    siblings: Vec<[Wire; 4]>,
+    nodes: Vec<Keccak256>,
+}
+
+impl KeccakInclusion {
+    pub fn new(builder: &CircuitBuilder, leaf_len: usize, depth: usize) -> Self {
+        let digest = |is_root: bool| -> [Wire; 4] {
+            core::array::from_fn(|_| {
+                if is_root {
+                    builder.add_inout()
+                } else {
+                    builder.add_witness()
+                }
+            })
+        };
+
+        let message: Vec<_> = (0..leaf_len.div_ceil(8))
+            .map(|_| builder.add_witness())
+            .collect();
+        let mut node = digest(depth == 0);
+        let leaf = keccak_prefixed(builder, LEAF_PREFIX, &message, leaf_len, node);
+
+        let index = builder.add_witness();
+        let siblings: Vec<[Wire; 4]> = (0..depth).map(|_| digest(false)).collect();
+
+        let nodes = siblings
+            .iter()
+            .enumerate()
+            .map(|(level, sibling)| {
+                // select picks its first operand when the MSB of the condition is set,
+                // so rotate index bit `level` into the MSB
+                let is_right = builder.rotl(index, 63 - level as u32);
+                let left = (0..4).map(|i| builder.select(is_right, sibling[i], node[i]));
+                let right = (0..4).map(|i| builder.select(is_right, node[i], sibling[i]));
+                let message: Vec<_> = left.chain(right).collect();
+
+                node = digest(level == depth - 1);
+                keccak_prefixed(builder, NODE_PREFIX, &message, 64, node)
+            })
+            .collect();
+
+        Self {
+            leaf,
+            message,
+            index,
+            siblings,
+            nodes,
+        }
+    }
+
+    pub fn populate(
+        &self,
+        witness: &mut WitnessFiller,
+        leaf: &[u8],
+        siblings: &[[u8; 32]],
+        index: usize,
+    ) {
+        debug_assert_eq!(siblings.len(), self.siblings.len());
+
+        let mut node = keccak(&leaf_message(leaf));
+        for (&w, v) in self
+            .message
+            .iter()
+            .zip(digest_to_words(leaf, WordEndian::Little))
+        {
+            witness[w] = v;
+        }
+        self.leaf.populate_message(witness, &leaf_message(leaf));
+        self.leaf.populate_digest(witness, node);
+
+        witness[self.index] = Word(index as u64);
+        for (level, ((wires, sibling), keccak_node)) in self
+            .siblings
+            .iter()
+            .zip(siblings)
+            .zip(&self.nodes)
+            .enumerate()
+        {
+            for (i, &w) in wires.iter().enumerate() {
+                let bytes: [u8; 8] = sibling[8 * i..8 * i + 8].try_into().unwrap();
+                witness[w] = Word(u64::from_le_bytes(bytes));
+            }
+
+            let message = if (index >> level) & 1 == 1 {
+                node_message(*sibling, node)
+            } else {
+                node_message(node, *sibling)
+            };
+            node = keccak(&message);
+            keccak_node.populate_message(witness, &message);
+            keccak_node.populate_digest(witness, node);
+        }
+    }
+}
+
+/// Prove that `leaf` at position `index` hashes up to the public root along the
+/// authentication path `siblings`, bottom level first.
+pub fn merkle_inclusion_circuit(
+    leaf: &[u8],
+    siblings: &[[u8; 32]],
+    index: usize,
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    // the index is a single 64-bit wire, one bit per level
+    if siblings.len() > 64 {
+        return Err(ProofError::InvalidParameter(format!(
+            "merkle paths are limited to 64 levels, got {}",
+            siblings.len()
+        )));
+    }
+    if siblings.len() < usize::BITS as usize && index >> siblings.len() != 0 {
+        return Err(ProofError::InvalidParameter(format!(
+            "index {index} does not fit a path of {} levels",
+            siblings.len()
+        )));
+    }
+
+    if config.print_progress() {
+        println!(
+            "Proof for keccak merkle inclusion under root {} at depth {}: ",
+            hex::encode(reference_inclusion_root(leaf, siblings, index)),
+            siblings.len()
+        );
+    }
+
+    let builder = CircuitBuilder::new();
+    let path = KeccakInclusion::new(&builder, leaf.len(), siblings.len());
+    let circuit = builder.build();
+
+    let mut witness = circuit.new_witness_filler();
+    path.populate(&mut witness, leaf, siblings, index);
+    circuit
+        .populate_wire_witness(&mut witness)
+        .map_err(|e| ProofError::from(e).in_circuit(INCLUSION_NAME, leaf.len()))?;
+
+    prove_and_verify_circuit(&circuit, witness.into_value_vec(), config)
+        .map_err(|e| e.in_circuit(INCLUSION_NAME, leaf.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use binius_core::verify::verify_constraints;
+
+    fn leaves(n: usize) -> Vec<Vec<u8>> {
+        // lengths across the 8-byte message words, including an empty leaf
+        (0..n)
+            .map(|i| (0..(i * 7) as u8).map(|b| b ^ i as u8).collect())
+            .collect()
+    }
+
+    #[test]
+    fn root_matches_reference() {
+        // 3 and 5 leaves pair an odd last node with itself
+        for n in [1, 2, 3, 5] {
+            let leaves = leaves(n);
+            let lens: Vec<_> = leaves.iter().map(Vec::len).collect();
+            let (circuit, tree) = build_tree(&lens);
+
+            let mut witness = circuit.new_witness_filler();
+            tree.populate(&mut witness, &leaves);
+            circuit
+                .populate_wire_witness(&mut witness)
+                .unwrap_or_else(|e| panic!("{n} leaves: {e:?}"));
+            let witness = witness.into_value_vec();
+            verify_constraints(circuit.constraint_system(), &witness)
+                .unwrap_or_else(|e| panic!("{n} leaves: {e:?}"));
+
+            let public = witness.public();
+            assert_eq!(
+                public[public.len() - 4..],
+                digest_to_words(&reference_root(&leaves), WordEndian::Little),
+                "{n} leaves"
+            );
+        }
+    }
+
+    #[test]
+    fn odd_node_is_paired_with_itself() {
+        let leaves = leaves(3);
+        let [a, b, c] = reference_levels(&leaves)[0][..] else {
+            unreachable!()
+        };
+        let root = keccak(&node_message(
+            keccak(&node_message(a, b)),
+            keccak(&node_message(c, c)),
+        ));
+        assert_eq!(reference_root(&leaves), root);
+    }
+
+    // The authentication path of leaf `index` in the tree with `levels`.
+    fn path_of(levels: &[Vec<[u8; 32]>], index: usize) -> Vec<[u8; 32]> {
+        levels[..levels.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(level, nodes)| {
+                let i = index >> level;
+                nodes.get(i ^ 1).copied().unwrap_or(nodes[i])
+            })
+            .collect()
+    }
+
+    // Whether the inclusion circuit accepts `root` for `leaf` at `index` along `siblings`.
+    fn inclusion_accepts(leaf: &[u8], siblings: &[[u8; 32]], index: usize, root: [u8; 32]) -> bool {
+        let builder = CircuitBuilder::new();
+        let path = KeccakInclusion::new(&builder, leaf.len(), siblings.len());
+        let circuit = builder.build();
+
+        let mut witness = circuit.new_witness_filler();
+        path.populate(&mut witness, leaf, siblings, index);
+        path.nodes
+            .last()
+            .unwrap_or(&path.leaf)
+            .populate_digest(&mut witness, root);
+        circuit.populate_wire_witness(&mut witness).is_ok()
+            && verify_constraints(circuit.constraint_system(), &witness.into_value_vec()).is_ok()
+    }
+
+    // every leaf of a full tree of depth 3 takes each side at some level
+    #[test]
+    fn inclusion_accepts_every_position() {
+        let leaves = leaves(8);
+        let levels = reference_levels(&leaves);
+        let root = reference_root(&leaves);
+        for (index, leaf) in leaves.iter().enumerate() {
+            let siblings = path_of(&levels, index);
+            assert_eq!(siblings.len(), 3);
+            assert_eq!(reference_inclusion_root(leaf, &siblings, index), root);
+            assert!(
+                inclusion_accepts(leaf, &siblings, index, root),
+                "index {index}"
+            );
+        }
+    }
+
+    // with no siblings the root is the leaf hash
+    #[test]
+    fn inclusion_at_depth_zero() {
+        let leaf = b"lonely leaf";
+        let root = reference_root(&[leaf.to_vec()]);
+        assert_eq!(reference_inclusion_root(leaf, &[], 0), root);
+        assert!(inclusion_accepts(leaf, &[], 0, root));
+    }
+
+    #[test]
+    fn inclusion_rejects_wrong_root() {
+        let leaves = leaves(4);
+        let siblings = path_of(&reference_levels(&leaves), 2);
+        let mut root = reference_root(&leaves);
+        root[0] ^= 0xff;
+        assert!(!inclusion_accepts(&leaves[2], &siblings, 2, root));
+    }
+
+    #[test]
+    fn inclusion_rejects_out_of_range_index() {
+        let leaves = leaves(4);
+        let siblings = path_of(&reference_levels(&leaves), 0);
+        for index in [4, usize::MAX] {
+            assert!(matches!(
+                merkle_inclusion_circuit(&leaves[0], &siblings, index, &RunConfig::default()),
+                Err(ProofError::InvalidParameter(_))
+            ));
+        }
+    }
+
+    // a leaf made of two child digests does not hash to their inner node
+    #[test]
+    fn leaf_cannot_pass_for_inner_node() {
+        let leaves = leaves(2);
+        let level = &reference_levels(&leaves)[0];
+        let forged = vec![[level[0], level[1]].concat()];
+        assert_ne!(reference_root(&forged), reference_root(&leaves));
+    }
+}
@@ -0,0 +1,98 @@
+//! SHA-256 and Keccak-256 of the same preimage in one proof.
+//!
+//! Both upstream gadgets read the message as little-endian 64-bit words, so a single set
+//! of message wires and a single length wire feed both of them, and both digests are
+//! public. Compared to proving the two hashes separately this saves the second copy of
+//! the message witness (one word per 8 bytes) and the second proof, but not constraints:
+//! the constraint count is the sum of the two gadgets', which `--check-only` on the
+//! `sha256` and `keccak` algos shows for a given size.
+
+use binius_circuits::{keccak::Keccak256, sha256::Sha256};
+use binius_core::word::Word;
+use binius_frontend::{CircuitBuilder, Wire, WitnessFiller};
+use sha2::{Digest, Sha256 as StdSha256};
+use sha3::Keccak256 as CpuKeccak256;
+
+use crate::config::RunConfig;
+use crate::error::ProofError;
+use crate::hash_circuit::{
+    HashCircuit, WordEndian, digest_words, run_hash_circuit_with_config, words_to_digest,
+};
+use crate::timings::CircuitTimings;
+
+/// A sha256 and a keccak-256 gadget over shared message wires. The digest is the
+/// 32-byte sha256 digest followed by the 32-byte keccak-256 digest.
+pub struct Sha256KeccakHash {
+    sha256: Sha256,
+    keccak: Keccak256,
+    len_bytes: Wire,
+    max_len: usize,
+}
+
+impl HashCircuit for Sha256KeccakHash {
+    const NAME: &'static str = "sha256+keccak";
+
+    fn build(builder: &CircuitBuilder, max_len: usize) -> Self {
+        // at least one message wire, as for `Sha256Hash`
+        let message: Vec<_> = (0..max_len.div_ceil(8).max(1))
+            .map(|_| builder.add_witness())
+            .collect();
+        let sha256_digest: [_; 4] = core::array::from_fn(|_| builder.add_inout());
+        let keccak_digest: [_; 4] = core::array::from_fn(|_| builder.add_inout());
+
+        let len_bytes = builder.add_witness();
+        let sha256 = Sha256::new(builder, len_bytes, sha256_digest, message.clone());
+        let keccak = Keccak256::new(builder, len_bytes, keccak_digest, message);
+
+        Self {
+            sha256,
+            keccak,
+            len_bytes,
+            max_len,
+        }
+    }
+
+    fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    fn populate_with_digest(&self, witness: &mut WitnessFiller, msg: &[u8], digest: &[u8]) {
+        witness[self.len_bytes] = Word(msg.len() as u64);
+
+        // both gadgets write the same values to the shared message wires
+        self.sha256.populate_message(witness, msg);
+        self.keccak.populate_message(witness, msg);
+
+        let (sha256_digest, keccak_digest) = digest.split_at(32);
+        self.sha256
+            .populate_digest(witness, sha256_digest.try_into().unwrap());
+        self.keccak
+            .populate_digest(witness, keccak_digest.try_into().unwrap());
+    }
+
+    // the sha256 digest words are big-endian, the keccak-256 ones little-endian
+    fn digest_from_public(&self, public: &[Word]) -> Vec<u8> {
+        let words = digest_words(public, 8).expect("public words hold the digest");
+        [
+            words_to_digest(&words[..4], 32, WordEndian::Big),
+            words_to_digest(&words[4..], 32, WordEndian::Little),
+        ]
+        .concat()
+    }
+
+    fn reference_digest(msg: &[u8]) -> Vec<u8> {
+        [
+            StdSha256::digest(msg).to_vec(),
+            CpuKeccak256::digest(msg).to_vec(),
+        ]
+        .concat()
+    }
+}
+
+/// Prove sha256(m) = h1 and keccak256(m) = h2 for the same `image_bytes` in one proof.
+pub fn multi_hash_circuit(
+    image_bytes: &[u8],
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    run_hash_circuit_with_config(image_bytes, config, Sha256KeccakHash::build)
+}
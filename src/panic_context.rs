@@ -0,0 +1,72 @@
+//! Circuit context for panics inside the prover.
+//!
+//! A panic in the binius prover, e.g. on a malformed witness, only carries binius' own
+//! message and backtrace. `enter` records what is being run for as long as its guard
+//! lives, and the hook `install_hook` adds prints those records before the panic
+//! message. Nothing here is active unless `install_hook` is called, so a library user's
+//! own panic hook is never replaced behind their back; the demo binary installs it.
+//!
+//! The records are process-wide rather than per thread: the prover runs on rayon's
+//! worker threads, so the panicking thread is usually not the one that entered the
+//! context. With several circuits in flight, e.g. `--parallel`, all of them are listed.
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static ACTIVE: Mutex<Vec<(u64, String)>> = Mutex::new(Vec::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Removes its record when dropped, see `enter`.
+#[must_use = "the context is removed as soon as the guard is dropped"]
+pub struct ContextGuard(u64);
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        let mut active = ACTIVE.lock().unwrap_or_else(|e| e.into_inner());
+        active.retain(|(id, _)| *id != self.0);
+    }
+}
+
+/// Record `label`, e.g. the algorithm and input size, until the guard is dropped.
+pub fn enter(label: impl Into<String>) -> ContextGuard {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    ACTIVE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push((id, label.into()));
+    ContextGuard(id)
+}
+
+fn active() -> Vec<String> {
+    // `try_lock` in case the panic happened while the list was locked
+    match ACTIVE.try_lock() {
+        Ok(active) => active.iter().map(|(_, label)| label.clone()).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Install a panic hook that prints the entered contexts to stderr, then runs the hook
+/// that was installed before, which prints the panic message as usual.
+pub fn install_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let active = active();
+        if !active.is_empty() {
+            eprintln!("panic while running {}", active.join(", "));
+        }
+        previous(info);
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn context_lives_as_long_as_its_guard() {
+        let guard = enter("sha256 on 64 bytes");
+        assert!(active().contains(&"sha256 on 64 bytes".to_string()));
+        drop(guard);
+        assert!(!active().contains(&"sha256 on 64 bytes".to_string()));
+    }
+}
@@ -0,0 +1,315 @@
+//! RIPEMD-160 and Bitcoin's hash160 = RIPEMD-160(SHA-256(x)).
+//!
+//! `binius_circuits` does not ship a RIPEMD gadget, so the compression function is built
+//! here from the 32-bit builder primitives, as for Blake3. The message length is fixed
+//! when the circuit is built.
+//!
+//! The 20-byte digest does not fill a whole number of 64-bit words, so instead of
+//! packing it into 3 words with a half-empty last one, it is exposed as 5 inout words,
+//! each holding one little-endian 32-bit word of the digest (the RIPEMD-160 state word
+//! h0..h4) in its low half with the high half zero.
+
+use binius_circuits::sha256::Sha256;
+use binius_core::word::Word;
+use binius_frontend::{CircuitBuilder, Wire, WitnessFiller};
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256 as StdSha256};
+
+use crate::config::RunConfig;
+use crate::error::ProofError;
+use crate::hash_circuit::{
+    HashCircuit, check_exact_len, digest_words, run_hash_circuit_with_config,
+};
+use crate::timings::CircuitTimings;
+
+const IV: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+// round constants of the left and right lines, one per group of 16 steps
+const K: [u32; 5] = [0x00000000, 0x5A827999, 0x6ED9EBA1, 0x8F1BBCDC, 0xA953FD4E];
+const K_PRIME: [u32; 5] = [0x50A28BE6, 0x5C4DD124, 0x6D703EF3, 0x7A6D76E9, 0x00000000];
+
+// message word selection
+const R: [usize; 80] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5,
+    2, 14, 11, 8, 3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12, 1, 9, 11, 10, 0, 8, 12, 4,
+    13, 3, 7, 15, 14, 5, 6, 2, 4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+];
+const R_PRIME: [usize; 80] = [
+    5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12, 6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12,
+    4, 9, 1, 2, 15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13, 8, 6, 4, 1, 3, 11, 15, 0, 5,
+    12, 2, 13, 9, 7, 10, 14, 12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+];
+
+// left rotation amounts
+const S: [u32; 80] = [
+    11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8, 7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15,
+    9, 11, 7, 13, 12, 11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5, 11, 12, 14, 15, 14,
+    15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12, 9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+];
+const S_PRIME: [u32; 80] = [
+    8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6, 9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12,
+    7, 6, 15, 13, 11, 9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5, 15, 5, 8, 11, 14, 14,
+    6, 14, 6, 9, 12, 9, 12, 5, 15, 8, 8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+];
+
+const BLOCK_LEN: usize = 64;
+const DIGEST_WORDS: usize = 5;
+
+fn rotl_32(b: &CircuitBuilder, x: Wire, n: u32) -> Wire {
+    b.rotr_32(x, 32 - n)
+}
+
+// 32-bit complement, the high half of the word is left zero
+fn not_32(b: &CircuitBuilder, x: Wire) -> Wire {
+    b.bxor(x, b.add_constant_64(0xffff_ffff))
+}
+
+fn or(b: &CircuitBuilder, x: Wire, y: Wire) -> Wire {
+    b.bxor(b.bxor(x, y), b.band(x, y))
+}
+
+// Boolean function of round `round` (0..5).
+fn f(b: &CircuitBuilder, round: usize, x: Wire, y: Wire, z: Wire) -> Wire {
+    match round {
+        0 => b.bxor(b.bxor(x, y), z),
+        1 => or(b, b.band(x, y), b.band(not_32(b, x), z)),
+        2 => b.bxor(or(b, x, not_32(b, y)), z),
+        3 => or(b, b.band(x, z), b.band(y, not_32(b, z))),
+        _ => b.bxor(x, or(b, y, not_32(b, z))),
+    }
+}
+
+// RIPEMD-160 compression of one 16-word block into the state `h`.
+fn compress(b: &CircuitBuilder, h: &mut [Wire; 5], block: &[Wire]) {
+    let [mut al, mut bl, mut cl, mut dl, mut el] = *h;
+    let [mut ar, mut br, mut cr, mut dr, mut er] = *h;
+
+    for j in 0..80 {
+        let round = j / 16;
+
+        let t = b.iadd_32(b.iadd_32(al, f(b, round, bl, cl, dl)), block[R[j]]);
+        let t = b.iadd_32(t, b.add_constant_64(K[round] as u64));
+        let t = b.iadd_32(rotl_32(b, t, S[j]), el);
+        (al, el, dl, cl, bl) = (el, dl, rotl_32(b, cl, 10), bl, t);
+
+        // the right line runs the boolean functions in reverse order
+        let t = b.iadd_32(
+            b.iadd_32(ar, f(b, 4 - round, br, cr, dr)),
+            block[R_PRIME[j]],
+        );
+        let t = b.iadd_32(t, b.add_constant_64(K_PRIME[round] as u64));
+        let t = b.iadd_32(rotl_32(b, t, S_PRIME[j]), er);
+        (ar, er, dr, cr, br) = (er, dr, rotl_32(b, cr, 10), br, t);
+    }
+
+    let t = b.iadd_32(b.iadd_32(h[1], cl), dr);
+    h[1] = b.iadd_32(b.iadd_32(h[2], dl), er);
+    h[2] = b.iadd_32(b.iadd_32(h[3], el), ar);
+    h[3] = b.iadd_32(b.iadd_32(h[4], al), br);
+    h[4] = b.iadd_32(b.iadd_32(h[0], bl), cr);
+    h[0] = t;
+}
+
+// RIPEMD-160 state after hashing `len` bytes held in `words`, one little-endian 32-bit
+// word per wire. The padding (0x80, zeros, 64-bit little-endian bit length) is fixed by
+// `len` and added as constants.
+fn ripemd160(b: &CircuitBuilder, words: &[Wire], len: usize) -> [Wire; 5] {
+    let padded_len = (len + 8) / BLOCK_LEN * BLOCK_LEN + BLOCK_LEN;
+    let mut pad_bytes = vec![0u8; padded_len - 4 * (len / 4)];
+    pad_bytes[len % 4] = 0x80;
+    let n = pad_bytes.len();
+    pad_bytes[n - 8..].copy_from_slice(&(8 * len as u64).to_le_bytes());
+
+    let padded: Vec<Wire> = (0..padded_len / 4)
+        .map(|k| {
+            let pad_word = if 4 * k + 4 > len {
+                let off = 4 * k - 4 * (len / 4);
+                u32::from_le_bytes(pad_bytes[off..off + 4].try_into().unwrap()) as u64
+            } else {
+                0
+            };
+            match words.get(k) {
+                // only the first len % 4 bytes of a partial last word are message
+                Some(&w) if 4 * k + 4 > len => {
+                    let mask = b.add_constant_64((1 << (8 * (len % 4))) - 1);
+                    b.bxor(b.band(w, mask), b.add_constant_64(pad_word))
+                }
+                Some(&w) => w,
+                None => b.add_constant_64(pad_word),
+            }
+        })
+        .collect();
+
+    let mut h: [Wire; 5] = core::array::from_fn(|i| b.add_constant_64(IV[i] as u64));
+    for block in padded.chunks(BLOCK_LEN / 4) {
+        compress(b, &mut h, block);
+    }
+    h
+}
+
+// Constrain the RIPEMD-160 state `out` to the digest inout words.
+fn assert_digest(b: &CircuitBuilder, out: &[Wire; 5], digest: &[Wire; DIGEST_WORDS]) {
+    let mask = b.add_constant_64(0xffff_ffff);
+    for (i, (&o, &d)) in out.iter().zip(digest).enumerate() {
+        b.assert_eq(format!("ripemd160 digest[{i}]"), b.band(o, mask), d);
+    }
+}
+
+fn populate_digest(witness: &mut WitnessFiller, wires: &[Wire; DIGEST_WORDS], digest: &[u8]) {
+    for (i, &w) in wires.iter().enumerate() {
+        let bytes: [u8; 4] = digest[4 * i..4 * i + 4].try_into().unwrap();
+        witness[w] = Word(u32::from_le_bytes(bytes) as u64);
+    }
+}
+
+// The 20-byte digest from the last public words, one little-endian 32-bit state word
+// in the low half of each.
+fn digest_from_public(public: &[Word]) -> Vec<u8> {
+    digest_words(public, DIGEST_WORDS)
+        .expect("public words hold the digest")
+        .iter()
+        .flat_map(|w| (w.0 as u32).to_le_bytes())
+        .collect()
+}
+
+/// RIPEMD-160 over a message whose length is fixed when the circuit is built.
+///
+/// Message wires hold one little-endian 32-bit word each.
+pub struct Ripemd160Hash {
+    len: usize,
+    message: Vec<Wire>,
+    digest: [Wire; DIGEST_WORDS],
+}
+
+impl HashCircuit for Ripemd160Hash {
+    const NAME: &'static str = "ripemd160";
+
+    fn build(builder: &CircuitBuilder, max_len: usize) -> Self {
+        let message: Vec<_> = (0..max_len.div_ceil(4))
+            .map(|_| builder.add_witness())
+            .collect();
+        let digest: [_; DIGEST_WORDS] = core::array::from_fn(|_| builder.add_inout());
+
+        let out = ripemd160(builder, &message, max_len);
+        assert_digest(builder, &out, &digest);
+
+        Self {
+            len: max_len,
+            message,
+            digest,
+        }
+    }
+
+    fn max_len(&self) -> usize {
+        self.len
+    }
+
+    // the message length is fixed when the circuit is built
+    fn check_len(&self, msg: &[u8]) -> Result<(), ProofError> {
+        check_exact_len(msg, self.len)
+    }
+
+    fn populate_with_digest(&self, witness: &mut WitnessFiller, msg: &[u8], digest: &[u8]) {
+        debug_assert!(msg.len() <= self.len);
+        for (i, &w) in self.message.iter().enumerate() {
+            let mut word = [0u8; 4];
+            for (k, byte) in word.iter_mut().enumerate() {
+                *byte = msg.get(4 * i + k).copied().unwrap_or(0);
+            }
+            witness[w] = Word(u32::from_le_bytes(word) as u64);
+        }
+        populate_digest(witness, &self.digest, digest);
+    }
+
+    fn digest_from_public(&self, public: &[Word]) -> Vec<u8> {
+        digest_from_public(public)
+    }
+
+    fn reference_digest(msg: &[u8]) -> Vec<u8> {
+        Ripemd160::digest(msg).to_vec()
+    }
+}
+
+/// hash160 = RIPEMD-160(SHA-256(x)), the upstream sha256 gadget feeding the RIPEMD-160
+/// gadget in one circuit. Only the 20-byte hash160 is public, the intermediate sha256
+/// digest stays in the witness.
+pub struct Hash160Hash {
+    sha256: Sha256,
+    len_bytes: Wire,
+    max_len: usize,
+    digest: [Wire; DIGEST_WORDS],
+}
+
+impl HashCircuit for Hash160Hash {
+    const NAME: &'static str = "hash160";
+
+    fn build(builder: &CircuitBuilder, max_len: usize) -> Self {
+        // at least one message wire, as for `Sha256Hash`
+        let message: Vec<_> = (0..max_len.div_ceil(8).max(1))
+            .map(|_| builder.add_witness())
+            .collect();
+        let sha_digest: [_; 4] = core::array::from_fn(|_| builder.add_witness());
+        let digest: [_; DIGEST_WORDS] = core::array::from_fn(|_| builder.add_inout());
+
+        let len_bytes = builder.add_witness();
+        let sha256 = Sha256::new(builder, len_bytes, sha_digest, message);
+
+        // The sha256 digest words are big-endian 64-bit, RIPEMD-160 reads little-endian
+        // 32-bit words, so byte m of each digest word is extract_byte(w, 7 - m).
+        let words: Vec<Wire> = sha_digest
+            .iter()
+            .flat_map(|&w| {
+                [0, 4].map(|first| {
+                    (0..4)
+                        .map(|m| builder.shl(builder.extract_byte(w, 7 - first - m), 8 * m))
+                        .reduce(|acc, byte| builder.bxor(acc, byte))
+                        .unwrap()
+                })
+            })
+            .collect();
+
+        let out = ripemd160(builder, &words, 32);
+        assert_digest(builder, &out, &digest);
+
+        Self {
+            sha256,
+            len_bytes,
+            max_len,
+            digest,
+        }
+    }
+
+    fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    fn populate_with_digest(&self, witness: &mut WitnessFiller, msg: &[u8], digest: &[u8]) {
+        witness[self.len_bytes] = Word(msg.len() as u64);
+        self.sha256.populate_message(witness, msg);
+        self.sha256
+            .populate_digest(witness, StdSha256::digest(msg).into());
+        populate_digest(witness, &self.digest, digest);
+    }
+
+    fn digest_from_public(&self, public: &[Word]) -> Vec<u8> {
+        digest_from_public(public)
+    }
+
+    fn reference_digest(msg: &[u8]) -> Vec<u8> {
+        Ripemd160::digest(StdSha256::digest(msg)).to_vec()
+    }
+}
+
+pub fn ripemd160_circuit(
+    image_bytes: &[u8],
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    run_hash_circuit_with_config(image_bytes, config, Ripemd160Hash::build)
+}
+
+pub fn hash160_circuit(
+    image_bytes: &[u8],
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    run_hash_circuit_with_config(image_bytes, config, Hash160Hash::build)
+}
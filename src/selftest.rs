@@ -0,0 +1,253 @@
+// `selftest`: known-answer tests of every compiled-in hash circuit. Each vector's
+// published digest is committed as the public output and the witness is checked
+// against the constraints, which passes exactly when the digest the circuit computes
+// is the published one. Nothing is proven, so this takes seconds.
+
+use sha256_example::ProofError;
+#[cfg(feature = "blake2b")]
+use sha256_example::blake2b::Blake2bHash;
+#[cfg(feature = "blake2s")]
+use sha256_example::blake2s::Blake2sHash;
+#[cfg(feature = "blake3")]
+use sha256_example::blake3::Blake3Hash;
+use sha256_example::hash_circuit::{HashCircuit, check_hash_with_digest};
+#[cfg(feature = "keccak")]
+use sha256_example::keccak::Keccak256Hash;
+#[cfg(feature = "legacy")]
+use sha256_example::md5::Md5Hash;
+#[cfg(all(feature = "sha256", feature = "keccak"))]
+use sha256_example::multi_hash::Sha256KeccakHash;
+#[cfg(feature = "ripemd")]
+use sha256_example::ripemd::{Hash160Hash, Ripemd160Hash};
+#[cfg(feature = "sha2-truncated")]
+use sha256_example::sha2_truncated::{Sha224Hash, Sha384Hash};
+#[cfg(feature = "sha3")]
+use sha256_example::sha3::{Keccak512Hash, Sha3_256Hash};
+#[cfg(feature = "sha256")]
+use sha256_example::sha256::{Sha256Hash, Sha256dHash};
+#[cfg(feature = "sha512")]
+use sha256_example::sha512::Sha512Hash;
+
+use crate::cli::Algo;
+
+// the two-block message of the FIPS 180-2 examples
+const NIST_448: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+// the two-block message of the FIPS 180-2 SHA-384 and SHA-512 examples
+const NIST_896: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+// (algo, message, published digest in hex)
+const VECTORS: &[(Algo, &[u8], &str)] = &[
+    (
+        Algo::Sha256,
+        b"",
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    ),
+    (
+        Algo::Sha256,
+        b"abc",
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+    ),
+    (
+        Algo::Sha256,
+        NIST_448,
+        "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+    ),
+    (
+        Algo::Sha256d,
+        b"",
+        "5df6e0e2761359d30a8275058e299fcc0381534545f55cf43e41983f5d4c9456",
+    ),
+    (
+        Algo::Sha512,
+        b"",
+        "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e",
+    ),
+    (
+        Algo::Sha512,
+        b"abc",
+        "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+    ),
+    (
+        Algo::Sha512,
+        NIST_896,
+        "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909",
+    ),
+    (
+        Algo::Sha224,
+        b"",
+        "d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f",
+    ),
+    (
+        Algo::Sha224,
+        b"abc",
+        "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7",
+    ),
+    (
+        Algo::Sha384,
+        b"",
+        "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da274edebfe76f65fbd51ad2f14898b95b",
+    ),
+    (
+        Algo::Sha384,
+        b"abc",
+        "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7",
+    ),
+    (
+        Algo::Keccak,
+        b"",
+        "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+    ),
+    (
+        Algo::Keccak,
+        b"abc",
+        "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45",
+    ),
+    (
+        Algo::Sha256Keccak,
+        b"abc",
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45",
+    ),
+    (
+        Algo::Sha3_256,
+        b"",
+        "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+    ),
+    (
+        Algo::Sha3_256,
+        b"abc",
+        "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+    ),
+    (
+        Algo::Keccak512,
+        b"",
+        "0eab42de4c3ceb9235fc91acffe746b29c29a8c366b7c60e4e67c466f36a4304c00fa9caf9d87976ba469bcbe06713b435f091ef2769fb160cdab33d3670680e",
+    ),
+    (
+        Algo::Blake2b,
+        b"",
+        "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce",
+    ),
+    // RFC 7693 appendix A
+    (
+        Algo::Blake2b,
+        b"abc",
+        "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+    ),
+    (
+        Algo::Blake2s,
+        b"",
+        "69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9",
+    ),
+    // RFC 7693 appendix B
+    (
+        Algo::Blake2s,
+        b"abc",
+        "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982",
+    ),
+    (
+        Algo::Blake3,
+        b"",
+        "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+    ),
+    (
+        Algo::Blake3,
+        b"abc",
+        "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85",
+    ),
+    (
+        Algo::Ripemd160,
+        b"",
+        "9c1185a5c5e9fc54612808977ee8f548b2258d31",
+    ),
+    (
+        Algo::Ripemd160,
+        b"abc",
+        "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc",
+    ),
+    (
+        Algo::Hash160,
+        b"",
+        "b472a266d0bd89c13706a4132ccfb16f7c3b9fcb",
+    ),
+    // RFC 1321 appendix A.5
+    (Algo::Md5, b"", "d41d8cd98f00b204e9800998ecf8427e"),
+    (Algo::Md5, b"abc", "900150983cd24fb0d6963f7d28e17f72"),
+];
+
+fn check(algo: Algo, msg: &[u8], digest: &[u8]) -> Result<(), ProofError> {
+    fn check_as<H: HashCircuit>(msg: &[u8], digest: &[u8]) -> Result<(), ProofError> {
+        check_hash_with_digest::<H>(msg, digest, false).map(|_| ())
+    }
+
+    match algo {
+        #[cfg(feature = "sha256")]
+        Algo::Sha256 => check_as::<Sha256Hash>(msg, digest),
+        #[cfg(feature = "sha256")]
+        Algo::Sha256d => check_as::<Sha256dHash>(msg, digest),
+        #[cfg(feature = "sha512")]
+        Algo::Sha512 => check_as::<Sha512Hash>(msg, digest),
+        #[cfg(feature = "sha2-truncated")]
+        Algo::Sha224 => check_as::<Sha224Hash>(msg, digest),
+        #[cfg(feature = "sha2-truncated")]
+        Algo::Sha384 => check_as::<Sha384Hash>(msg, digest),
+        #[cfg(feature = "keccak")]
+        Algo::Keccak => check_as::<Keccak256Hash>(msg, digest),
+        #[cfg(all(feature = "sha256", feature = "keccak"))]
+        Algo::Sha256Keccak => check_as::<Sha256KeccakHash>(msg, digest),
+        #[cfg(feature = "sha3")]
+        Algo::Sha3_256 => check_as::<Sha3_256Hash>(msg, digest),
+        #[cfg(feature = "sha3")]
+        Algo::Keccak512 => check_as::<Keccak512Hash>(msg, digest),
+        #[cfg(feature = "blake2b")]
+        Algo::Blake2b => check_as::<Blake2bHash>(msg, digest),
+        #[cfg(feature = "blake2s")]
+        Algo::Blake2s => check_as::<Blake2sHash>(msg, digest),
+        #[cfg(feature = "blake3")]
+        Algo::Blake3 => check_as::<Blake3Hash>(msg, digest),
+        #[cfg(feature = "ripemd")]
+        Algo::Ripemd160 => check_as::<Ripemd160Hash>(msg, digest),
+        #[cfg(feature = "ripemd")]
+        Algo::Hash160 => check_as::<Hash160Hash>(msg, digest),
+        #[cfg(feature = "legacy")]
+        Algo::Md5 => check_as::<Md5Hash>(msg, digest),
+        // `run` skips algos that are not compiled in, and the lattice relation has no
+        // published vectors
+        #[allow(unreachable_patterns)]
+        _ => unreachable!("no known-answer test for {}", algo.name()),
+    }
+}
+
+// Short form of a test message for the report.
+fn describe(msg: &[u8]) -> String {
+    match msg {
+        b"" => "\"\"".to_string(),
+        NIST_448 => "NIST 448-bit".to_string(),
+        NIST_896 => "NIST 896-bit".to_string(),
+        _ => format!("{:?}", String::from_utf8_lossy(msg)),
+    }
+}
+
+/// Check every vector of every compiled-in algo, printing one line each, and return
+/// whether all of them passed.
+pub fn run() -> bool {
+    let mut failed = 0;
+    let mut n = 0;
+    for &(algo, msg, expected) in VECTORS.iter().filter(|(algo, ..)| algo.enabled()) {
+        n += 1;
+        let digest = hex::decode(expected).expect("vectors are valid hex");
+        match check(algo, msg, &digest) {
+            Ok(()) => println!("ok    {} {}", algo.name(), describe(msg)),
+            Err(e) => {
+                failed += 1;
+                println!("FAIL  {} {}: {e}", algo.name(), describe(msg));
+            }
+        }
+    }
+
+    if failed == 0 {
+        println!("selftest: all {n} known-answer tests passed");
+    } else {
+        println!("selftest: {failed} of {n} known-answer tests failed");
+    }
+    failed == 0
+}
@@ -1,95 +1,462 @@
 use binius_circuits::sha256::Sha256;
-use binius_core::{verify::verify_constraints, word::Word};
-use binius_frontend::CircuitBuilder;
+use binius_core::{
+    constraint_system::{ConstraintSystem, ValueVec},
+    word::Word,
+};
+use binius_frontend::{Circuit, CircuitBuilder, Wire, WitnessFiller};
+use binius_verifier::config::StdChallenger;
 use sha2::{Digest, Sha256 as StdSha256};
 
-use binius_prover::{
-    OptimalPackedB128, Prover, hash::parallel_compression::ParallelCompressionAdaptor,
-};
-use binius_transcript::{ProverTranscript, VerifierTranscript};
-use binius_verifier::{
-    Verifier,
-    config::StdChallenger,
-    hash::{StdCompression, StdDigest},
+use crate::config::RunConfig;
+use crate::error::ProofError;
+use crate::hash_circuit::{
+    HashCircuit, HashProver, WordEndian, build_cs, digest_only_words, digest_words,
+    prove_and_verify_circuit, prove_circuit, prove_hash, prove_hash_with_digest,
+    prove_hash_with_max_len, public_digest, run_hash_circuit_with_config, verify_circuit,
+    verify_hash, words_to_bytes, words_to_digest,
 };
+use crate::timings::CircuitTimings;
 
-use std::time::Instant;
+/// The sha256 gadget together with the wire carrying the preimage length.
+pub struct Sha256Hash {
+    sha256: Sha256,
+    message: Vec<Wire>,
+    len_bytes: Wire,
+    max_len: usize,
+}
 
-pub fn sha256_circuit(image_bytes: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
-    // // make m
-    // let mut rng = rand::rng();
-    // let mut message_bytes = vec![0u8; size];
-    // rng.fill(&mut message_bytes[..]);
+impl HashCircuit for Sha256Hash {
+    const NAME: &'static str = "sha256";
 
-    println!("Proof for sha256 circuit: ");
-    // Start timer for setup
-    //let setup_timer = Instant::now();
-    // New Circuit
-    let builder = CircuitBuilder::new();
+    fn build(builder: &CircuitBuilder, max_len: usize) -> Self {
+        // Make wires for message
+        // the gadget needs at least one message wire, an empty message still gets one
+        // zero word that the length wire marks as unused
+        let n_wires = max_len.div_ceil(8).max(1);
 
-    let size = image_bytes.len();
-    // Make wires for message
-    let n_wires = (size + 7) / 8;
+        let message: Vec<_> = (0..n_wires).map(|_| builder.add_witness()).collect();
 
-    let message: Vec<_> = (0..n_wires).map(|_| builder.add_witness()).collect();
+        let commitment: [_; 4] = core::array::from_fn(|_| builder.add_inout());
 
-    let commitment: [_; 4] = core::array::from_fn(|_| builder.add_inout());
+        // Build sha256 gadget
+        let len_bytes = builder.add_witness();
+        let sha256 = Sha256::new(builder, len_bytes, commitment, message.clone());
 
-    // Build sha256 gadget
-    let len_bytes = builder.add_witness();
-    let sha256 = Sha256::new(&builder, len_bytes, commitment, message);
+        Self {
+            sha256,
+            message,
+            len_bytes,
+            max_len,
+        }
+    }
 
-    let circuit = builder.build();
+    fn max_len(&self) -> usize {
+        self.max_len
+    }
 
-    let mut witness = circuit.new_witness_filler();
-    witness[len_bytes] = Word(size as u64); // feed the circuit a wire containing the preimage length, in bytes.
+    fn populate_with_digest(&self, witness: &mut WitnessFiller, msg: &[u8], digest: &[u8]) {
+        witness[self.len_bytes] = Word(msg.len() as u64); // feed the circuit a wire containing the preimage length, in bytes.
+
+        self.sha256.populate_message(witness, msg);
+
+        let mut digest_bytes = [0u8; 32];
+        digest_bytes.copy_from_slice(digest);
+        self.sha256.populate_digest(witness, digest_bytes);
+    }
+
+    // the message wires are these words, assign them without going through bytes
+    fn populate_words(&self, witness: &mut WitnessFiller, words: &[Word]) {
+        debug_assert!(words.len() <= self.message.len());
+        for (i, &w) in self.message.iter().enumerate() {
+            witness[w] = words.get(i).copied().unwrap_or(Word(0));
+        }
+        witness[self.len_bytes] = Word(8 * words.len() as u64);
+
+        // the reference implementation still needs the bytes
+        let msg = words_to_bytes(words);
+        self.sha256
+            .populate_digest(witness, StdSha256::digest(&msg).into());
+    }
+
+    fn digest_from_public(&self, public: &[Word]) -> Vec<u8> {
+        public_digest(public, 32, DIGEST_ENDIAN)
+    }
+
+    fn reference_digest(msg: &[u8]) -> Vec<u8> {
+        StdSha256::digest(msg).to_vec()
+    }
+}
+
+/// Byte order of the sha256 digest words: SHA-256 reads its state words big-endian, so
+/// digest byte `8i` is the most significant byte of digest word `i`.
+pub const DIGEST_ENDIAN: WordEndian = WordEndian::Big;
+
+/// The public words of a sha256 or sha256d proof, with the digest at its known position.
+///
+/// Use this instead of indexing the raw words, whose leading entries are the circuit's
+/// constants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sha256PublicInputs {
+    words: Vec<Word>,
+}
+
+impl Sha256PublicInputs {
+    /// Wrap the public words of a proof, e.g. as returned by `sha256_prove`.
+    pub fn from_words(words: Vec<Word>) -> Result<Self, ProofError> {
+        digest_words(&words, 4)?;
+        Ok(Self { words })
+    }
+
+    /// The 32-byte digest, the last 4 public words, each big-endian.
+    pub fn digest(&self) -> [u8; 32] {
+        self.digest_with_endian(DIGEST_ENDIAN)
+    }
+
+    /// The last 4 public words as bytes in `endian` order, e.g. to compare with a
+    /// consumer that reads them little-endian. Only `DIGEST_ENDIAN` gives the digest.
+    pub fn digest_with_endian(&self, endian: WordEndian) -> [u8; 32] {
+        let words = digest_words(&self.words, 4).expect("checked in from_words");
+        words_to_digest(words, 32, endian)
+            .try_into()
+            .expect("4 words hold 32 bytes")
+    }
+
+    /// All public words, as passed to the verifier.
+    pub fn words(&self) -> &[Word] {
+        &self.words
+    }
+
+    pub fn into_words(self) -> Vec<Word> {
+        self.words
+    }
+}
+
+pub type Sha256Prover = HashProver<Sha256Hash>;
+
+// The sha256 digest words are big-endian, the message words little-endian, so a digest
+// has to be byte swapped before it is fed to another sha256 gadget.
+pub(crate) fn bswap64(b: &CircuitBuilder, x: Wire) -> Wire {
+    (0..8)
+        .map(|k| b.shl(b.extract_byte(x, k), 8 * (7 - k)))
+        .reduce(|acc, byte| b.bxor(acc, byte))
+        .unwrap()
+}
+
+/// sha256d = SHA-256(SHA-256(x)), Bitcoin's block and transaction hash, as two chained
+/// sha256 gadgets in one circuit. Only the outer digest is public, the inner digest
+/// stays in the witness.
+pub struct Sha256dHash {
+    inner: Sha256,
+    outer: Sha256,
+    len_bytes: Wire,
+    max_len: usize,
+}
+
+impl HashCircuit for Sha256dHash {
+    const NAME: &'static str = "sha256d";
+
+    fn build(builder: &CircuitBuilder, max_len: usize) -> Self {
+        // at least one message wire, as for `Sha256Hash`
+        let message: Vec<_> = (0..max_len.div_ceil(8).max(1))
+            .map(|_| builder.add_witness())
+            .collect();
+        let inner_digest: [_; 4] = core::array::from_fn(|_| builder.add_witness());
+        let commitment: [_; 4] = core::array::from_fn(|_| builder.add_inout());
+
+        let len_bytes = builder.add_witness();
+        let inner = Sha256::new(builder, len_bytes, inner_digest, message);
+
+        let outer_message = inner_digest.iter().map(|&d| bswap64(builder, d)).collect();
+        let outer_len = builder.add_constant_64(32);
+        let outer = Sha256::new(builder, outer_len, commitment, outer_message);
+
+        Self {
+            inner,
+            outer,
+            len_bytes,
+            max_len,
+        }
+    }
+
+    fn max_len(&self) -> usize {
+        self.max_len
+    }
 
-    // println!("Message bytes: {:?}", message_bytes);
+    fn populate_with_digest(&self, witness: &mut WitnessFiller, msg: &[u8], digest: &[u8]) {
+        witness[self.len_bytes] = Word(msg.len() as u64);
 
-    sha256.populate_message(&mut witness, &image_bytes);
+        let inner_digest: [u8; 32] = StdSha256::digest(msg).into();
+        self.inner.populate_message(witness, msg);
+        self.inner.populate_digest(witness, inner_digest);
 
-    let digest = StdSha256::digest(&image_bytes);
-    let mut digest_bytes = [0u8; 32];
-    digest_bytes.copy_from_slice(&digest);
-    sha256.populate_digest(&mut witness, digest_bytes);
+        let mut digest_bytes = [0u8; 32];
+        digest_bytes.copy_from_slice(digest);
+        self.outer.populate_message(witness, &inner_digest);
+        self.outer.populate_digest(witness, digest_bytes);
+    }
 
+    fn digest_from_public(&self, public: &[Word]) -> Vec<u8> {
+        public_digest(public, 32, DIGEST_ENDIAN)
+    }
+
+    fn reference_digest(msg: &[u8]) -> Vec<u8> {
+        StdSha256::digest(StdSha256::digest(msg)).to_vec()
+    }
+}
+
+/// Prove sha256(m) = h and return the finalized proof bytes with the public words.
+pub fn sha256_prove(
+    image_bytes: &[u8],
+    verbose: bool,
+) -> Result<(Vec<u8>, Vec<Word>, CircuitTimings), ProofError> {
+    prove_hash::<Sha256Hash>(image_bytes, verbose)
+}
+
+/// Like `sha256_prove`, with the circuit built for messages of up to `max_len` bytes,
+/// see `prove_hash_with_max_len`. Verify with `sha256_verify(max_len, ..)`.
+pub fn sha256_prove_with_max_len(
+    image_bytes: &[u8],
+    max_len: usize,
+    verbose: bool,
+) -> Result<(Vec<u8>, Vec<Word>, CircuitTimings), ProofError> {
+    prove_hash_with_max_len::<Sha256Hash>(image_bytes, max_len, verbose)
+}
+
+/// The sha256 digest of `image_bytes` read from the public words of the checked circuit
+/// witness, without proving, see `hash_circuit::digest_only_words`.
+pub fn digest_only_sha256(image_bytes: &[u8]) -> Result<[u8; 32], ProofError> {
+    let words = digest_only_words::<Sha256Hash>(image_bytes)?;
+    Ok(Sha256PublicInputs::from_words(words)?.digest())
+}
+
+/// Prove knowledge of a preimage of the given sha256 `digest`, see
+/// `prove_hash_with_digest`.
+pub fn sha256_prove_digest(
+    image_bytes: &[u8],
+    digest: &[u8; 32],
+    verbose: bool,
+) -> Result<(Vec<u8>, Vec<Word>, CircuitTimings), ProofError> {
+    prove_hash_with_digest::<Sha256Hash>(image_bytes, digest, verbose)
+}
+
+/// Verify a proof produced by `sha256_prove` for a message of `size` bytes, or by
+/// `sha256_prove_with_max_len` with `max_len` = `size`.
+/// Only the setup and verify fields of the returned timings are filled in.
+pub fn sha256_verify(
+    size: usize,
+    proof: Vec<u8>,
+    public_words: &[Word],
+    verbose: bool,
+) -> Result<CircuitTimings, ProofError> {
+    verify_hash::<Sha256Hash>(size, proof, public_words, verbose)
+}
+
+/// Verify a sha256 proof from its public words alone, without the witness or any
+/// prover setup. `max_len` is the length the circuit was built for, the message
+/// length or the `max_len` given to `sha256_prove_with_max_len`.
+pub fn verify_sha256(
+    max_len: usize,
+    proof: Vec<u8>,
+    public_words: &[Word],
+) -> Result<(), ProofError> {
+    verify_hash::<Sha256Hash>(max_len, proof, public_words, false).map(|_| ())
+}
+
+/// The sha256 constraint system for messages of up to `max_len` bytes, see
+/// `hash_circuit::build_cs`.
+pub fn build_cs_sha256(max_len: usize) -> ConstraintSystem {
+    build_cs::<Sha256Hash>(max_len)
+}
+
+pub fn sha256_circuit(
+    image_bytes: &[u8],
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    run_hash_circuit_with_config(image_bytes, config, Sha256Hash::build)
+}
+
+pub fn sha256d_circuit(
+    image_bytes: &[u8],
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    run_hash_circuit_with_config(image_bytes, config, Sha256dHash::build)
+}
+
+// One sha256 gadget per message length, all in the same circuit.
+fn build_batch(lens: &[usize]) -> (Circuit, Vec<Sha256Hash>) {
+    let builder = CircuitBuilder::new();
+    let hashes = lens
+        .iter()
+        .map(|&len| Sha256Hash::build(&builder, len))
+        .collect();
+    (builder.build(), hashes)
+}
+
+/// Prove sha256(m_i) = h_i for every message in a single proof.
+///
+/// The public words hold the N digests in message order. Setup, the commitment to the
+/// witness and the verifier's work are paid once for the whole batch instead of once
+/// per message, so for many small messages the per-message cost drops towards the
+/// cost of the sha256 constraints alone.
+pub fn sha256_batch_prove(
+    messages: &[Vec<u8>],
+    verbose: bool,
+) -> Result<(Vec<u8>, Vec<Word>, CircuitTimings), ProofError> {
+    let (circuit, witness_vec) = batch_witness(messages)?;
+    prove_circuit(&circuit, witness_vec, StdChallenger::default(), verbose)
+}
+
+// Build the batch circuit for `messages` and populate its witness.
+fn batch_witness(messages: &[Vec<u8>]) -> Result<(Circuit, ValueVec), ProofError> {
+    let lens: Vec<_> = messages.iter().map(Vec::len).collect();
+    let (circuit, hashes) = build_batch(&lens);
+
+    let mut witness = circuit.new_witness_filler();
+    for (hash, msg) in hashes.iter().zip(messages) {
+        hash.populate(&mut witness, msg);
+    }
     circuit.populate_wire_witness(&mut witness)?;
+    Ok((circuit, witness.into_value_vec()))
+}
+
+/// Verify a proof produced by `sha256_batch_prove` for messages of the given lengths.
+pub fn sha256_batch_verify(
+    lens: &[usize],
+    proof: Vec<u8>,
+    public_words: &[Word],
+    verbose: bool,
+) -> Result<CircuitTimings, ProofError> {
+    let (circuit, _) = build_batch(lens);
+    verify_circuit(
+        &circuit,
+        proof,
+        public_words,
+        StdChallenger::default(),
+        verbose,
+    )
+}
 
-    // check sha256(m) = h
-    let cs = circuit.constraint_system();
-    let witness_vec = witness.into_value_vec();
-    verify_constraints(cs, &witness_vec)?;
+pub fn sha256_batch_circuit(
+    messages: &[Vec<u8>],
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    if config.print_progress() {
+        println!("Proof for sha256 batch of {} messages: ", messages.len());
+    }
 
-    println!("✓ constraint verified");
+    let (circuit, witness_vec) = batch_witness(messages)?;
+    prove_and_verify_circuit(&circuit, witness_vec, config)
+}
 
-    // prove / verify sha256(m) = h
-    let compression = ParallelCompressionAdaptor::new(StdCompression::default());
-    let verifier = Verifier::<StdDigest, _>::setup(cs.clone(), 1, StdCompression::default())?;
-    let prover = Prover::<OptimalPackedB128, _, StdDigest>::setup(verifier.clone(), compression)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use binius_core::verify::verify_constraints;
 
-    let challenger = StdChallenger::default();
-    let mut prover_transcript = ProverTranscript::new(challenger.clone());
-    let public_words = witness_vec.public().to_vec();
+    #[test]
+    fn public_inputs_digest_matches_reference() {
+        let msg = b"abc";
+        let (_, public_words, _) =
+            crate::hash_circuit::prove_hash::<Sha256Hash>(msg, false).unwrap();
+        let public = Sha256PublicInputs::from_words(public_words.clone()).unwrap();
+        assert_eq!(public.digest().to_vec(), Sha256Hash::reference_digest(msg));
+        assert_eq!(public.words(), &public_words[..]);
+    }
 
-    //println!("Setup time {}ms", setup_timer.elapsed().as_millis());
+    #[test]
+    fn digest_word_order_is_explicit() {
+        use crate::hash_circuit::digest_to_words;
 
-    let prove_timer = Instant::now();
+        let msg = b"abc";
+        let reference = Sha256Hash::reference_digest(msg);
+        let (_, public_words, _) =
+            crate::hash_circuit::prove_hash::<Sha256Hash>(msg, false).unwrap();
+        let public = Sha256PublicInputs::from_words(public_words.clone()).unwrap();
 
-    prover.prove(witness_vec, &mut prover_transcript)?;
-    let proof = prover_transcript.finalize();
+        // the gadget's digest words are the reference packed big-endian
+        assert_eq!(
+            digest_words(&public_words, 4).unwrap(),
+            &digest_to_words(&reference, WordEndian::Big)[..]
+        );
+        assert_eq!(
+            public.digest_with_endian(WordEndian::Big).to_vec(),
+            reference
+        );
 
-    println!("Proof time {}ms", prove_timer.elapsed().as_millis());
+        // read little-endian, every 8-byte group comes out reversed
+        let little = public.digest_with_endian(WordEndian::Little);
+        assert_ne!(little.to_vec(), reference);
+        for (l, r) in little.chunks(8).zip(reference.chunks(8)) {
+            assert!(l.iter().eq(r.iter().rev()));
+        }
 
-    let mut verifier_transcript = VerifierTranscript::new(challenger, proof);
+        // both orders round trip, including a partial last word
+        for endian in [WordEndian::Big, WordEndian::Little] {
+            for len in [32, 28, 1] {
+                let words = digest_to_words(&reference[..len], endian);
+                assert_eq!(words.len(), len.div_ceil(8));
+                assert_eq!(words_to_digest(&words, len, endian), &reference[..len]);
+            }
+        }
+    }
 
-    let verify_timer = Instant::now();
+    #[test]
+    fn digest_only_matches_known_answer() {
+        assert_eq!(
+            hex::encode(digest_only_sha256(b"abc").unwrap()),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
 
-    verifier.verify(&public_words, &mut verifier_transcript)?;
-    verifier_transcript.finalize()?;
+    // SHA-256 padding of a `len`-byte message: 0x80, zeros up to 56 mod 64, then the
+    // bit length as 8 big-endian bytes.
+    fn md_padding(len: usize) -> Vec<u8> {
+        let mut pad = vec![0x80];
+        pad.resize((119 - len % 64) % 64 + 1, 0);
+        pad.extend_from_slice(&(8 * len as u64).to_be_bytes());
+        pad
+    }
 
-    println!("Verify time {}ms", verify_timer.elapsed().as_millis());
+    // Length extension: given sha256(m) and |m| alone, anyone can compute
+    // sha256(m || pad(m) || suffix) without knowing m. The circuit does not prevent that,
+    // it is a hash and not a MAC. What it guarantees is that a proof is about the digest
+    // of exactly the `len_bytes` bytes the length wire binds, so the extended message is
+    // a different statement with a different digest, and neither a witness nor a proof
+    // for one passes for the other.
+    #[test]
+    fn length_extension_is_a_different_statement() {
+        let msg = b"amount=100";
+        let extended = [&msg[..], &md_padding(msg.len()), b"&amount=1000000"].concat();
+        assert_eq!((msg.len() + md_padding(msg.len()).len()) % 64, 0);
+        let digest = Sha256Hash::reference_digest(msg);
+        let extended_digest = Sha256Hash::reference_digest(&extended);
+        assert_ne!(digest, extended_digest);
 
-    println!("✓ proof successfully verified");
+        // one circuit, built for the extended length, fits both messages
+        let builder = CircuitBuilder::new();
+        let hash = Sha256Hash::build(&builder, extended.len());
+        let circuit = builder.build();
+        let satisfied = |msg: &[u8], digest: &[u8], len_bytes: usize| {
+            let mut witness = circuit.new_witness_filler();
+            hash.populate_with_digest(&mut witness, msg, digest);
+            witness[hash.len_bytes] = Word(len_bytes as u64);
+            circuit.populate_wire_witness(&mut witness).is_ok()
+                && verify_constraints(circuit.constraint_system(), &witness.into_value_vec())
+                    .is_ok()
+        };
+        assert!(satisfied(msg, &digest, msg.len()));
+        assert!(satisfied(&extended, &extended_digest, extended.len()));
+        // the extended message cannot claim the original digest
+        assert!(!satisfied(&extended, &digest, extended.len()));
+        // nor can the extended digest be claimed with the original length, which would
+        // mean the length wire is not bound to the bytes hashed
+        assert!(!satisfied(&extended, &extended_digest, msg.len()));
 
-    Ok(())
+        // a proof of the extended message does not verify against the original's
+        // public words
+        let prover = Sha256Prover::new(extended.len()).unwrap();
+        let (_, public_words) = prover.prove(msg).unwrap();
+        let (extended_proof, _) = prover.prove(&extended).unwrap();
+        assert!(prover.verify(extended_proof, &public_words).is_err());
+    }
 }
@@ -0,0 +1,431 @@
+//! SHA-224 and SHA-384 circuits.
+//!
+//! Both are SHA-256 / SHA-512 with their own IVs and a truncated output. The upstream
+//! `Sha256` and `Sha512` gadgets hardcode the standard IVs, and proving the full digest
+//! and truncating it afterwards would prove a different hash, so the compression
+//! function is built here from the builder primitives instead, with the IV as a
+//! parameter. As for the other hand-built gadgets, the message length is fixed when the
+//! circuit is built.
+//!
+//! The digest is exposed in the same layout as the upstream sha256 commitment, 8 digest
+//! bytes per inout word in big-endian order. SHA-384's 48 bytes fill 6 words exactly,
+//! SHA-224's 28 bytes take 4 words with the low half of the last one zero.
+
+use binius_core::word::Word;
+use binius_frontend::{CircuitBuilder, Wire, WitnessFiller};
+use sha2::{Digest, Sha224 as StdSha224, Sha384 as StdSha384};
+
+use crate::config::RunConfig;
+use crate::error::ProofError;
+use crate::hash_circuit::{
+    HashCircuit, WordEndian, check_exact_len, public_digest, run_hash_circuit_with_config,
+};
+use crate::timings::CircuitTimings;
+
+const K256: [u64; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+const K512: [u64; 80] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+/// SHA-256 with the IV and output length of one of its truncated variants.
+struct Variant {
+    // 4 for the SHA-256 family, 8 for SHA-512
+    word_bytes: usize,
+    iv: [u64; 8],
+    k: &'static [u64],
+    // rotations of Σ0 and Σ1, then rotations and shift of σ0 and σ1
+    big_sigma: [[u32; 3]; 2],
+    small_sigma: [[u32; 3]; 2],
+    out_len: usize,
+}
+
+const SHA224: Variant = Variant {
+    word_bytes: 4,
+    iv: [
+        0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939, 0xffc00b31, 0x68581511, 0x64f98fa7,
+        0xbefa4fa4,
+    ],
+    k: &K256,
+    big_sigma: [[2, 13, 22], [6, 11, 25]],
+    small_sigma: [[7, 18, 3], [17, 19, 10]],
+    out_len: 28,
+};
+
+const SHA384: Variant = Variant {
+    word_bytes: 8,
+    iv: [
+        0xcbbb9d5dc1059ed8,
+        0x629a292a367cd507,
+        0x9159015a3070dd17,
+        0x152fecd8f70e5939,
+        0x67332667ffc00b31,
+        0x8eb44a8768581511,
+        0xdb0c2e0d64f98fa7,
+        0x47b5481dbefa4fa4,
+    ],
+    k: &K512,
+    big_sigma: [[28, 34, 39], [14, 18, 41]],
+    small_sigma: [[1, 8, 7], [19, 61, 6]],
+    out_len: 48,
+};
+
+impl Variant {
+    // 64-byte blocks with a 64-bit length for SHA-256, 128-byte blocks with a 128-bit
+    // length for SHA-512
+    fn block_len(&self) -> usize {
+        16 * self.word_bytes
+    }
+
+    fn word_mask(&self) -> u64 {
+        u64::MAX >> (64 - 8 * self.word_bytes)
+    }
+
+    fn add(&self, b: &CircuitBuilder, x: Wire, y: Wire) -> Wire {
+        if self.word_bytes == 4 {
+            b.iadd_32(x, y)
+        } else {
+            b.iadd_cin_cout(x, y, b.add_constant_64(0)).0
+        }
+    }
+
+    fn rotr(&self, b: &CircuitBuilder, x: Wire, n: u32) -> Wire {
+        if self.word_bytes == 4 {
+            b.rotr_32(x, n)
+        } else {
+            b.rotr(x, n)
+        }
+    }
+
+    fn big_sigma(&self, b: &CircuitBuilder, i: usize, x: Wire) -> Wire {
+        let [r0, r1, r2] = self.big_sigma[i];
+        b.bxor(
+            b.bxor(self.rotr(b, x, r0), self.rotr(b, x, r1)),
+            self.rotr(b, x, r2),
+        )
+    }
+
+    fn small_sigma(&self, b: &CircuitBuilder, i: usize, x: Wire) -> Wire {
+        let [r0, r1, s] = self.small_sigma[i];
+        b.bxor(
+            b.bxor(self.rotr(b, x, r0), self.rotr(b, x, r1)),
+            b.shr(x, s),
+        )
+    }
+
+    // Message words after padding: 0x80, zeros, then the bit length big-endian in the
+    // last 2 words of the final block.
+    fn pad(&self, b: &CircuitBuilder, words: &[Wire], len: usize) -> Vec<Wire> {
+        let wb = self.word_bytes;
+        let bl = self.block_len();
+        let padded_len = (len + 2 * wb) / bl * bl + bl;
+
+        let mut pad_bytes = vec![0u8; padded_len];
+        pad_bytes[len] = 0x80;
+        pad_bytes[padded_len - 8..].copy_from_slice(&(8 * len as u64).to_be_bytes());
+
+        (0..padded_len / wb)
+            .map(|k| {
+                let pad_word = pad_bytes[wb * k..wb * k + wb]
+                    .iter()
+                    .fold(0u64, |acc, &byte| (acc << 8) | byte as u64);
+                match words.get(k) {
+                    // only the first len % wb bytes of a partial last word are message,
+                    // and in big-endian order those are the high bytes
+                    Some(&w) if wb * k + wb > len => {
+                        let keep = self.word_mask() << (8 * (wb - len % wb)) & self.word_mask();
+                        let masked = b.band(w, b.add_constant_64(keep));
+                        b.bxor(masked, b.add_constant_64(pad_word))
+                    }
+                    // σ0 and σ1 shift the whole 64-bit word, so 32-bit message words must
+                    // not carry anything in their high half
+                    Some(&w) if wb == 4 => b.band(w, b.add_constant_64(self.word_mask())),
+                    Some(&w) => w,
+                    None => b.add_constant_64(pad_word),
+                }
+            })
+            .collect()
+    }
+
+    fn compress(&self, b: &CircuitBuilder, h: &mut [Wire; 8], block: &[Wire]) {
+        let rounds = self.k.len();
+        let mut w: Vec<Wire> = block.to_vec();
+        for t in 16..rounds {
+            let s0 = self.small_sigma(b, 0, w[t - 15]);
+            let s1 = self.small_sigma(b, 1, w[t - 2]);
+            let sum = self.add(b, self.add(b, s1, w[t - 7]), self.add(b, s0, w[t - 16]));
+            w.push(sum);
+        }
+
+        let [mut a, mut bb, mut c, mut d, mut e, mut f, mut g, mut hh] = *h;
+        for t in 0..rounds {
+            // Ch(e, f, g) = g ^ (e & (f ^ g)), Maj(a, b, c) = (a & b) ^ (c & (a ^ b))
+            let ch = b.bxor(g, b.band(e, b.bxor(f, g)));
+            let maj = b.bxor(b.band(a, bb), b.band(c, b.bxor(a, bb)));
+
+            let k = b.add_constant_64(self.k[t]);
+            let t1 = self.add(b, hh, self.big_sigma(b, 1, e));
+            let t1 = self.add(b, self.add(b, t1, ch), self.add(b, k, w[t]));
+            let t2 = self.add(b, self.big_sigma(b, 0, a), maj);
+
+            (hh, g, f, e, d, c, bb, a) =
+                (g, f, e, self.add(b, d, t1), c, bb, a, self.add(b, t1, t2));
+        }
+
+        for (s, v) in h.iter_mut().zip([a, bb, c, d, e, f, g, hh]) {
+            *s = self.add(b, *s, v);
+        }
+    }
+
+    // Hash `len` bytes held in `words` (one big-endian word each) and constrain the
+    // truncated digest to `digest`, 8 bytes per word.
+    fn build(&self, b: &CircuitBuilder, words: &[Wire], len: usize, digest: &[Wire]) {
+        let padded = self.pad(b, words, len);
+        let mut h: [Wire; 8] = core::array::from_fn(|i| b.add_constant_64(self.iv[i]));
+        for block in padded.chunks(16) {
+            self.compress(b, &mut h, block);
+        }
+
+        let mask = b.add_constant_64(self.word_mask());
+        let out: Vec<Wire> = h[..self.out_len / self.word_bytes]
+            .iter()
+            .map(|&x| b.band(x, mask))
+            .collect();
+
+        // two 32-bit output words per commitment word, the second one may be missing
+        let packed: Vec<Wire> = if self.word_bytes == 4 {
+            out.chunks(2)
+                .map(|pair| match pair {
+                    [hi, lo] => b.bxor(b.shl(*hi, 32), *lo),
+                    [hi] => b.shl(*hi, 32),
+                    _ => unreachable!(),
+                })
+                .collect()
+        } else {
+            out
+        };
+
+        for (i, (&p, &d)) in packed.iter().zip(digest).enumerate() {
+            b.assert_eq(format!("sha2 digest[{i}]"), p, d);
+        }
+    }
+
+    fn populate_message(&self, witness: &mut WitnessFiller, words: &[Wire], msg: &[u8]) {
+        let wb = self.word_bytes;
+        for (i, &w) in words.iter().enumerate() {
+            witness[w] = Word((0..wb).fold(0u64, |acc, k| {
+                (acc << 8) | msg.get(wb * i + k).copied().unwrap_or(0) as u64
+            }));
+        }
+    }
+}
+
+fn populate_digest(witness: &mut WitnessFiller, wires: &[Wire], digest: &[u8]) {
+    for (i, &w) in wires.iter().enumerate() {
+        let mut word = [0u8; 8];
+        for (k, byte) in word.iter_mut().enumerate() {
+            *byte = digest.get(8 * i + k).copied().unwrap_or(0);
+        }
+        witness[w] = Word(u64::from_be_bytes(word));
+    }
+}
+
+/// A truncated SHA-2 variant over a message whose length is fixed when the circuit is
+/// built.
+struct Sha2Truncated {
+    variant: &'static Variant,
+    len: usize,
+    message: Vec<Wire>,
+    digest: Vec<Wire>,
+}
+
+impl Sha2Truncated {
+    fn new(b: &CircuitBuilder, variant: &'static Variant, len: usize) -> Self {
+        let message: Vec<_> = (0..len.div_ceil(variant.word_bytes))
+            .map(|_| b.add_witness())
+            .collect();
+        let digest: Vec<_> = (0..variant.out_len.div_ceil(8))
+            .map(|_| b.add_inout())
+            .collect();
+        variant.build(b, &message, len, &digest);
+
+        Self {
+            variant,
+            len,
+            message,
+            digest,
+        }
+    }
+
+    fn populate(&self, witness: &mut WitnessFiller, msg: &[u8], digest: &[u8]) {
+        debug_assert!(msg.len() <= self.len);
+        self.variant.populate_message(witness, &self.message, msg);
+        populate_digest(witness, &self.digest, digest);
+    }
+}
+
+/// SHA-224, 28-byte digest in 4 commitment words.
+pub struct Sha224Hash(Sha2Truncated);
+
+impl HashCircuit for Sha224Hash {
+    const NAME: &'static str = "sha224";
+
+    fn build(builder: &CircuitBuilder, max_len: usize) -> Self {
+        Self(Sha2Truncated::new(builder, &SHA224, max_len))
+    }
+
+    fn max_len(&self) -> usize {
+        self.0.len
+    }
+
+    // the message length is fixed when the circuit is built
+    fn check_len(&self, msg: &[u8]) -> Result<(), ProofError> {
+        check_exact_len(msg, self.0.len)
+    }
+
+    fn populate_with_digest(&self, witness: &mut WitnessFiller, msg: &[u8], digest: &[u8]) {
+        self.0.populate(witness, msg, digest);
+    }
+
+    fn digest_from_public(&self, public: &[Word]) -> Vec<u8> {
+        public_digest(public, self.0.variant.out_len, WordEndian::Big)
+    }
+
+    fn reference_digest(msg: &[u8]) -> Vec<u8> {
+        StdSha224::digest(msg).to_vec()
+    }
+}
+
+/// SHA-384, 48-byte digest in 6 commitment words.
+pub struct Sha384Hash(Sha2Truncated);
+
+impl HashCircuit for Sha384Hash {
+    const NAME: &'static str = "sha384";
+
+    fn build(builder: &CircuitBuilder, max_len: usize) -> Self {
+        Self(Sha2Truncated::new(builder, &SHA384, max_len))
+    }
+
+    fn max_len(&self) -> usize {
+        self.0.len
+    }
+
+    // the message length is fixed when the circuit is built
+    fn check_len(&self, msg: &[u8]) -> Result<(), ProofError> {
+        check_exact_len(msg, self.0.len)
+    }
+
+    fn populate_with_digest(&self, witness: &mut WitnessFiller, msg: &[u8], digest: &[u8]) {
+        self.0.populate(witness, msg, digest);
+    }
+
+    fn digest_from_public(&self, public: &[Word]) -> Vec<u8> {
+        public_digest(public, self.0.variant.out_len, WordEndian::Big)
+    }
+
+    fn reference_digest(msg: &[u8]) -> Vec<u8> {
+        StdSha384::digest(msg).to_vec()
+    }
+}
+
+pub fn sha224_circuit(
+    image_bytes: &[u8],
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    run_hash_circuit_with_config(image_bytes, config, Sha224Hash::build)
+}
+
+pub fn sha384_circuit(
+    image_bytes: &[u8],
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    run_hash_circuit_with_config(image_bytes, config, Sha384Hash::build)
+}
@@ -0,0 +1,250 @@
+//! SHA3-256 and Keccak-512 circuits.
+//!
+//! Keccak and SHA3 share the Keccak-f[1600] permutation and only differ in the padding
+//! byte appended to the message:
+//! - Keccak (pre-standard, used by Ethereum): `0x01`. `binius_circuits::keccak::Keccak256`
+//!   is this variant, wrapped by `Keccak256Hash` in `keccak.rs`.
+//! - SHA3 (NIST FIPS 202): `0x06`. `Sha3_256Hash` below.
+//!
+//! Both add a final `0x80` bit at the end of the last rate block. Upstream only ships
+//! Keccak-256, so this module builds its own sponge over the permutation for
+//! `Sha3_256Hash` (rate 136 bytes, SHA3 padding) and `Keccak512Hash` (rate 72 bytes,
+//! Keccak padding). The message length is fixed when the circuit is built.
+
+use binius_core::word::Word;
+use binius_frontend::{CircuitBuilder, Wire, WitnessFiller};
+use sha3::{Digest, Keccak512 as CpuKeccak512, Sha3_256 as CpuSha3_256};
+
+use crate::config::RunConfig;
+use crate::error::ProofError;
+use crate::hash_circuit::{
+    HashCircuit, WordEndian, check_exact_len, public_digest, run_hash_circuit_with_config,
+};
+use crate::timings::CircuitTimings;
+
+const RC: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808A,
+    0x8000000080008000,
+    0x000000000000808B,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008A,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000A,
+    0x000000008000808B,
+    0x800000000000008B,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800A,
+    0x800000008000000A,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+// rotation offsets, indexed by x + 5 * y
+const RHO: [u32; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+const KECCAK_PAD: u8 = 0x01;
+const SHA3_PAD: u8 = 0x06;
+
+fn rotl(b: &CircuitBuilder, x: Wire, n: u32) -> Wire {
+    if n == 0 { x } else { b.rotl(x, n) }
+}
+
+// Keccak-f[1600] on 25 64-bit lanes, lane (x, y) at index x + 5 * y.
+fn keccak_f1600(b: &CircuitBuilder, a: &mut [Wire; 25]) {
+    let all1 = b.add_constant(Word::ALL_ONE);
+
+    for rc in RC {
+        // theta
+        let c: [Wire; 5] =
+            core::array::from_fn(|x| (1..5).fold(a[x], |acc, y| b.bxor(acc, a[x + 5 * y])));
+        for x in 0..5 {
+            let d = b.bxor(c[(x + 4) % 5], rotl(b, c[(x + 1) % 5], 1));
+            for y in 0..5 {
+                a[x + 5 * y] = b.bxor(a[x + 5 * y], d);
+            }
+        }
+
+        // rho and pi
+        let mut t = *a;
+        for x in 0..5 {
+            for y in 0..5 {
+                t[y + 5 * ((2 * x + 3 * y) % 5)] = rotl(b, a[x + 5 * y], RHO[x + 5 * y]);
+            }
+        }
+
+        // chi
+        for y in 0..5 {
+            for x in 0..5 {
+                let not_next = b.bxor(t[(x + 1) % 5 + 5 * y], all1);
+                a[x + 5 * y] = b.bxor(t[x + 5 * y], b.band(not_next, t[(x + 2) % 5 + 5 * y]));
+            }
+        }
+
+        // iota
+        a[0] = b.bxor(a[0], b.add_constant_64(rc));
+    }
+}
+
+/// A Keccak sponge over a message whose length is fixed when the circuit is built.
+///
+/// Message wires hold 8 little-endian bytes each, the digest is exposed as
+/// `out_len / 8` inout words in the same packing.
+struct KeccakSponge {
+    len: usize,
+    message: Vec<Wire>,
+    digest: Vec<Wire>,
+}
+
+impl KeccakSponge {
+    fn new(b: &CircuitBuilder, len: usize, rate: usize, pad: u8, out_len: usize) -> Self {
+        let message: Vec<_> = (0..len.div_ceil(8)).map(|_| b.add_witness()).collect();
+        let digest: Vec<_> = (0..out_len / 8).map(|_| b.add_inout()).collect();
+
+        // pad with `pad` right after the message and 0x80 in the last byte of the block
+        let padded_len = (len / rate + 1) * rate;
+        let mut pad_bytes = vec![0u8; padded_len - 8 * (len / 8)];
+        pad_bytes[len % 8] ^= pad;
+        *pad_bytes.last_mut().unwrap() ^= 0x80;
+
+        let lanes: Vec<Wire> = (0..padded_len / 8)
+            .map(|k| {
+                let pad_word = if 8 * k + 8 > len {
+                    let off = 8 * k - 8 * (len / 8);
+                    u64::from_le_bytes(pad_bytes[off..off + 8].try_into().unwrap())
+                } else {
+                    0
+                };
+                match message.get(k) {
+                    // only the first len % 8 bytes of a partial last word are message
+                    Some(&w) if 8 * k + 8 > len => {
+                        let mask = b.add_constant_64((1 << (8 * (len % 8))) - 1);
+                        b.bxor(b.band(w, mask), b.add_constant_64(pad_word))
+                    }
+                    Some(&w) => w,
+                    None => b.add_constant_64(pad_word),
+                }
+            })
+            .collect();
+
+        let mut state: [Wire; 25] = core::array::from_fn(|_| b.add_constant_64(0));
+        for block in lanes.chunks(rate / 8) {
+            for (s, &lane) in state.iter_mut().zip(block) {
+                *s = b.bxor(*s, lane);
+            }
+            keccak_f1600(b, &mut state);
+        }
+
+        for (i, &d) in digest.iter().enumerate() {
+            b.assert_eq(format!("keccak digest[{i}]"), state[i], d);
+        }
+
+        Self {
+            len,
+            message,
+            digest,
+        }
+    }
+
+    fn populate(&self, witness: &mut WitnessFiller, msg: &[u8], digest: &[u8]) {
+        debug_assert!(msg.len() <= self.len);
+        for (words, bytes) in [(&self.message, msg), (&self.digest, digest)] {
+            for (i, &w) in words.iter().enumerate() {
+                let mut word = [0u8; 8];
+                for (k, byte) in word.iter_mut().enumerate() {
+                    *byte = bytes.get(8 * i + k).copied().unwrap_or(0);
+                }
+                witness[w] = Word(u64::from_le_bytes(word));
+            }
+        }
+    }
+}
+
+/// SHA3-256 (NIST padding, rate 136 bytes, 32-byte digest in 4 commitment words).
+pub struct Sha3_256Hash(KeccakSponge);
+
+impl HashCircuit for Sha3_256Hash {
+    const NAME: &'static str = "sha3-256";
+
+    fn build(builder: &CircuitBuilder, max_len: usize) -> Self {
+        Self(KeccakSponge::new(builder, max_len, 136, SHA3_PAD, 32))
+    }
+
+    fn max_len(&self) -> usize {
+        self.0.len
+    }
+
+    // the message length is fixed when the circuit is built
+    fn check_len(&self, msg: &[u8]) -> Result<(), ProofError> {
+        check_exact_len(msg, self.0.len)
+    }
+
+    fn populate_with_digest(&self, witness: &mut WitnessFiller, msg: &[u8], digest: &[u8]) {
+        self.0.populate(witness, msg, digest);
+    }
+
+    fn digest_from_public(&self, public: &[Word]) -> Vec<u8> {
+        public_digest(public, 8 * self.0.digest.len(), WordEndian::Little)
+    }
+
+    fn reference_digest(msg: &[u8]) -> Vec<u8> {
+        CpuSha3_256::digest(msg).to_vec()
+    }
+}
+
+/// Keccak-512 (Keccak padding, rate 72 bytes, 64-byte digest in 8 commitment words).
+pub struct Keccak512Hash(KeccakSponge);
+
+impl HashCircuit for Keccak512Hash {
+    const NAME: &'static str = "keccak512";
+
+    fn build(builder: &CircuitBuilder, max_len: usize) -> Self {
+        Self(KeccakSponge::new(builder, max_len, 72, KECCAK_PAD, 64))
+    }
+
+    fn max_len(&self) -> usize {
+        self.0.len
+    }
+
+    // the message length is fixed when the circuit is built
+    fn check_len(&self, msg: &[u8]) -> Result<(), ProofError> {
+        check_exact_len(msg, self.0.len)
+    }
+
+    fn populate_with_digest(&self, witness: &mut WitnessFiller, msg: &[u8], digest: &[u8]) {
+        self.0.populate(witness, msg, digest);
+    }
+
+    fn digest_from_public(&self, public: &[Word]) -> Vec<u8> {
+        public_digest(public, 8 * self.0.digest.len(), WordEndian::Little)
+    }
+
+    fn reference_digest(msg: &[u8]) -> Vec<u8> {
+        CpuKeccak512::digest(msg).to_vec()
+    }
+}
+
+pub fn sha3_256_circuit(
+    image_bytes: &[u8],
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    run_hash_circuit_with_config(image_bytes, config, Sha3_256Hash::build)
+}
+
+pub fn keccak512_circuit(
+    image_bytes: &[u8],
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    run_hash_circuit_with_config(image_bytes, config, Keccak512Hash::build)
+}
@@ -0,0 +1,78 @@
+use binius_circuits::sha512::Sha512;
+use binius_core::{constraint_system::ConstraintSystem, word::Word};
+use binius_frontend::{CircuitBuilder, Wire, WitnessFiller};
+use sha2::{Digest, Sha512 as StdSha512};
+
+use crate::config::RunConfig;
+use crate::error::ProofError;
+use crate::hash_circuit::{
+    HashCircuit, WordEndian, build_cs, public_digest, run_hash_circuit_with_config,
+};
+use crate::timings::CircuitTimings;
+
+/// The sha512 gadget together with the wire carrying the preimage length.
+pub struct Sha512Hash {
+    sha512: Sha512,
+    len_bytes: Wire,
+    max_len: usize,
+}
+
+impl HashCircuit for Sha512Hash {
+    const NAME: &'static str = "sha512";
+
+    fn build(builder: &CircuitBuilder, max_len: usize) -> Self {
+        // Make wires for message
+        // the gadget needs at least one message wire, an empty message still gets one
+        // zero word that the length wire marks as unused
+        let n_wires = max_len.div_ceil(8).max(1);
+
+        let message: Vec<_> = (0..n_wires).map(|_| builder.add_witness()).collect();
+
+        let commitment: [_; 8] = core::array::from_fn(|_| builder.add_inout());
+
+        // Build sha512 gadget, the 64-byte digest needs 8 commitment words
+        let len_bytes = builder.add_witness();
+
+        let sha512 = Sha512::new(builder, len_bytes, commitment, message);
+
+        Self {
+            sha512,
+            len_bytes,
+            max_len,
+        }
+    }
+
+    fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    fn populate_with_digest(&self, witness: &mut WitnessFiller, msg: &[u8], digest: &[u8]) {
+        witness[self.len_bytes] = Word(msg.len() as u64);
+        self.sha512.populate_message(witness, msg);
+
+        let mut digest_bytes = [0u8; 64];
+        digest_bytes.copy_from_slice(digest);
+        self.sha512.populate_digest(witness, digest_bytes);
+    }
+
+    fn digest_from_public(&self, public: &[Word]) -> Vec<u8> {
+        public_digest(public, 64, WordEndian::Big)
+    }
+
+    fn reference_digest(msg: &[u8]) -> Vec<u8> {
+        StdSha512::digest(msg).to_vec()
+    }
+}
+
+/// The sha512 constraint system for messages of up to `max_len` bytes, see
+/// `hash_circuit::build_cs`.
+pub fn build_cs_sha512(max_len: usize) -> ConstraintSystem {
+    build_cs::<Sha512Hash>(max_len)
+}
+
+pub fn sha512_circuit(
+    image_bytes: &[u8],
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    run_hash_circuit_with_config(image_bytes, config, Sha512Hash::build)
+}
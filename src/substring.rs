@@ -0,0 +1,207 @@
+//! SHA-256 of a message together with SHA-256 of one of its slices, for selective
+//! disclosure: the verifier learns the digest of the whole message and the digest of
+//! bytes `start..end`, and can check revealed slice bytes against the latter without
+//! seeing the rest of the message.
+//!
+//! Range selection: the range is fixed when the circuit is built, so it is part of the
+//! constraint system and the verifier rebuilds the circuit for the same range. With the
+//! range known, each slice message word is a fixed combination of at most two adjacent
+//! message words of the full message: for `start = 8q + r`,
+//! slice word `i` is `m[q + i] >> 8r ^ m[q + i + 1] << (64 - 8r)`, and only `m[q + i]`
+//! when `r = 0`. The last slice word is masked to the slice's remaining bytes. This costs
+//! a few shifts per slice word on top of the two gadgets, where a range chosen by the
+//! prover would need a multiplexer over every offset for each slice word.
+//!
+//! Both lengths are fixed at build time as well, so the message hashed by the first
+//! gadget is exactly the bytes the slice is taken from.
+
+use std::ops::Range;
+
+use binius_circuits::sha256::Sha256;
+use binius_core::word::Word;
+use binius_frontend::{CircuitBuilder, Wire, WitnessFiller};
+use sha2::{Digest, Sha256 as StdSha256};
+
+use crate::config::RunConfig;
+use crate::error::ProofError;
+use crate::hash_circuit::{
+    HashCircuit, WordEndian, check_exact_len, public_digest, run_hash_circuit_with_config,
+};
+use crate::timings::CircuitTimings;
+
+/// A sha256 gadget over a `len`-byte message and a second one over the message's bytes
+/// `range`, read from the same message wires. The digest is the 32-byte digest of the
+/// message followed by the 32-byte digest of the slice.
+pub struct Sha256SliceHash {
+    full: Sha256,
+    slice: Sha256,
+    len: usize,
+    range: Range<usize>,
+}
+
+impl Sha256SliceHash {
+    /// Build the gadgets for `len`-byte messages and the slice `range`, which must lie
+    /// within `0..len`.
+    pub fn new(builder: &CircuitBuilder, len: usize, range: Range<usize>) -> Self {
+        assert!(range.start <= range.end && range.end <= len);
+
+        // at least one message wire, as for `Sha256Hash`
+        let message: Vec<_> = (0..len.div_ceil(8).max(1))
+            .map(|_| builder.add_witness())
+            .collect();
+        let full_digest: [_; 4] = core::array::from_fn(|_| builder.add_inout());
+        let slice_digest: [_; 4] = core::array::from_fn(|_| builder.add_inout());
+
+        let zero = builder.add_constant_64(0);
+        let word = |i: usize| message.get(i).copied().unwrap_or(zero);
+        let (q, r) = (range.start / 8, range.start % 8);
+        let slice_len = range.len();
+        let slice_message: Vec<Wire> = (0..slice_len.div_ceil(8).max(1))
+            .map(|i| {
+                let w = if r == 0 {
+                    word(q + i)
+                } else {
+                    builder.bxor(
+                        builder.shr(word(q + i), 8 * r as u32),
+                        builder.shl(word(q + i + 1), 64 - 8 * r as u32),
+                    )
+                };
+                // bytes past the end of the slice are zero, as `populate_message` writes them
+                let n_bytes = slice_len.saturating_sub(8 * i).min(8);
+                if n_bytes == 8 {
+                    w
+                } else {
+                    builder.band(w, builder.add_constant_64((1 << (8 * n_bytes)) - 1))
+                }
+            })
+            .collect();
+
+        let full_len = builder.add_constant_64(len as u64);
+        let full = Sha256::new(builder, full_len, full_digest, message);
+        let slice_len_wire = builder.add_constant_64(slice_len as u64);
+        let slice = Sha256::new(builder, slice_len_wire, slice_digest, slice_message);
+
+        Self {
+            full,
+            slice,
+            len,
+            range,
+        }
+    }
+
+    fn reference(&self, msg: &[u8]) -> Vec<u8> {
+        [
+            StdSha256::digest(msg).to_vec(),
+            StdSha256::digest(&msg[self.range.clone()]).to_vec(),
+        ]
+        .concat()
+    }
+}
+
+impl HashCircuit for Sha256SliceHash {
+    const NAME: &'static str = "sha256-slice";
+
+    // the slice is the whole message
+    fn build(builder: &CircuitBuilder, max_len: usize) -> Self {
+        Self::new(builder, max_len, 0..max_len)
+    }
+
+    fn max_len(&self) -> usize {
+        self.len
+    }
+
+    // the message length is fixed, a shorter message would not contain the slice
+    fn check_len(&self, msg: &[u8]) -> Result<(), ProofError> {
+        check_exact_len(msg, self.len)
+    }
+
+    // the reference digest has to be computed for this circuit's range
+    fn expected_digest(&self, msg: &[u8]) -> Vec<u8> {
+        self.reference(msg)
+    }
+
+    fn populate_with_digest(&self, witness: &mut WitnessFiller, msg: &[u8], digest: &[u8]) {
+        self.full.populate_message(witness, msg);
+        // the slice gadget's message wires are computed from the full message's, this
+        // writes the same values as `populate_wire_witness` does, as for `Sha256dHash`
+        self.slice
+            .populate_message(witness, &msg[self.range.clone()]);
+
+        let (full_digest, slice_digest) = digest.split_at(32);
+        self.full
+            .populate_digest(witness, full_digest.try_into().unwrap());
+        self.slice
+            .populate_digest(witness, slice_digest.try_into().unwrap());
+    }
+
+    // the full message's digest words, then the slice's, both big-endian like sha256's
+    fn digest_from_public(&self, public: &[Word]) -> Vec<u8> {
+        public_digest(public, 64, WordEndian::Big)
+    }
+
+    fn reference_digest(msg: &[u8]) -> Vec<u8> {
+        let digest = StdSha256::digest(msg).to_vec();
+        [digest.clone(), digest].concat()
+    }
+}
+
+/// Prove sha256(m) = h1 and sha256(m[range]) = h2 for `image_bytes` in one proof.
+pub fn substring_circuit(
+    image_bytes: &[u8],
+    range: Range<usize>,
+    config: &RunConfig,
+) -> Result<CircuitTimings, ProofError> {
+    if range.start > range.end || range.end > image_bytes.len() {
+        return Err(ProofError::InvalidParameter(format!(
+            "slice {range:?} is not within the {}-byte message",
+            image_bytes.len()
+        )));
+    }
+
+    run_hash_circuit_with_config(image_bytes, config, |builder, len| {
+        Sha256SliceHash::new(builder, len, range)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use binius_core::verify::verify_constraints;
+
+    #[test]
+    fn slice_digest_matches_reference() {
+        let msg: Vec<u8> = (0..100).collect();
+        // aligned and unaligned starts, a slice ending mid-word, empty and whole slices
+        for range in [0..100, 8..24, 3..20, 13..100, 50..50, 7..8] {
+            let builder = CircuitBuilder::new();
+            let hash = Sha256SliceHash::new(&builder, msg.len(), range.clone());
+            let circuit = builder.build();
+
+            let mut witness = circuit.new_witness_filler();
+            hash.populate(&mut witness, &msg);
+            circuit
+                .populate_wire_witness(&mut witness)
+                .unwrap_or_else(|e| panic!("range {range:?}: {e:?}"));
+            verify_constraints(circuit.constraint_system(), &witness.into_value_vec())
+                .unwrap_or_else(|e| panic!("range {range:?}: {e:?}"));
+        }
+    }
+
+    #[test]
+    fn wrong_slice_digest_is_rejected() {
+        let msg: Vec<u8> = (0..40).collect();
+        let builder = CircuitBuilder::new();
+        let hash = Sha256SliceHash::new(&builder, msg.len(), 5..17);
+        let circuit = builder.build();
+
+        // the digest of a neighbouring slice
+        let mut digest = StdSha256::digest(&msg).to_vec();
+        digest.extend(StdSha256::digest(&msg[6..18]));
+        let mut witness = circuit.new_witness_filler();
+        hash.populate_with_digest(&mut witness, &msg, &digest);
+
+        let rejected = circuit.populate_wire_witness(&mut witness).is_err()
+            || verify_constraints(circuit.constraint_system(), &witness.into_value_vec()).is_err();
+        assert!(rejected);
+    }
+}
@@ -0,0 +1,97 @@
+use binius_core::constraint_system::ConstraintSystem;
+use serde::Serialize;
+
+use std::fmt;
+
+/// Wall-clock time spent in each phase of a circuit run, in milliseconds, the size of
+/// the resulting proof and the size of the circuit it was made for.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct CircuitTimings {
+    pub setup_ms: u128,
+    pub prove_ms: u128,
+    pub verify_ms: u128,
+    pub constraint_check_ms: u128,
+    /// Length of the serialized proof from `ProverTranscript::finalize`.
+    pub proof_bytes: usize,
+    /// AND and MUL constraints in the constraint system.
+    pub n_constraints: usize,
+    /// Private words in the value vector, witness and internal wires.
+    pub n_witness: usize,
+    /// Public words in the value vector, constants and inout wires.
+    pub n_public: usize,
+    /// Peak resident set size of the process right after proving, see
+    /// `mem::peak_rss_bytes`.
+    pub peak_rss_bytes: Option<u64>,
+    /// The `log_inv_rate` prover and verifier were set up with, see
+    /// `hash_circuit::DEFAULT_LOG_INV_RATE`. 0 for a constraint check only.
+    pub log_inv_rate: usize,
+}
+
+impl CircuitTimings {
+    /// Average of each phase over several runs.
+    pub fn mean(runs: &[CircuitTimings]) -> CircuitTimings {
+        let n = runs.len().max(1) as u128;
+        CircuitTimings {
+            setup_ms: runs.iter().map(|t| t.setup_ms).sum::<u128>() / n,
+            prove_ms: runs.iter().map(|t| t.prove_ms).sum::<u128>() / n,
+            verify_ms: runs.iter().map(|t| t.verify_ms).sum::<u128>() / n,
+            constraint_check_ms: runs.iter().map(|t| t.constraint_check_ms).sum::<u128>() / n,
+            proof_bytes: runs.iter().map(|t| t.proof_bytes).sum::<usize>() / n as usize,
+            // a high-water mark, so the largest one is the peak over all runs
+            peak_rss_bytes: runs.iter().filter_map(|t| t.peak_rss_bytes).max(),
+            // the circuit is the same in every run
+            ..runs.first().copied().unwrap_or_default()
+        }
+    }
+
+    /// Proving throughput for an `input_bytes` message in MB/s (10^6 bytes), from
+    /// `prove_ms`. `None` if the proof took under a millisecond, too fast to measure.
+    pub fn throughput_mbps(&self, input_bytes: usize) -> Option<f64> {
+        (self.prove_ms > 0).then(|| input_bytes as f64 / (self.prove_ms as f64 * 1000.0))
+    }
+
+    /// Fill in the circuit size fields from `cs`.
+    pub fn record_circuit_stats(&mut self, cs: &ConstraintSystem) {
+        let layout = &cs.value_vec_layout;
+        self.n_constraints = cs.and_constraints.len() + cs.mul_constraints.len();
+        self.n_witness = layout.n_witness + layout.n_internal;
+        self.n_public = layout.n_const + layout.n_inout;
+    }
+}
+
+/// Minimum, median and maximum of one phase over several runs, in milliseconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Spread {
+    pub min: u128,
+    pub median: u128,
+    pub max: u128,
+}
+
+impl Spread {
+    /// The spread of `values`, all zero if there are none. With an even count the
+    /// median is the mean of the two middle values.
+    pub fn of(values: impl IntoIterator<Item = u128>) -> Spread {
+        let mut values: Vec<_> = values.into_iter().collect();
+        if values.is_empty() {
+            return Spread::default();
+        }
+        values.sort_unstable();
+        let n = values.len();
+        let median = if n % 2 == 1 {
+            values[n / 2]
+        } else {
+            (values[n / 2 - 1] + values[n / 2]) / 2
+        };
+        Spread {
+            min: values[0],
+            median,
+            max: values[n - 1],
+        }
+    }
+}
+
+impl fmt::Display for Spread {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{}ms", self.min, self.median, self.max)
+    }
+}
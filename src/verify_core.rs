@@ -0,0 +1,73 @@
+//! The verify path with nothing but the verifier itself: no timers, no printing, no
+//! logging and no file access, written against `core` and `alloc` only, for embedded
+//! and other constrained verifiers.
+//!
+//! Behind the `verify-core` feature. The crate as a whole still links `std`, and so does this
+//! path through its dependencies:
+//!
+//! - `binius_frontend`, to rebuild the constraint system with `CircuitBuilder`, and
+//!   `binius_verifier` and `binius_transcript` are `std` crates at the pinned revision.
+//! - `ProofError::SetupFailed` and `VerifyFailed` box a `std::error::Error`.
+//! - rayon, serde and the reference hash crates are plain dependencies, not optional.
+//!
+//! What this module removes is the crate's own use of `std`: `std::time::Instant` and
+//! `println!` in `hash_circuit::verify_hash` and `log` in its circuit build. Once the
+//! binius crates build without `std`, this is the part that can go along.
+
+use alloc::vec::Vec;
+
+use binius_core::word::Word;
+use binius_frontend::CircuitBuilder;
+use binius_transcript::VerifierTranscript;
+use binius_verifier::config::StdChallenger;
+
+use crate::error::ProofError;
+use crate::hash_circuit::{HashCircuit, setup_verifier};
+
+/// Verify a proof produced by `prove_hash_with_rate` for `H` built for messages of up to
+/// `max_len` bytes, `log_inv_rate` as it was proven with. The same check as
+/// `verify_hash_with_rate`, without its timings and output.
+pub fn verify_hash_core<H: HashCircuit>(
+    max_len: usize,
+    log_inv_rate: usize,
+    proof: Vec<u8>,
+    public_words: &[Word],
+) -> Result<(), ProofError> {
+    let builder = CircuitBuilder::new();
+    H::build(&builder, max_len);
+    let circuit = builder.build();
+
+    let verifier =
+        setup_verifier(&circuit, log_inv_rate).map_err(|e| e.in_circuit(H::NAME, max_len))?;
+    let mut transcript = VerifierTranscript::new(StdChallenger::default(), proof);
+    verifier.verify(public_words, &mut transcript)?;
+    transcript.finalize()?;
+    Ok(())
+}
+
+#[cfg(all(test, feature = "sha256"))]
+mod tests {
+    use super::*;
+    use crate::hash_circuit::{DEFAULT_LOG_INV_RATE, prove_hash};
+    use crate::sha256::Sha256Hash;
+
+    #[test]
+    fn accepts_proof_and_rejects_tampered_words() {
+        let msg = b"verify core";
+        let (proof, mut public_words, _) = prove_hash::<Sha256Hash>(msg, false).unwrap();
+        verify_hash_core::<Sha256Hash>(
+            msg.len(),
+            DEFAULT_LOG_INV_RATE,
+            proof.clone(),
+            &public_words,
+        )
+        .unwrap();
+
+        let last = public_words.last_mut().unwrap();
+        *last = Word(last.0 ^ 1);
+        assert!(
+            verify_hash_core::<Sha256Hash>(msg.len(), DEFAULT_LOG_INV_RATE, proof, &public_words)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,161 @@
+// Prove and verify every hash circuit at lengths around the common block sizes, to
+// exercise the padding boundaries a single demo size misses.
+//
+// Each proof is verified from separate state, as a prover and a verifier in different
+// processes would: the prover side hands over only the proof bytes and the public words
+// as plain integers, everything it built is dropped, and the verifier rebuilds the
+// constraint system and sets itself up from the message length alone.
+
+use binius_core::word::Word;
+use sha256_example::hash_circuit::{HashCircuit, digest_only, prove_hash, verify_hash};
+
+// 64 bytes is the sha256, blake2s, blake3, ripemd160 and md5 block, 128 the sha512 and
+// blake2b block; the keccak rates (72 to 144 bytes) fall between 65 and 1000.
+const LENS: [usize; 7] = [0, 1, 63, 64, 65, 128, 1000];
+
+// Everything that crosses from prover to verifier.
+struct Transmitted {
+    proof: Vec<u8>,
+    public_words: Vec<u64>,
+}
+
+impl Transmitted {
+    fn new(proof: Vec<u8>, public_words: &[Word]) -> Self {
+        Self {
+            proof,
+            public_words: public_words.iter().map(|w| w.0).collect(),
+        }
+    }
+
+    fn public_words(&self) -> Vec<Word> {
+        self.public_words.iter().copied().map(Word).collect()
+    }
+}
+
+fn prove_only<H: HashCircuit>(msg: &[u8]) -> Transmitted {
+    let (proof, public_words, _) =
+        prove_hash::<H>(msg, false).unwrap_or_else(|e| panic!("{} {}: {e}", H::NAME, msg.len()));
+    Transmitted::new(proof, &public_words)
+}
+
+fn prove_all_lengths<H: HashCircuit>() {
+    for len in LENS {
+        let msg: Vec<u8> = (0..len).map(|i| (i * 31 + 7) as u8).collect();
+        let transmitted = prove_only::<H>(&msg);
+        let public_words = transmitted.public_words();
+        verify_hash::<H>(len, transmitted.proof, &public_words, false)
+            .unwrap_or_else(|e| panic!("{} {len}: {e}", H::NAME));
+
+        // the digest as read back from the circuit's public words
+        let digest = digest_only::<H>(&msg).unwrap_or_else(|e| panic!("{} {len}: {e}", H::NAME));
+        assert_eq!(digest, H::reference_digest(&msg), "{} {len}", H::NAME);
+    }
+}
+
+// The lattice circuit is not a `HashCircuit` and grows with m * n, so it is proven for
+// a few short images only.
+#[cfg(feature = "lattice")]
+#[test]
+fn lattice() {
+    use sha256_example::lattice::{lattice_prove, verify_lattice};
+
+    for len in [0, 1, 8] {
+        let msg: Vec<u8> = (0..len).map(|i| (i * 31 + 7) as u8).collect();
+        let (proof, public_words) =
+            lattice_prove(&msg, Some(0)).unwrap_or_else(|e| panic!("lattice {len}: {e}"));
+        let transmitted = Transmitted::new(proof, &public_words);
+        let public_words = transmitted.public_words();
+        verify_lattice(len, transmitted.proof, &public_words)
+            .unwrap_or_else(|e| panic!("lattice {len}: {e}"));
+    }
+}
+
+#[cfg(feature = "sha256")]
+#[test]
+fn sha256() {
+    prove_all_lengths::<sha256_example::sha256::Sha256Hash>();
+}
+
+#[cfg(feature = "sha256")]
+#[test]
+fn sha256d() {
+    prove_all_lengths::<sha256_example::sha256::Sha256dHash>();
+}
+
+#[cfg(feature = "sha512")]
+#[test]
+fn sha512() {
+    prove_all_lengths::<sha256_example::sha512::Sha512Hash>();
+}
+
+#[cfg(feature = "sha2-truncated")]
+#[test]
+fn sha224() {
+    prove_all_lengths::<sha256_example::sha2_truncated::Sha224Hash>();
+}
+
+#[cfg(feature = "sha2-truncated")]
+#[test]
+fn sha384() {
+    prove_all_lengths::<sha256_example::sha2_truncated::Sha384Hash>();
+}
+
+#[cfg(feature = "keccak")]
+#[test]
+fn keccak() {
+    prove_all_lengths::<sha256_example::keccak::Keccak256Hash>();
+}
+
+#[cfg(all(feature = "sha256", feature = "keccak"))]
+#[test]
+fn sha256_keccak() {
+    prove_all_lengths::<sha256_example::multi_hash::Sha256KeccakHash>();
+}
+
+#[cfg(feature = "sha3")]
+#[test]
+fn sha3_256() {
+    prove_all_lengths::<sha256_example::sha3::Sha3_256Hash>();
+}
+
+#[cfg(feature = "sha3")]
+#[test]
+fn keccak512() {
+    prove_all_lengths::<sha256_example::sha3::Keccak512Hash>();
+}
+
+#[cfg(feature = "blake2b")]
+#[test]
+fn blake2b() {
+    prove_all_lengths::<sha256_example::blake2b::Blake2bHash>();
+}
+
+#[cfg(feature = "blake2s")]
+#[test]
+fn blake2s() {
+    prove_all_lengths::<sha256_example::blake2s::Blake2sHash>();
+}
+
+#[cfg(feature = "blake3")]
+#[test]
+fn blake3() {
+    prove_all_lengths::<sha256_example::blake3::Blake3Hash>();
+}
+
+#[cfg(feature = "ripemd")]
+#[test]
+fn ripemd160() {
+    prove_all_lengths::<sha256_example::ripemd::Ripemd160Hash>();
+}
+
+#[cfg(feature = "ripemd")]
+#[test]
+fn hash160() {
+    prove_all_lengths::<sha256_example::ripemd::Hash160Hash>();
+}
+
+#[cfg(feature = "legacy")]
+#[test]
+fn md5() {
+    prove_all_lengths::<sha256_example::md5::Md5Hash>();
+}